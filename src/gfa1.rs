@@ -1,9 +1,13 @@
-pub use crate::gfa2::{orientation::*, traits::*};
-use crate::tag::*;
+pub use crate::gfa2::{orientation::*, traits::*, CustomRecord, Newline, WriteOptions};
+use crate::{
+    gfa2::cigar::Cigar,
+    parser_gfa2::{GFAVersion, ParseFieldError},
+    tag::*,
+};
 
 use bstr::{BStr, BString, ByteSlice};
 use serde::{Deserialize, Serialize};
-use std::fmt;
+use std::{collections::HashMap, fmt};
 
 /// This module defines the various GFA line types, the GFA object,
 /// and some utility functions and types.
@@ -33,47 +37,42 @@ use std::fmt;
 ///     ],
 /// };
 /// ```
-#[derive(Default, Debug, Clone, PartialEq, PartialOrd)]
+#[derive(Default, Debug, Clone, PartialEq, Eq, PartialOrd, Serialize, Deserialize, Hash)]
 pub struct GFA<N, T: OptFields> {
     pub headers: Vec<Header<T>>,
     pub segments: Vec<Segment<N, T>>,
     pub links: Vec<Link<N, T>>,
     pub containments: Vec<Containment<N, T>>,
     pub paths: Vec<Path<N, T>>,
+    /// The comment lines, without the leading `#`, only stored if
+    /// the parser keeps them
+    pub comments: Vec<BString>,
+    /// The lines of unknown types, only stored if the parser keeps them
+    pub custom_records: Vec<CustomRecord>,
 }
 
+/// Writes every line followed by a newline, so an empty GFA (e.g. a
+/// new one) is written as an empty string, without a default header
 impl<N: SegmentId, T: OptFields> fmt::Display for GFA<N, T> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(
-            f,
-            "{}{}{}{}{}",
-            self.headers
-                .iter()
-                .fold(String::new(), |acc, str| acc + &str.to_string() + "\n"),
-            self.segments
-                .iter()
-                .fold(String::new(), |acc, str| acc + &str.to_string() + "\n"),
-            self.links
-                .iter()
-                .fold(String::new(), |acc, str| acc + &str.to_string() + "\n"),
-            self.containments
-                .iter()
-                .fold(String::new(), |acc, str| acc + &str.to_string() + "\n"),
-            self.paths
-                .iter()
-                .fold(String::new(), |acc, str| acc + &str.to_string() + "\n"),
-        )
+        for line in self.lines_iter() {
+            writeln!(f, "{}", line)?;
+        }
+        Ok(())
     }
 }
 
 /// Enum containing the different kinds of GFA lines.
-#[derive(Debug, Clone, PartialEq, PartialOrd)]
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Serialize, Deserialize, Hash)]
 pub enum Line<N, T: OptFields> {
     Header(Header<T>),
     Segment(Segment<N, T>),
     Link(Link<N, T>),
     Containment(Containment<N, T>),
     Path(Path<N, T>),
+    /// A comment line, without the leading `#`
+    Comment(BString),
+    CustomRecord(CustomRecord),
 }
 
 macro_rules! some_line_fn {
@@ -95,6 +94,30 @@ some_line_fn!(some_segment, Segment<N, T>, Line::Segment);
 some_line_fn!(some_link, Link<N, T>, Line::Link);
 some_line_fn!(some_containment, Containment<N, T>, Line::Containment);
 some_line_fn!(some_path, Path<N, T>, Line::Path);
+some_line_fn!(some_comment, BString, Line::Comment);
+some_line_fn!(some_custom_record, CustomRecord, Line::CustomRecord);
+
+impl<N, T: OptFields> Line<N, T> {
+    /// Borrows the line as a LineRef, without cloning it
+    pub fn as_ref(&self) -> LineRef<'_, N, T> {
+        use Line::*;
+        match self {
+            Header(x) => LineRef::Header(x),
+            Segment(x) => LineRef::Segment(x),
+            Link(x) => LineRef::Link(x),
+            Containment(x) => LineRef::Containment(x),
+            Path(x) => LineRef::Path(x),
+            Comment(x) => LineRef::Comment(x),
+            CustomRecord(x) => LineRef::CustomRecord(x),
+        }
+    }
+}
+
+impl<'a, N, T: OptFields> From<&'a Line<N, T>> for LineRef<'a, N, T> {
+    fn from(line: &'a Line<N, T>) -> Self {
+        line.as_ref()
+    }
+}
 
 macro_rules! some_line_ref_fn {
     ($name:ident, $tgt:ty, $variant:path) => {
@@ -115,14 +138,32 @@ some_line_ref_fn!(some_segment, Segment<N, T>, LineRef::Segment);
 some_line_ref_fn!(some_link, Link<N, T>, LineRef::Link);
 some_line_ref_fn!(some_containment, Containment<N, T>, LineRef::Containment);
 some_line_ref_fn!(some_path, Path<N, T>, LineRef::Path);
+some_line_ref_fn!(some_comment, BString, LineRef::Comment);
+some_line_ref_fn!(some_custom_record, CustomRecord, LineRef::CustomRecord);
 
-#[derive(Debug, Clone, PartialEq, PartialOrd)]
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Serialize, Hash)]
 pub enum LineRef<'a, N, T: OptFields> {
     Header(&'a Header<T>),
     Segment(&'a Segment<N, T>),
     Link(&'a Link<N, T>),
     Containment(&'a Containment<N, T>),
     Path(&'a Path<N, T>),
+    Comment(&'a BString),
+    CustomRecord(&'a CustomRecord),
+}
+
+impl<'a, N: SegmentId, T: OptFields> fmt::Display for LineRef<'a, N, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LineRef::Header(h) => write!(f, "{}", h),
+            LineRef::Segment(s) => write!(f, "{}", s),
+            LineRef::Link(l) => write!(f, "{}", l),
+            LineRef::Containment(c) => write!(f, "{}", c),
+            LineRef::Path(p) => write!(f, "{}", p),
+            LineRef::Comment(c) => write!(f, "#{}", c),
+            LineRef::CustomRecord(r) => write!(f, "{}", r),
+        }
+    }
 }
 
 impl<N, T: OptFields> GFA<N, T> {
@@ -138,12 +179,33 @@ impl<N, T: OptFields> GFA<N, T> {
             Link(s) => self.links.push(s),
             Containment(s) => self.containments.push(s),
             Path(s) => self.paths.push(s),
+            Comment(c) => self.comments.push(c),
+            CustomRecord(r) => self.custom_records.push(r),
+        }
+    }
+
+    /// Appends all the lines of ```other``` to the graph, keeping only
+    /// the headers of the graph (or the ones of ```other```, if the
+    /// graph has none).\
+    /// The segment names are not checked
+    pub fn merge(&mut self, other: GFA<N, T>) {
+        if self.headers.is_empty() {
+            self.headers = other.headers;
         }
+        self.segments.extend(other.segments);
+        self.links.extend(other.links);
+        self.containments.extend(other.containments);
+        self.paths.extend(other.paths);
+        self.comments.extend(other.comments);
+        self.custom_records.extend(other.custom_records);
     }
 
     /// Consume a GFA object to produce an iterator over all the lines
-    /// contained within. The iterator first produces all segments, then
-    /// links, then containments, and finally paths.
+    /// contained within. The iterator first produces all headers, then
+    /// segments, links, containments, paths, comments and finally
+    /// custom records.\
+    /// The lines are grouped by kind, so a comment doesn't keep its
+    /// position among the other lines of the parsed file
     pub fn lines_into_iter(self) -> impl Iterator<Item = Line<N, T>> {
         use Line::*;
         let heads = self.headers.into_iter().map(Header);
@@ -151,11 +213,21 @@ impl<N, T: OptFields> GFA<N, T> {
         let links = self.links.into_iter().map(Link);
         let conts = self.containments.into_iter().map(Containment);
         let paths = self.paths.into_iter().map(Path);
-
-        heads.chain(segs).chain(links).chain(conts).chain(paths)
+        let comments = self.comments.into_iter().map(Comment);
+        let records = self.custom_records.into_iter().map(CustomRecord);
+
+        heads
+            .chain(segs)
+            .chain(links)
+            .chain(conts)
+            .chain(paths)
+            .chain(comments)
+            .chain(records)
     }
 
-    /// Return an iterator over references to the lines in the GFA
+    /// Return an iterator over references to the lines in the GFA, in
+    /// the same order of lines_into_iter (so the comments come after
+    /// all the paths)
     pub fn lines_iter(&'_ self) -> impl Iterator<Item = LineRef<'_, N, T>> {
         use LineRef::*;
         let heads = self.headers.iter().map(Header);
@@ -163,8 +235,16 @@ impl<N, T: OptFields> GFA<N, T> {
         let links = self.links.iter().map(Link);
         let conts = self.containments.iter().map(Containment);
         let paths = self.paths.iter().map(Path);
-
-        heads.chain(segs).chain(links).chain(conts).chain(paths)
+        let comments = self.comments.iter().map(Comment);
+        let records = self.custom_records.iter().map(CustomRecord);
+
+        heads
+            .chain(segs)
+            .chain(links)
+            .chain(conts)
+            .chain(paths)
+            .chain(comments)
+            .chain(records)
     }
 }
 
@@ -172,6 +252,115 @@ impl<N: SegmentId, T: OptFields> GFA<N, T> {
     pub fn new() -> Self {
         Default::default()
     }
+
+    /// Returns an iterator over the names of the segments, in the order
+    /// they were inserted, without cloning them
+    pub fn segment_ids_iter(&self) -> impl Iterator<Item = &N> {
+        self.segments.iter().map(|s| &s.name)
+    }
+
+    /// Returns the names of the segments, in the order they were inserted
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// let parser: GFAParser<BString, ()> = GFAParser::new();
+    /// let gfa = parser.parse_file("./tests/gfa1_files/lil.gfa").unwrap();
+    ///
+    /// let names: HashSet<_> = gfa.segment_ids().into_iter().collect();
+    /// ```
+    pub fn segment_ids(&self) -> Vec<&N> {
+        self.segment_ids_iter().collect()
+    }
+
+    /// Writes the lines of the GFA object, in the same order as
+    /// ```lines_iter```, ending them as set in the options
+    pub fn write_with<W: std::io::Write>(
+        &self,
+        out: &mut W,
+        options: &WriteOptions,
+    ) -> std::io::Result<()> {
+        crate::gfa2::write_lines(out, self.lines_iter(), options)
+    }
+
+    /// Writes every line followed by a newline, as the Display of the
+    /// GFA object but without building the whole text in memory first
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// let mut file = BufWriter::new(File::create("graph.gfa")?);
+    /// gfa.write_to(&mut file)?;
+    /// ```
+    pub fn write_to<W: std::io::Write>(&self, out: &mut W) -> std::io::Result<()> {
+        let options = WriteOptions {
+            newline: Newline::Lf,
+            trailing_newline: true,
+        };
+        self.write_with(out, &options)
+    }
+}
+
+impl<N: SegmentId + PartialEq, T: OptFields> GFA<N, T> {
+    /// Like insert_line, but a segment whose name is already used by
+    /// another segment, or a link with the same segments, orientations
+    /// and overlap of another link (whatever its tags), is not inserted.
+    /// Returns if the line has been inserted.\
+    /// Every other kind of line is always inserted.\
+    /// The existing lines are scanned at every insertion
+    pub fn insert_line_dedup(&mut self, line: Line<N, T>) -> bool {
+        let duplicate = match &line {
+            Line::Segment(s) => self.segments.iter().any(|x| x.name == s.name),
+            Line::Link(l) => self.links.iter().any(|x| {
+                x.from_segment == l.from_segment
+                    && x.from_orient == l.from_orient
+                    && x.to_segment == l.to_segment
+                    && x.to_orient == l.to_orient
+                    && x.overlap == l.overlap
+            }),
+            _ => false,
+        };
+        if !duplicate {
+            self.insert_line(line);
+        }
+        !duplicate
+    }
+}
+
+impl<N: SegmentId + PartialOrd, T: OptFields + PartialOrd> GFA<N, T> {
+    /// Sorts every kind of line by its fields (the headers with a
+    /// version first), so that the same lines are always displayed in
+    /// the same order, whatever their order in the source file.\
+    /// The optional fields of every line are sorted by their tag too.
+    pub fn sort_canonical(&mut self) {
+        use crate::gfa2::canonical_order;
+
+        macro_rules! sort_tags {
+            ($($lines:expr),*) => {
+                $(for line in $lines.iter_mut() {
+                    line.optional = line.optional.sorted();
+                })*
+            };
+        }
+        sort_tags!(
+            self.headers,
+            self.segments,
+            self.links,
+            self.containments,
+            self.paths
+        );
+
+        self.headers.sort_by(|a, b| {
+            b.version
+                .is_some()
+                .cmp(&a.version.is_some())
+                .then_with(|| canonical_order(a, b))
+        });
+        self.segments.sort_by(canonical_order);
+        self.links.sort_by(canonical_order);
+        self.containments.sort_by(canonical_order);
+        self.paths.sort_by(canonical_order);
+    }
 }
 
 /// The header line of a GFA graph
@@ -186,7 +375,7 @@ impl<N: SegmentId, T: OptFields> GFA<N, T> {
 ///     optional: (),
 /// };
 /// ```
-#[derive(Debug, Clone, PartialEq, PartialOrd)]
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Serialize, Deserialize, Hash)]
 pub struct Header<T: OptFields> {
     pub version: Option<BString>,
     pub optional: T,
@@ -208,30 +397,25 @@ impl<T: OptFields> Header<T> {
             optional: Default::default(),
         }
     }
+
+    /// Returns the version of the format declared by the header, if
+    /// it has one, whether the version has been stored as the whole
+    /// tag (```VN:Z:1.0```) or only as its value (```1.0```)
+    pub fn gfa_version(&self) -> Option<GFAVersion> {
+        self.version
+            .as_ref()
+            .map(|version| GFAVersion::from_version(version))
+    }
 }
 
 impl<T: OptFields> fmt::Display for Header<T> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        let mut opt = vec![];
-        for tag in self.optional.fields() {
-            opt.push(tag);
-        }
+        // an header without version can still have some optional tags
+        write!(f, "H")?;
         if let Some(v) = &self.version {
-            write!(
-                f,
-                "H\t{}\t{}",
-                v,
-                opt.iter()
-                    .fold(String::new(), |acc, str| acc + &str.to_string() + "\t"),
-            )
-        } else {
-            write!(
-                f,
-                "H\t{}",
-                opt.iter()
-                    .fold(String::new(), |acc, str| acc + &str.to_string() + "\t"),
-            )
+            write!(f, "\t{}", v)?;
         }
+        write_tags(f, self.optional.fields())
     }
 }
 
@@ -249,7 +433,7 @@ impl<T: OptFields> fmt::Display for Header<T> {
 ///     optional:(),
 /// };
 /// ```
-#[derive(Default, Debug, Clone, PartialEq, PartialOrd, Serialize, Deserialize, Hash)]
+#[derive(Default, Debug, Clone, PartialEq, Eq, PartialOrd, Serialize, Deserialize, Hash)]
 pub struct Segment<N, T: OptFields> {
     pub name: N,
     pub sequence: BString,
@@ -266,20 +450,41 @@ impl<T: OptFields> Segment<BString, T> {
     }
 }
 
+impl<N, T: OptFields> Segment<N, T> {
+    /// Checks if the segment has a sequence, i.e. it's not the ```*```
+    /// placeholder
+    pub fn has_sequence(&self) -> bool {
+        self.sequence != "*"
+    }
+
+    /// Returns the number of ```A```, ```C```, ```G```, ```T``` and other
+    /// bases of the sequence (see base_counts), all 0 if the sequence is
+    /// ```*```
+    pub fn base_counts(&self) -> [u64; 5] {
+        if self.has_sequence() {
+            base_counts(&self.sequence)
+        } else {
+            [0; 5]
+        }
+    }
+
+    /// Returns the fraction of ```G``` and ```C``` bases among the
+    /// ```ACGT``` ones of the sequence, ignoring their case (see
+    /// gc_content), or None if the sequence is ```*``` or has no
+    /// ```ACGT``` bases
+    pub fn gc_content(&self) -> Option<f64> {
+        if self.has_sequence() {
+            gc_content(&self.sequence)
+        } else {
+            None
+        }
+    }
+}
+
 impl<N: SegmentId, T: OptFields> fmt::Display for Segment<N, T> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        let mut opt = vec![];
-        for tag in self.optional.fields() {
-            opt.push(tag);
-        }
-        write!(
-            f,
-            "S\t{}\t{}\t{}",
-            self.name,
-            self.sequence.as_bstr(),
-            opt.iter()
-                .fold(String::new(), |acc, str| acc + &str.to_string() + "\t"),
-        )
+        write!(f, "S\t{}\t{}", self.name.to_name(), self.sequence.as_bstr())?;
+        write_tags(f, self.optional.fields())
     }
 }
 
@@ -298,7 +503,7 @@ impl<N: SegmentId, T: OptFields> fmt::Display for Segment<N, T> {
 ///     optional:(),
 /// };
 /// ```
-#[derive(Default, Debug, Clone, PartialEq, PartialOrd, Serialize, Deserialize, Hash)]
+#[derive(Default, Debug, Clone, PartialEq, Eq, PartialOrd, Serialize, Deserialize, Hash)]
 pub struct Link<N, T: OptFields> {
     pub from_segment: N,
     pub from_orient: Orientation,
@@ -327,23 +532,35 @@ impl<T: OptFields> Link<BString, T> {
     }
 }
 
+impl<N, T: OptFields> Link<N, T> {
+    /// Parses the overlap of the link as a CIGAR string, where the
+    /// ```*``` overlap is an empty CIGAR
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// let link: Link<BString, ()> =
+    ///     Link::new(b"15", Orientation::Backward, b"10", Orientation::Forward, b"4M1I");
+    /// let cigar = link.overlap_cigar().unwrap();
+    /// assert_eq!((4, 5), (cigar.reference_len(), cigar.query_len()));
+    /// ```
+    pub fn overlap_cigar(&self) -> Result<Cigar, ParseFieldError> {
+        Cigar::parse(&self.overlap)
+    }
+}
+
 impl<N: SegmentId, T: OptFields> fmt::Display for Link<N, T> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        let mut opt = vec![];
-        for tag in self.optional.fields() {
-            opt.push(tag);
-        }
         write!(
             f,
-            "L\t{}\t{}\t{}\t{}\t{}\t{}",
-            self.from_segment,
+            "L\t{}\t{}\t{}\t{}\t{}",
+            self.from_segment.to_name(),
             self.from_orient,
-            self.to_segment,
+            self.to_segment.to_name(),
             self.to_orient,
-            self.overlap,
-            opt.iter()
-                .fold(String::new(), |acc, str| acc + &str.to_string() + "\t"),
-        )
+            self.overlap
+        )?;
+        write_tags(f, self.optional.fields())
     }
 }
 
@@ -363,7 +580,7 @@ impl<N: SegmentId, T: OptFields> fmt::Display for Link<N, T> {
 ///     optional:(),
 /// };
 /// ```
-#[derive(Default, Debug, Clone, PartialEq, PartialOrd, Serialize, Deserialize, Hash)]
+#[derive(Default, Debug, Clone, PartialEq, Eq, PartialOrd, Serialize, Deserialize, Hash)]
 pub struct Containment<N, T: OptFields> {
     pub container_name: N,
     pub container_orient: Orientation,
@@ -374,24 +591,27 @@ pub struct Containment<N, T: OptFields> {
     pub optional: T,
 }
 
+impl<N, T: OptFields> Containment<N, T> {
+    /// Parses the overlap of the containment as a CIGAR string, where
+    /// the ```*``` overlap is an empty CIGAR
+    pub fn overlap_cigar(&self) -> Result<Cigar, ParseFieldError> {
+        Cigar::parse(&self.overlap)
+    }
+}
+
 impl<N: SegmentId, T: OptFields> fmt::Display for Containment<N, T> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        let mut opt = vec![];
-        for tag in self.optional.fields() {
-            opt.push(tag);
-        }
         write!(
             f,
-            "C\t{}\t{}\t{}\t{}\t{}\t{}\t{}",
-            self.container_name,
+            "C\t{}\t{}\t{}\t{}\t{}\t{}",
+            self.container_name.to_name(),
             self.container_orient,
-            self.contained_name,
+            self.contained_name.to_name(),
             self.contained_orient,
             self.pos,
-            self.overlap,
-            opt.iter()
-                .fold(String::new(), |acc, str| acc + &str.to_string() + "\t"),
-        )
+            self.overlap
+        )?;
+        write_tags(f, self.optional.fields())
     }
 }
 
@@ -410,7 +630,7 @@ impl<N: SegmentId, T: OptFields> fmt::Display for Containment<N, T> {
 ///     (),
 /// );
 /// ```
-#[derive(Default, Debug, Clone, PartialEq, PartialOrd, Serialize, Deserialize, Hash)]
+#[derive(Default, Debug, Clone, PartialEq, Eq, PartialOrd, Serialize, Deserialize, Hash)]
 pub struct Path<N, T: OptFields> {
     pub path_name: BString,
     pub segment_names: BString,
@@ -432,21 +652,28 @@ impl<N: SegmentId, T: OptFields> Path<N, T> {
 }
 
 impl<N: SegmentId, T: OptFields> Path<N, T> {
-    /// Parses (and copies!) a segment ID in the path segment list
+    /// Parses (and copies!) a segment ID in the path segment list,
+    /// None if it doesn't include the orientation
     fn parse_segment_id(input: &[u8]) -> Option<(N, Orientation)> {
-        use Orientation::*;
-        let last = input.len() - 1;
-        let orient = match input[last] {
-            b'+' => Forward,
-            b'-' => Backward,
-            _ => panic!("Path segment did not include orientation"),
-        };
-        let seg = &input[..last];
+        let (last, seg) = input.split_last()?;
+        let orient = Orientation::from_bytes_plus_minus([*last])?;
         let id = N::parse_id(seg)?;
         Some((id, orient))
     }
 }
 
+impl<N, T: OptFields> Path<N, T> {
+    /// Parses the comma separated overlaps of the path as CIGAR
+    /// strings, one for each pair of consecutive segments.\
+    /// The ```*``` overlaps field gives no CIGAR
+    pub fn overlap_cigars(&self) -> Result<Vec<Cigar>, ParseFieldError> {
+        if self.overlaps == "*" {
+            return Ok(vec![]);
+        }
+        self.overlaps.split_str(b",").map(Cigar::parse).collect()
+    }
+}
+
 impl<T: OptFields> Path<BString, T> {
     /// Produces an iterator over the segments of the given path,
     /// parsing the orientation and producing a slice to each segment
@@ -466,6 +693,37 @@ impl<T: OptFields> Path<BString, T> {
         let seg = &input[..last];
         (seg.as_ref(), orient)
     }
+
+    /// Returns the sequence spelled by the path, concatenating the
+    /// sequences of its segments, reverse complemented if the segment
+    /// is traversed backward.\
+    /// The overlaps are not removed, and the result is None if a
+    /// segment can't be parsed, is missing or has no sequence (```*```)
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// let segments: HashMap<BString, &Segment<BString, ()>> =
+    ///     gfa.segments.iter().map(|s| (s.name.clone(), s)).collect();
+    /// let sequence = gfa.paths[0].spell(&segments).unwrap();
+    /// ```
+    pub fn spell(&self, segments: &HashMap<BString, &Segment<BString, T>>) -> Option<BString> {
+        let mut sequence = BString::default();
+        for member in self.segment_names.split_str(b",") {
+            let (id, orient) = Self::parse_segment_id(member)?;
+            let segment = segments.get(&id)?;
+            if segment.sequence == "*" {
+                return None;
+            }
+            match orient {
+                Orientation::Forward => sequence.extend_from_slice(&segment.sequence),
+                Orientation::Backward => {
+                    sequence.extend_from_slice(&reverse_complement(&segment.sequence))
+                }
+            }
+        }
+        Some(sequence)
+    }
 }
 
 impl<T: OptFields> Path<usize, T> {
@@ -480,19 +738,14 @@ impl<T: OptFields> Path<usize, T> {
 
 impl<N: SegmentId, T: OptFields> fmt::Display for Path<N, T> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        let mut opt = vec![];
-        for tag in self.optional.fields() {
-            opt.push(tag);
-        }
         write!(
             f,
-            "P\t{}\t{}\t{}\t{}",
+            "P\t{}\t{}\t{}",
             self.path_name,
-            self.segment_names.as_bstr().to_string(),
-            self.overlaps.as_bstr().to_string(),
-            opt.iter()
-                .fold(String::new(), |acc, str| acc + &str.to_string() + "\t"),
-        )
+            self.segment_names.as_bstr(),
+            self.overlaps.as_bstr()
+        )?;
+        write_tags(f, self.optional.fields())
     }
 }
 
@@ -500,6 +753,56 @@ impl<N: SegmentId, T: OptFields> fmt::Display for Path<N, T> {
 mod tests {
     use super::*;
 
+    #[test]
+    fn segment_gc_content() {
+        let segment: Segment<BString, ()> = Segment::new(b"11", b"GGcaNtAC");
+        assert_eq!([2, 2, 2, 1, 1], segment.base_counts());
+        assert_eq!(Some(4.0 / 7.0), segment.gc_content());
+
+        let segment: Segment<BString, ()> = Segment::new(b"12", b"*");
+        assert_eq!([0; 5], segment.base_counts());
+        assert_eq!(None, segment.gc_content());
+    }
+
+    #[test]
+    fn can_merge_graphs() {
+        let mut gfa: GFA<BString, ()> = GFA::new();
+        gfa.headers.push(Header::new(Some("VN:Z:1.0".into())));
+        gfa.segments.push(Segment::new(b"11", b"ACGT"));
+        let mut other: GFA<BString, ()> = GFA::new();
+        other.headers.push(Header::new(Some("VN:Z:1.1".into())));
+        other.segments.push(Segment::new(b"12", b"AAC"));
+        other.comments.push(" merged".into());
+
+        let mut merged = gfa.clone();
+        merged.merge(other.clone());
+        assert_eq!(gfa.headers, merged.headers);
+        assert_eq!(2, merged.segments.len());
+        assert_eq!(1, merged.comments.len());
+
+        let mut merged: GFA<BString, ()> = GFA::new();
+        merged.merge(other.clone());
+        assert_eq!(other, merged);
+    }
+
+    #[test]
+    fn can_spell_path() {
+        let segments: Vec<Segment<BString, ()>> = vec![
+            Segment::new(b"11", b"ACGT"),
+            Segment::new(b"12", b"AAC"),
+            Segment::new(b"13", b"*"),
+        ];
+        let index: HashMap<BString, &Segment<BString, ()>> =
+            segments.iter().map(|s| (s.name.clone(), s)).collect();
+
+        let path: Path<BString, ()> = Path::new("14".into(), "11+,12-".into(), "*".into(), ());
+        assert_eq!(Some("ACGTGTT".into()), path.spell(&index));
+        let path: Path<BString, ()> = Path::new("15".into(), "11+,13+".into(), "*".into(), ());
+        assert_eq!(None, path.spell(&index));
+        let path: Path<BString, ()> = Path::new("16".into(), "11+,17-".into(), "*".into(), ());
+        assert_eq!(None, path.spell(&index));
+    }
+
     #[test]
     fn path_iter() {
         use Orientation::*;
@@ -514,6 +817,78 @@ mod tests {
         assert_eq!(None, path_iter.next());
     }
 
+    #[test]
+    fn can_parse_overlap_cigars() {
+        use crate::gfa2::cigar::CigarOp;
+        use Orientation::*;
+
+        let link: Link<BString, ()> = Link::new(b"15", Backward, b"10", Forward, b"4M1I");
+        let cigar = link.overlap_cigar().unwrap();
+        assert_eq!(Cigar(vec![(4, CigarOp::M), (1, CigarOp::I)]), cigar);
+        assert_eq!((4, 5), (cigar.reference_len(), cigar.query_len()));
+        assert_eq!("4M1I", cigar.to_string());
+
+        let path: Path<BString, ()> =
+            Path::new("14".into(), "11+,12-,13+".into(), "4M,5M".into(), ());
+        let overlaps = path.overlap_cigars().unwrap();
+        assert_eq!(
+            vec![4, 5],
+            overlaps.iter().map(|c| c.query_len()).collect::<Vec<_>>()
+        );
+
+        let path: Path<BString, ()> = Path::new("14".into(), "11+,12-".into(), "*".into(), ());
+        assert!(path.overlap_cigars().unwrap().is_empty());
+        let path: Path<BString, ()> = Path::new("14".into(), "11+,12-".into(), "4Q".into(), ());
+        assert!(path.overlap_cigars().is_err());
+    }
+
+    #[test]
+    fn can_insert_lines_without_duplicates() {
+        use Orientation::*;
+
+        let mut gfa: GFA<BString, ()> = GFA::new();
+        assert!(gfa.insert_line_dedup(Line::Segment(Segment::new(b"1", b"ACGT"))));
+        assert!(!gfa.insert_line_dedup(Line::Segment(Segment::new(b"1", b"TT"))));
+        assert!(gfa.insert_line_dedup(Line::Link(Link::new(b"1", Forward, b"2", Backward, b"2M"))));
+        assert!(!gfa.insert_line_dedup(Line::Link(Link::new(b"1", Forward, b"2", Backward, b"2M"))));
+        assert!(gfa.insert_line_dedup(Line::Link(Link::new(b"1", Forward, b"2", Forward, b"2M"))));
+        assert_eq!(1, gfa.segments.len());
+        assert_eq!(2, gfa.links.len());
+    }
+
+    #[test]
+    fn segment_ids() {
+        let parser: crate::parser_gfa1::GFAParser<BString, ()> =
+            crate::parser_gfa1::GFAParser::new();
+        let gfa = parser.parse_file(&"./tests/gfa1_files/lil.gfa").unwrap();
+
+        let ids = gfa.segment_ids();
+        assert_eq!(gfa.segments.len(), ids.len());
+        assert_eq!(Some(&&BString::from("1")), ids.first());
+        assert_eq!(Some(&&BString::from("15")), ids.last());
+    }
+
+    #[test]
+    fn can_write_with_crlf() {
+        let parser: crate::parser_gfa1::GFAParser<BString, ()> =
+            crate::parser_gfa1::GFAParser::new();
+        let gfa = parser.parse_file(&"./tests/gfa1_files/lil.gfa").unwrap();
+        let options = WriteOptions {
+            newline: Newline::CrLf,
+            trailing_newline: false,
+        };
+
+        let mut out = vec![];
+        gfa.write_with(&mut out, &options).unwrap();
+        let lines: Vec<_> = out.split_str("\r\n").collect();
+        assert_eq!(gfa.lines_iter().count(), lines.len());
+        assert!(!out.ends_with(b"\r\n"));
+
+        let mut out = vec![];
+        gfa.write_to(&mut out).unwrap();
+        assert_eq!(gfa.to_string(), out.to_str().unwrap());
+    }
+
     #[test]
     fn gfa_line_ref_iter() {
         let parser: crate::parser_gfa1::GFAParser<usize, ()> = crate::parser_gfa1::GFAParser::new();
@@ -525,4 +900,25 @@ mod tests {
             println!("{:?}", seg);
         }
     }
+
+    #[test]
+    fn canonical_sort_ignores_line_order() {
+        let parser: crate::parser_gfa1::GFAParser<BString, ()> =
+            crate::parser_gfa1::GFAParser::new();
+        let lines = [
+            "H\tVN:Z:1.0",
+            "S\t11\tACCTT",
+            "S\t12\tTCAAGG",
+            "L\t11\t+\t12\t-\t4M",
+            "L\t12\t-\t11\t+\t5M",
+            "P\t14\t11+,12-\t4M",
+        ];
+        let mut gfa = parser.parse_lines(lines.iter()).unwrap();
+        let mut shuffled = parser.parse_lines(lines.iter().rev()).unwrap();
+        assert_ne!(gfa, shuffled);
+
+        gfa.sort_canonical();
+        shuffled.sort_canonical();
+        assert_eq!(gfa, shuffled);
+    }
 }
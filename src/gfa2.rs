@@ -1,14 +1,29 @@
 //pub mod name_conversion;
+pub mod cigar;
 pub mod orientation;
+pub mod packed_seq;
+pub mod position;
+pub mod stats;
 pub mod traits;
 
+pub use self::cigar::*;
 pub use self::orientation::*;
+pub use self::packed_seq::*;
+pub use self::position::*;
 pub use self::traits::*;
 
-use crate::tag::*;
+use crate::{
+    parser_gfa2::{GFAVersion, ParseFieldError},
+    tag::*,
+};
 use bstr::{BStr, BString, ByteSlice};
 use serde::{Deserialize, Serialize};
-use std::fmt;
+use std::{
+    borrow::Cow,
+    collections::{HashMap, HashSet},
+    fmt,
+    hash::Hash,
+};
 
 /// Returns an Header line
 ///
@@ -24,7 +39,7 @@ use std::fmt;
 ///     tag: (),
 /// };
 /// ```
-#[derive(Debug, Clone, PartialEq, PartialOrd)]
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Serialize, Deserialize, Hash)]
 pub struct Header<T: OptFields> {
     pub version: Option<BString>,
     pub tag: T,
@@ -39,6 +54,17 @@ impl<T: OptFields> Header<T> {
     }
 }
 
+impl<T: OptFields> Header<T> {
+    /// Returns the version of the format declared by the header, if
+    /// it has one, whether the version has been stored as the whole
+    /// tag (```VN:Z:2.0```) or only as its value (```2.0```)
+    pub fn gfa_version(&self) -> Option<GFAVersion> {
+        self.version
+            .as_ref()
+            .map(|version| GFAVersion::from_version(version))
+    }
+}
+
 impl<T: OptFields> Default for Header<T> {
     fn default() -> Self {
         Header {
@@ -50,26 +76,12 @@ impl<T: OptFields> Default for Header<T> {
 
 impl<T: OptFields> fmt::Display for Header<T> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        let mut opt = vec![];
-        for tag in self.tag.fields() {
-            opt.push(tag);
-        }
+        // an header without version can still have some optional tags
+        write!(f, "H")?;
         if let Some(v) = &self.version {
-            write!(
-                f,
-                "H\t{}\t{}",
-                v,
-                opt.iter()
-                    .fold(String::new(), |acc, str| acc + &str.to_string() + "\t"),
-            )
-        } else {
-            write!(
-                f,
-                "H\t{}",
-                opt.iter()
-                    .fold(String::new(), |acc, str| acc + &str.to_string() + "\t"),
-            )
+            write!(f, "\t{}", v)?;
         }
+        write_tags(f, self.tag.fields())
     }
 }
 
@@ -90,11 +102,11 @@ impl<T: OptFields> fmt::Display for Header<T> {
 ///     tag:(),
 /// };
 /// ```
-#[derive(Default, Debug, Clone, PartialEq, PartialOrd, Serialize, Deserialize, Hash)]
+#[derive(Default, Debug, Clone, PartialEq, Eq, PartialOrd, Serialize, Deserialize, Hash)]
 pub struct Segment<N, T: OptFields> {
     pub id: N,
     pub len: BString,
-    pub sequence: BString,
+    pub sequence: SegmentSequence,
     pub tag: T,
 }
 
@@ -103,27 +115,217 @@ impl<T: OptFields> Segment<BString, T> {
         Segment {
             id: BString::from(id),
             len: BString::from(len),
-            sequence: BString::from(sequence),
+            sequence: sequence.into(),
             tag: Default::default(),
         }
     }
 }
 
+impl<N: SegmentId, T: OptFields> Segment<N, T> {
+    /// Returns the length of the segment, or None if it's unknown
+    /// (```*```) or not valid
+    pub fn length(&self) -> Option<u64> {
+        self.len.to_str().ok()?.parse::<u64>().ok()
+    }
+
+    /// Returns the sequence of the segment, unpacking it if it has
+    /// been packed (the ```*``` placeholder is returned as it is, see
+    /// known_sequence to skip it)
+    pub fn sequence_bytes(&self) -> Cow<'_, [u8]> {
+        self.sequence.bytes()
+    }
+
+    /// Checks if the segment has a sequence, i.e. it's not the ```*```
+    /// placeholder
+    pub fn has_sequence(&self) -> bool {
+        self.sequence != "*"
+    }
+
+    /// Returns the sequence of the segment like sequence_bytes, or None
+    /// if it's the ```*``` placeholder
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// // the total length of the sequences, skipping the placeholders
+    /// let total: usize = gfa2
+    ///     .segments
+    ///     .iter()
+    ///     .filter_map(|s| s.known_sequence())
+    ///     .map(|sequence| sequence.len())
+    ///     .sum();
+    /// ```
+    pub fn known_sequence(&self) -> Option<Cow<'_, [u8]>> {
+        if self.has_sequence() {
+            Some(self.sequence_bytes())
+        } else {
+            None
+        }
+    }
+
+    /// Packs the sequence on 2 bits per base, if it's made only of the
+    /// uppercase ```ACGT``` bases, returning if the sequence is packed
+    pub fn pack_sequence(&mut self) -> bool {
+        self.sequence.pack()
+    }
+
+    /// Returns the portion ```start..end``` of the sequence of the segment,
+    /// or None if the interval is out of bounds or the sequence is `*`
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// let segment: Segment<BString, ()> = Segment::new(b"A", b"10", b"AAAAAAACGT");
+    /// assert_eq!(Some("ACG".into()), segment.subsequence(6, 9));
+    /// assert_eq!(None, segment.subsequence(6, 11));
+    /// ```
+    pub fn subsequence(&self, start: u64, end: u64) -> Option<BString> {
+        let sequence = self.known_sequence().filter(|_| start <= end)?;
+        sequence
+            .get(start as usize..end as usize)
+            .map(BString::from)
+    }
+
+    /// Checks if the sequence of the segment is the reverse complement
+    /// of the sequence of another segment, ignoring the case of the
+    /// bases (a segment without sequence is never a reverse complement)
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// let segment: Segment<BString, ()> = Segment::new(b"A", b"4", b"AACG");
+    /// let other: Segment<BString, ()> = Segment::new(b"B", b"4", b"cgtt");
+    /// assert!(segment.is_rc_of(&other));
+    /// ```
+    pub fn is_rc_of(&self, other: &Segment<N, T>) -> bool {
+        match (self.known_sequence(), other.known_sequence()) {
+            (Some(sequence), Some(other)) => {
+                reverse_complement(&sequence).eq_ignore_ascii_case(&other)
+            }
+            _ => false,
+        }
+    }
+
+    /// Checks if the sequence of the segment is the same of another
+    /// segment or its reverse complement, ignoring the case of the bases
+    pub fn is_same_or_rc(&self, other: &Segment<N, T>) -> bool {
+        match self.known_sequence() {
+            Some(sequence) if sequence.eq_ignore_ascii_case(&other.sequence_bytes()) => true,
+            _ => self.is_rc_of(other),
+        }
+    }
+
+    /// Checks if the sequence has bases relative to a reference, the
+    /// ```=``` base that matches the reference or the ```.``` unknown
+    /// base, so it's not made only of literal nucleotides
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// let segment: Segment<BString, ()> = Segment::new(b"A", b"6", b"AC==.T");
+    /// assert!(segment.has_reference_relative_bases());
+    /// ```
+    pub fn has_reference_relative_bases(&self) -> bool {
+        // a packed sequence is made only of ACGT bases
+        !self.sequence.is_packed()
+            && self
+                .sequence_bytes()
+                .iter()
+                .any(|b| matches!(b, b'=' | b'.'))
+    }
+
+    /// Returns a copy of the segment with the reverse complement of its
+    /// sequence, packed again if the sequence was packed
+    pub fn reverse_complement(&self) -> Segment<N, T>
+    where
+        N: Clone,
+    {
+        let mut segment = Segment {
+            id: self.id.clone(),
+            len: self.len.clone(),
+            sequence: reverse_complement(&self.sequence_bytes()).into(),
+            tag: self.tag.clone(),
+        };
+        if self.sequence.is_packed() {
+            segment.pack_sequence();
+        }
+        segment
+    }
+
+    /// Returns the number of ```A```, ```C```, ```G```, ```T``` and other
+    /// bases of the sequence (see base_counts), all 0 if the sequence is
+    /// ```*```
+    pub fn base_counts(&self) -> [u64; 5] {
+        match self.known_sequence() {
+            Some(sequence) => base_counts(&sequence),
+            None => [0; 5],
+        }
+    }
+
+    /// Returns the fraction of ```G``` and ```C``` bases among the
+    /// ```ACGT``` ones of the sequence, ignoring their case (see
+    /// gc_content), or None if the sequence is ```*``` or has no
+    /// ```ACGT``` bases
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// let segment: Segment<BString, ()> = Segment::new(b"A", b"6", b"ACgtNN");
+    /// assert_eq!(Some(0.5), segment.gc_content());
+    /// ```
+    pub fn gc_content(&self) -> Option<f64> {
+        gc_content(&self.known_sequence()?)
+    }
+
+    /// Returns the GC skew, ```(G - C) / (G + C)```, of every window of
+    /// ```window``` bases of the sequence, ignoring the case of the bases.\
+    /// The windows don't overlap and a last window shorter than the others
+    /// is skipped, so the result is empty if the window is longer than the
+    /// sequence or the sequence is ```*```. A window without G or C bases
+    /// has a skew of 0, and the ```=``` and ```.``` bases are counted as
+    /// ```N```
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// let segment: Segment<BString, ()> = Segment::new(b"A", b"9", b"GGCAATCCA");
+    /// assert_eq!(vec![1.0 / 3.0, 0.0, -1.0], segment.gc_skew_windows(3));
+    /// ```
+    pub fn gc_skew_windows(&self, window: usize) -> Vec<f64> {
+        let sequence = match self.known_sequence() {
+            Some(sequence) if window > 0 => sequence,
+            _ => return vec![],
+        };
+        sequence
+            .chunks_exact(window)
+            .map(|bases| {
+                let count = |base: u8| {
+                    bases
+                        .iter()
+                        .filter(|b| b.eq_ignore_ascii_case(&base))
+                        .count()
+                };
+                let (g, c) = (count(b'G') as f64, count(b'C') as f64);
+                if g + c == 0.0 {
+                    0.0
+                } else {
+                    (g - c) / (g + c)
+                }
+            })
+            .collect()
+    }
+}
+
 impl<N: SegmentId, T: OptFields> fmt::Display for Segment<N, T> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        let mut opt = vec![];
-        for tag in self.tag.fields() {
-            opt.push(tag);
-        }
         write!(
             f,
-            "S\t{}\t{}\t{}\t{}",
-            self.id,
+            "S\t{}\t{}\t{}",
+            self.id.to_name(),
             self.len.as_bstr(),
-            self.sequence.as_bstr(),
-            opt.iter()
-                .fold(String::new(), |acc, str| acc + &str.to_string() + "\t"),
-        )
+            self.sequence_bytes().as_bstr()
+        )?;
+        write_tags(f, self.tag.fields())
     }
 }
 
@@ -148,7 +350,7 @@ impl<N: SegmentId, T: OptFields> fmt::Display for Segment<N, T> {
 ///     tag:(),
 /// };
 /// ```
-#[derive(Default, Debug, Clone, PartialEq, PartialOrd, Serialize, Deserialize, Hash)]
+#[derive(Default, Debug, Clone, PartialEq, Eq, PartialOrd, Serialize, Deserialize, Hash)]
 pub struct Fragment<N, T: OptFields> {
     pub id: N,
     pub ext_ref: N, // orientation as final char (+-)
@@ -183,25 +385,38 @@ impl<T: OptFields> Fragment<BString, T> {
     }
 }
 
+impl<N: SegmentId, T: OptFields> Fragment<N, T> {
+    /// Parses the alignment of the fragment as a CIGAR string,
+    /// returning an error if it's a trace.\
+    /// The ```*``` alignment is an empty CIGAR
+    pub fn alignment_cigar(&self) -> Result<Cigar, ParseFieldError> {
+        Cigar::parse(&self.alignment)
+    }
+}
+
 impl<N: SegmentId, T: OptFields> fmt::Display for Fragment<N, T> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        let mut opt = vec![];
-        for tag in self.tag.fields() {
-            opt.push(tag);
-        }
         write!(
             f,
-            "F\t{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}",
-            self.id,
-            self.ext_ref,
+            "F\t{}\t{}\t{}\t{}\t{}\t{}\t{}",
+            self.id.to_name(),
+            self.ext_ref.to_ref_name(),
             self.sbeg.as_bstr(),
             self.send.as_bstr(),
             self.fbeg.as_bstr(),
             self.fend.as_bstr(),
-            self.alignment.as_bstr(),
-            opt.iter()
-                .fold(String::new(), |acc, str| acc + &str.to_string() + "\t"),
-        )
+            self.alignment.as_bstr()
+        )?;
+        write_tags(f, self.tag.fields())
+    }
+}
+
+/// writes an optional id, using `*` for the anonymous id
+fn opt_id<N: SegmentId>(id: &N) -> String {
+    if id.is_anonymous() {
+        "*".to_string()
+    } else {
+        id.to_name()
     }
 }
 
@@ -227,7 +442,7 @@ impl<N: SegmentId, T: OptFields> fmt::Display for Fragment<N, T> {
 ///     tag:(),
 /// };
 /// ```
-#[derive(Default, Debug, Clone, PartialEq, PartialOrd, Serialize, Deserialize, Hash)]
+#[derive(Default, Debug, Clone, PartialEq, Eq, PartialOrd, Serialize, Deserialize, Hash)]
 pub struct Edge<N, T: OptFields> {
     pub id: N,   // optional id, can be either * or id tag
     pub sid1: N, // orientation as final char (+-)
@@ -265,26 +480,154 @@ impl<T: OptFields> Edge<BString, T> {
     }
 }
 
+impl<N: SegmentId, T: OptFields> Edge<N, T> {
+    /// parses a position field of the edge, the error tells its name
+    fn position_field(input: &[u8], field: &'static str) -> Result<(usize, bool), ParseFieldError> {
+        parse_position(input).ok_or(ParseFieldError::InvalidField(field))
+    }
+
+    /// Returns the beg1 position, and whether it's marked with the
+    /// final `$` char (i.e. it's the end of the first segment)
+    pub fn beg1_pos(&self) -> Result<(usize, bool), ParseFieldError> {
+        Self::position_field(&self.beg1, "beg1")
+    }
+
+    /// Returns the end1 position, and whether it's marked with the
+    /// final `$` char (i.e. it's the end of the first segment)
+    pub fn end1_pos(&self) -> Result<(usize, bool), ParseFieldError> {
+        Self::position_field(&self.end1, "end1")
+    }
+
+    /// Returns the beg2 position, and whether it's marked with the
+    /// final `$` char (i.e. it's the end of the second segment)
+    pub fn beg2_pos(&self) -> Result<(usize, bool), ParseFieldError> {
+        Self::position_field(&self.beg2, "beg2")
+    }
+
+    /// Returns the end2 position, and whether it's marked with the
+    /// final `$` char (i.e. it's the end of the second segment)
+    pub fn end2_pos(&self) -> Result<(usize, bool), ParseFieldError> {
+        Self::position_field(&self.end2, "end2")
+    }
+
+    /// Returns the lengths of the aligned intervals of the two segments,
+    /// ```end1 - beg1``` and ```end2 - beg2```, returning an error if a
+    /// position can't be parsed or an interval ends before its beginning
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// let edge: Edge<BString, ()> =
+    ///     Edge::new(b"*", b"2+", b"45+", b"2531", b"2591$", b"0", b"60", b"60M");
+    /// assert_eq!((60, 60), edge.overlap_lengths().unwrap());
+    /// ```
+    pub fn overlap_lengths(&self) -> Result<(usize, usize), ParseFieldError> {
+        let (beg1, _) = self.beg1_pos()?;
+        let (end1, _) = self.end1_pos()?;
+        let (beg2, _) = self.beg2_pos()?;
+        let (end2, _) = self.end2_pos()?;
+        let len1 = end1
+            .checked_sub(beg1)
+            .ok_or(ParseFieldError::InvalidField("end1"))?;
+        let len2 = end2
+            .checked_sub(beg2)
+            .ok_or(ParseFieldError::InvalidField("end2"))?;
+        Ok((len1, len2))
+    }
+
+    /// returns which ends of a segment are reached by the interval
+    /// ```beg..end```, as (reaches the start, reaches the end)
+    pub(crate) fn reached_ends(beg: &[u8], end: &[u8]) -> Option<(bool, bool)> {
+        let (beg, _) = parse_position(beg)?;
+        let (_, is_end) = parse_position(end)?;
+        Some((beg == 0, is_end))
+    }
+
+    /// Checks if the edge describes a dovetail overlap, i.e. the alignment
+    /// reaches exactly one end of each segment
+    pub fn is_dovetail(&self) -> bool {
+        let ends1 = Self::reached_ends(&self.beg1, &self.end1);
+        let ends2 = Self::reached_ends(&self.beg2, &self.end2);
+        match (ends1, ends2) {
+            (Some((start1, end1)), Some((start2, end2))) => start1 != end1 && start2 != end2,
+            _ => false,
+        }
+    }
+
+    /// Checks if the edge describes a containment, i.e. the alignment
+    /// covers one of the segments entirely
+    pub fn is_containment(&self) -> bool {
+        let ends1 = Self::reached_ends(&self.beg1, &self.end1);
+        let ends2 = Self::reached_ends(&self.beg2, &self.end2);
+        ends1 == Some((true, true)) || ends2 == Some((true, true))
+    }
+
+    /// Checks if the edge is a blunt-end edge, i.e. a dovetail whose
+    /// intervals are empty, so the sequences of the segments just abut
+    /// without overlapping.\
+    /// The alignment has to be ```*``` or a CIGAR that covers no bases
+    /// (e.g. ```0M```); a trace is not checked, as only the positions
+    /// tell the length of the overlap.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// let blunt: Edge<BString, ()> =
+    ///     Edge::new(b"*", b"1+", b"2+", b"4$", b"4$", b"0", b"0", b"*");
+    /// assert!(blunt.is_blunt());
+    /// ```
+    pub fn is_blunt(&self) -> bool {
+        let empty = |beg: &[u8], end: &[u8]| match (parse_position(beg), parse_position(end)) {
+            (Some((beg, _)), Some((end, _))) => beg == end,
+            _ => false,
+        };
+        let no_overlap = match Cigar::parse(&self.alignment) {
+            Ok(cigar) => cigar.reference_len() == 0 && cigar.query_len() == 0,
+            Err(_) => true,
+        };
+        no_overlap
+            && empty(&self.beg1, &self.end1)
+            && empty(&self.beg2, &self.end2)
+            && self.is_dovetail()
+    }
+
+    /// Checks if the edge has no id (the optional id `*`)
+    pub fn is_anonymous(&self) -> bool {
+        self.id.is_anonymous()
+    }
+
+    /// Parses the alignment of the edge as a CIGAR string, returning an
+    /// error if it's a trace.\
+    /// The ```*``` alignment is an empty CIGAR
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// let edge: Edge<BString, ()> =
+    ///     Edge::new(b"*", b"1+", b"2+", b"8", b"12$", b"0", b"4", b"4M");
+    /// let cigar = edge.alignment_cigar().unwrap();
+    /// assert_eq!(4, cigar.reference_len());
+    /// ```
+    pub fn alignment_cigar(&self) -> Result<Cigar, ParseFieldError> {
+        Cigar::parse(&self.alignment)
+    }
+}
+
 impl<N: SegmentId, T: OptFields> fmt::Display for Edge<N, T> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        let mut opt = vec![];
-        for tag in self.tag.fields() {
-            opt.push(tag);
-        }
         write!(
             f,
-            "E\t{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}",
-            self.id,
-            self.sid1,
-            self.sid2,
+            "E\t{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}",
+            opt_id(&self.id),
+            self.sid1.to_ref_name(),
+            self.sid2.to_ref_name(),
             self.beg1.as_bstr(),
             self.end1.as_bstr(),
             self.beg2.as_bstr(),
             self.end2.as_bstr(),
-            self.alignment.as_bstr(),
-            opt.iter()
-                .fold(String::new(), |acc, str| acc + &str.to_string() + "\t"),
-        )
+            self.alignment.as_bstr()
+        )?;
+        write_tags(f, self.tag.fields())
     }
 }
 
@@ -307,7 +650,7 @@ impl<N: SegmentId, T: OptFields> fmt::Display for Edge<N, T> {
 ///     tag:(),
 /// };
 /// ```
-#[derive(Default, Debug, Clone, PartialEq, PartialOrd, Serialize, Deserialize, Hash)]
+#[derive(Default, Debug, Clone, PartialEq, Eq, PartialOrd, Serialize, Deserialize, Hash)]
 pub struct Gap<N, T: OptFields> {
     pub id: N,   // optional id, can be either * or id tag
     pub sid1: N, // orientation as final char (+-)
@@ -330,23 +673,25 @@ impl<T: OptFields> Gap<BString, T> {
     }
 }
 
+impl<N: SegmentId, T: OptFields> Gap<N, T> {
+    /// Checks if the gap has no id (the optional id `*`)
+    pub fn is_anonymous(&self) -> bool {
+        self.id.is_anonymous()
+    }
+}
+
 impl<N: SegmentId, T: OptFields> fmt::Display for Gap<N, T> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        let mut opt = vec![];
-        for tag in self.tag.fields() {
-            opt.push(tag);
-        }
         write!(
             f,
-            "G\t{}\t{}\t{}\t{}\t{}\t{}",
-            self.id,
-            self.sid1,
-            self.sid2,
+            "G\t{}\t{}\t{}\t{}\t{}",
+            opt_id(&self.id),
+            self.sid1.to_ref_name(),
+            self.sid2.to_ref_name(),
             self.dist.as_bstr(),
-            self.var.as_bstr(),
-            opt.iter()
-                .fold(String::new(), |acc, str| acc + &str.to_string() + "\t"),
-        )
+            self.var.as_bstr()
+        )?;
+        write_tags(f, self.tag.fields())
     }
 }
 
@@ -366,7 +711,7 @@ impl<N: SegmentId, T: OptFields> fmt::Display for Gap<N, T> {
 ///     (),
 /// );
 /// ```
-#[derive(Default, Debug, Clone, PartialEq, PartialOrd, Serialize, Deserialize, Hash)]
+#[derive(Default, Debug, Clone, PartialEq, Eq, PartialOrd, Serialize, Deserialize, Hash)]
 pub struct GroupO<N, T: OptFields> {
     // O-Group and U-Group are different only for one field
     // this field can implment or not an optional tag (using * char)
@@ -385,19 +730,79 @@ impl<N: SegmentId, T: OptFields> GroupO<N, T> {
             _segment_names: std::marker::PhantomData,
         }
     }
+
+    /// Builds an O-Group from its oriented members, writing them in
+    /// the var_field separated by a space
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// let ogroup: GroupO<BString, ()> = GroupO::from_members(
+    ///     "P1".into(),
+    ///     &[("36".into(), Orientation::Forward), ("50".into(), Orientation::Backward)],
+    /// );
+    /// assert_eq!("36+ 50-", ogroup.var_field);
+    /// ```
+    pub fn from_members(id: BString, members: &[(N, Orientation)]) -> Self {
+        let var_field = members
+            .iter()
+            .map(|(seg, orient)| format!("{}{}", seg.to_name(), orient))
+            .collect::<Vec<_>>()
+            .join(" ");
+        Self::new(id, var_field.into(), T::default())
+    }
 }
 
 impl<N: SegmentId, T: OptFields> GroupO<N, T> {
-    /// parses (and copies) a segment ID in the group segment list
+    /// Returns the number of members of the group, counting the
+    /// references in the var_field without parsing them (multiple
+    /// whitespaces between them are allowed)
+    pub fn len(&self) -> usize {
+        self.members().count()
+    }
+
+    /// Checks if the group has no members
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// iterates over the members of the group, without parsing them
+    /// (multiple whitespaces between them are allowed, so a member is
+    /// never empty)
+    pub(crate) fn members(&self) -> impl Iterator<Item = &'_ [u8]> {
+        self.var_field.fields()
+    }
+
+    /// Checks if the id is a member of the group, with any orientation.\
+    /// The members are parsed as ids, so for the usize groups the id is
+    /// compared with the encoded members
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// let ogroup: GroupO<BString, ()> = GroupO::new("P1".into(), "36+ 50-".into(), ());
+    /// assert!(ogroup.contains(&"50".into()));
+    /// ```
+    pub fn contains(&self, id: &N) -> bool
+    where
+        N: PartialEq,
+    {
+        self.members()
+            .filter_map(Self::parse_segment_id)
+            .any(|(member, _)| &member == id)
+    }
+
+    /// parses every member of the group with its orientation, None if
+    /// the member can't be parsed
+    pub(crate) fn oriented_members(&self) -> impl Iterator<Item = Option<(N, Orientation)>> + '_ {
+        self.members().map(Self::parse_segment_id)
+    }
+
+    /// parses (and copies) a segment ID in the group segment list,
+    /// None if it doesn't include the orientation
     fn parse_segment_id(input: &[u8]) -> Option<(N, Orientation)> {
-        use Orientation::*;
-        let last = input.len() - 1;
-        let orient = match input[last] {
-            b'+' => Forward,
-            b'-' => Backward,
-            _ => panic!("Group O segment did not include orientation"),
-        };
-        let seg = &input[..last];
+        let (last, seg) = input.split_last()?;
+        let orient = Orientation::from_bytes_plus_minus([*last])?;
         let id = N::parse_id(seg)?;
         Some((id, orient))
     }
@@ -406,9 +811,7 @@ impl<N: SegmentId, T: OptFields> GroupO<N, T> {
 impl<T: OptFields> GroupO<usize, T> {
     /// Produces an iterator over the usize segments of the given group
     pub fn iter<'a>(&'a self) -> impl Iterator<Item = (usize, Orientation)> + 'a {
-        self.var_field
-            .split_str(b" ")
-            .filter_map(Self::parse_segment_id)
+        self.members().filter_map(Self::parse_segment_id)
     }
 }
 
@@ -417,7 +820,7 @@ impl<T: OptFields> GroupO<BString, T> {
     /// parsing the orientation and producing a slice to each segment
     /// name
     pub fn iter(&self) -> impl Iterator<Item = (&'_ BStr, Orientation)> {
-        self.var_field.split_str(b" ").map(Self::segment_id_ref)
+        self.members().map(Self::segment_id_ref)
     }
 
     fn segment_id_ref(input: &[u8]) -> (&'_ BStr, Orientation) {
@@ -431,22 +834,40 @@ impl<T: OptFields> GroupO<BString, T> {
         let seg = &input[..last];
         (seg.as_ref(), orient)
     }
+
+    /// Returns the sequence spelled by the group, concatenating the
+    /// sequences of its segments, reverse complemented if the segment
+    /// is traversed backward.\
+    /// The overlaps between the segments are not removed, and the result
+    /// is None if a member can't be parsed, is not a segment (e.g. it's
+    /// an edge) or the segment has no sequence (```*```)
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// let segments: HashMap<BString, &Segment<BString, ()>> =
+    ///     gfa2.segments.iter().map(|s| (s.id.clone(), s)).collect();
+    /// let sequence = gfa2.groups_o[0].spell(&segments).unwrap();
+    /// ```
+    pub fn spell(&self, segments: &HashMap<BString, &Segment<BString, T>>) -> Option<BString> {
+        let mut sequence = BString::default();
+        for member in self.oriented_members() {
+            let (id, orient) = member?;
+            let segment = segments.get(&id)?;
+            let bases = segment.known_sequence()?;
+            match orient {
+                Orientation::Forward => sequence.extend_from_slice(&bases),
+                Orientation::Backward => sequence.extend_from_slice(&reverse_complement(&bases)),
+            }
+        }
+        Some(sequence)
+    }
 }
 
 impl<N: SegmentId, T: OptFields> fmt::Display for GroupO<N, T> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        let mut opt = vec![];
-        for tag in self.tag.fields() {
-            opt.push(tag);
-        }
-        write!(
-            f,
-            "O\t{}\t{}\t{}",
-            self.id,
-            self.var_field.as_bstr().to_string(),
-            opt.iter()
-                .fold(String::new(), |acc, str| acc + &str.to_string() + "\t"),
-        )
+        write!(f, "O\t{}\t{}", self.id, self.var_field.as_bstr())?;
+        write_tags(f, self.tag.fields())
     }
 }
 
@@ -466,7 +887,7 @@ impl<N: SegmentId, T: OptFields> fmt::Display for GroupO<N, T> {
 ///     (),
 /// );
 /// ```
-#[derive(Default, Debug, Clone, PartialEq, PartialOrd, Serialize, Deserialize, Hash)]
+#[derive(Default, Debug, Clone, PartialEq, Eq, PartialOrd, Serialize, Deserialize, Hash)]
 pub struct GroupU<N, T: OptFields> {
     // O-Group and U-Group are different only for one field
     // this field can implment or not an optional tag (using * char)
@@ -485,11 +906,68 @@ impl<N: SegmentId, T: OptFields> GroupU<N, T> {
             _segment_names: std::marker::PhantomData,
         }
     }
+
+    /// Builds an U-Group from its members, writing them in the
+    /// var_field separated by a space
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// let ugroup: GroupU<BString, ()> =
+    ///     GroupU::from_members("SG1".into(), &["16".into(), "24".into()]);
+    /// assert_eq!("16 24", ugroup.var_field);
+    /// ```
+    pub fn from_members(id: BString, members: &[N]) -> Self {
+        let var_field = members
+            .iter()
+            .map(|seg| seg.to_name())
+            .collect::<Vec<_>>()
+            .join(" ");
+        Self::new(id, var_field.into(), T::default())
+    }
 }
 
 // U-Group do not have any orientations on the segment ids that they contained
 // so I used as "deafult orientation" the Forward one ('+')
 impl<N: SegmentId, T: OptFields> GroupU<N, T> {
+    /// Returns the number of members of the group, counting the ids
+    /// in the var_field without parsing them (multiple whitespaces
+    /// between them are allowed)
+    pub fn len(&self) -> usize {
+        self.members().count()
+    }
+
+    /// Checks if the group has no members
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// iterates over the members of the group, without parsing them
+    /// (multiple whitespaces between them are allowed, so a member is
+    /// never empty)
+    pub(crate) fn members(&self) -> impl Iterator<Item = &'_ [u8]> {
+        self.var_field.fields()
+    }
+
+    /// Checks if the id is a member of the group.\
+    /// The members are parsed as ids, so for the usize groups the id is
+    /// compared with the encoded members
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// let ugroup: GroupU<BString, ()> = GroupU::new("SG1".into(), "16 24".into(), ());
+    /// assert!(ugroup.contains(&"24".into()));
+    /// ```
+    pub fn contains(&self, id: &N) -> bool
+    where
+        N: PartialEq,
+    {
+        self.members()
+            .filter_map(Self::parse_segment_id)
+            .any(|member| &member == id)
+    }
+
     /// parses (and copies) a segment ID in the group segment list
     fn parse_segment_id(input: &[u8]) -> Option<N> {
         let id = N::parse_opt_id(input)?;
@@ -500,9 +978,7 @@ impl<N: SegmentId, T: OptFields> GroupU<N, T> {
 impl<T: OptFields> GroupU<usize, T> {
     /// Produces an iterator over the usize segments of the given group
     pub fn iter<'a>(&'a self) -> impl Iterator<Item = usize> + 'a {
-        self.var_field
-            .split_str(b" ")
-            .filter_map(Self::parse_segment_id)
+        self.members().filter_map(Self::parse_segment_id)
     }
 }
 
@@ -513,7 +989,7 @@ impl<T: OptFields> GroupU<BString, T> {
     /// parsing the orientation and producing a slice to each segment
     /// name
     pub fn iter(&self) -> impl Iterator<Item = &'_ BStr> {
-        self.var_field.split_str(b" ").map(Self::segment_id_ref)
+        self.members().map(Self::segment_id_ref)
     }
 
     fn segment_id_ref(input: &[u8]) -> &'_ BStr {
@@ -523,18 +999,37 @@ impl<T: OptFields> GroupU<BString, T> {
 
 impl<N: SegmentId, T: OptFields> fmt::Display for GroupU<N, T> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        let mut opt = vec![];
-        for tag in self.tag.fields() {
-            opt.push(tag);
+        write!(f, "U\t{}\t{}", self.id, self.var_field.as_bstr())?;
+        write_tags(f, self.tag.fields())
+    }
+}
+
+/// A line of a type unknown to the parser (e.g. a custom record of
+/// some tool), kept as it is so it can be written back
+///
+/// # Examples
+///
+/// ```ignore
+/// // a custom record "X\tfoo\tbar"
+/// let record = CustomRecord {
+///     type_field: b'X',
+///     fields: vec!["foo".into(), "bar".into()],
+/// };
+/// assert_eq!("X\tfoo\tbar", record.to_string());
+/// ```
+#[derive(Default, Debug, Clone, PartialEq, Eq, PartialOrd, Serialize, Deserialize, Hash)]
+pub struct CustomRecord {
+    pub type_field: u8,
+    pub fields: Vec<BString>,
+}
+
+impl fmt::Display for CustomRecord {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.type_field as char)?;
+        for field in self.fields.iter() {
+            write!(f, "\t{}", field)?;
         }
-        write!(
-            f,
-            "U\t{}\t{}\t{}",
-            self.id,
-            self.var_field.as_bstr().to_string(),
-            opt.iter()
-                .fold(String::new(), |acc, str| acc + &str.to_string() + "\t"),
-        )
+        Ok(())
     }
 }
 
@@ -571,7 +1066,7 @@ impl<N: SegmentId, T: OptFields> fmt::Display for GroupU<N, T> {
 /// };
 /// // inizialize a simple gfa2 object
 /// ```
-#[derive(Default, Debug, Clone, PartialEq, PartialOrd)]
+#[derive(Default, Debug, Clone, PartialEq, Eq, PartialOrd, Serialize, Deserialize, Hash)]
 pub struct GFA2<N, T: OptFields> {
     // OptFields is used to encode the <tag>* item
     // struct to hold the results of parsing a file; not actually a graph
@@ -582,10 +1077,15 @@ pub struct GFA2<N, T: OptFields> {
     pub gaps: Vec<Gap<N, T>>,
     pub groups_o: Vec<GroupO<N, T>>,
     pub groups_u: Vec<GroupU<N, T>>,
+    /// The comment lines, without the leading `#`, only stored if
+    /// the parser keeps them
+    pub comments: Vec<BString>,
+    /// The lines of unknown types, only stored if the parser keeps them
+    pub custom_records: Vec<CustomRecord>,
 }
 
 /// Enum containing the different kinds of GFA2 lines.
-#[derive(Debug, Clone, PartialEq, PartialOrd)]
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Serialize, Deserialize, Hash)]
 pub enum Line<N, T: OptFields> {
     Header(Header<T>),
     Segment(Segment<N, T>),
@@ -594,6 +1094,9 @@ pub enum Line<N, T: OptFields> {
     Gap(Gap<N, T>),
     GroupO(GroupO<N, T>),
     GroupU(GroupU<N, T>),
+    /// A comment line, without the leading `#`
+    Comment(BString),
+    CustomRecord(CustomRecord),
 }
 
 macro_rules! some_line_fn {
@@ -617,8 +1120,10 @@ some_line_fn!(some_edge, Edge<N, T>, Line::Edge);
 some_line_fn!(some_gap, Gap<N, T>, Line::Gap);
 some_line_fn!(some_ogroup, GroupO<N, T>, Line::GroupO);
 some_line_fn!(some_ugroup, GroupU<N, T>, Line::GroupU);
+some_line_fn!(some_comment, BString, Line::Comment);
+some_line_fn!(some_custom_record, CustomRecord, Line::CustomRecord);
 
-#[derive(Debug, Clone, PartialEq, PartialOrd)]
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Serialize, Hash)]
 pub enum LineRef<'a, N, T: OptFields> {
     Header(&'a Header<T>),
     Segment(&'a Segment<N, T>),
@@ -627,6 +1132,48 @@ pub enum LineRef<'a, N, T: OptFields> {
     Gap(&'a Gap<N, T>),
     GroupO(&'a GroupO<N, T>),
     GroupU(&'a GroupU<N, T>),
+    Comment(&'a BString),
+    CustomRecord(&'a CustomRecord),
+}
+
+impl<'a, N: SegmentId, T: OptFields> fmt::Display for LineRef<'a, N, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LineRef::Header(h) => write!(f, "{}", h),
+            LineRef::Segment(s) => write!(f, "{}", s),
+            LineRef::Fragment(fr) => write!(f, "{}", fr),
+            LineRef::Edge(e) => write!(f, "{}", e),
+            LineRef::Gap(g) => write!(f, "{}", g),
+            LineRef::GroupO(o) => write!(f, "{}", o),
+            LineRef::GroupU(u) => write!(f, "{}", u),
+            LineRef::Comment(c) => write!(f, "#{}", c),
+            LineRef::CustomRecord(r) => write!(f, "{}", r),
+        }
+    }
+}
+
+impl<N, T: OptFields> Line<N, T> {
+    /// Borrows the line as a LineRef, without cloning it
+    pub fn as_ref(&self) -> LineRef<'_, N, T> {
+        use Line::*;
+        match self {
+            Header(x) => LineRef::Header(x),
+            Segment(x) => LineRef::Segment(x),
+            Fragment(x) => LineRef::Fragment(x),
+            Edge(x) => LineRef::Edge(x),
+            Gap(x) => LineRef::Gap(x),
+            GroupO(x) => LineRef::GroupO(x),
+            GroupU(x) => LineRef::GroupU(x),
+            Comment(x) => LineRef::Comment(x),
+            CustomRecord(x) => LineRef::CustomRecord(x),
+        }
+    }
+}
+
+impl<'a, N, T: OptFields> From<&'a Line<N, T>> for LineRef<'a, N, T> {
+    fn from(line: &'a Line<N, T>) -> Self {
+        line.as_ref()
+    }
 }
 
 macro_rules! some_line_ref_fn {
@@ -650,6 +1197,8 @@ some_line_ref_fn!(some_edge, Edge<N, T>, LineRef::Edge);
 some_line_ref_fn!(some_gap, Gap<N, T>, LineRef::Gap);
 some_line_ref_fn!(some_ogroup, GroupO<N, T>, LineRef::GroupO);
 some_line_ref_fn!(some_ugroup, GroupU<N, T>, LineRef::GroupU);
+some_line_ref_fn!(some_comment, BString, LineRef::Comment);
+some_line_ref_fn!(some_custom_record, CustomRecord, LineRef::CustomRecord);
 
 /// Insert a GFA line (wrapped in the Line enum) into an existing
 /// GFA. Simply pushes it into the corresponding Vec in the GFA,
@@ -659,7 +1208,7 @@ impl<N, T: OptFields> GFA2<N, T> {
     /// Insert a GFA line (wrapped in the Line enum) into an existing
     /// GFA. Simply pushes it into the corresponding Vec in the GFA,
     /// or replaces the header, so there's no deduplication or sorting
-    /// taking place.
+    /// taking place (see sort and sort_canonical).
     pub fn insert_line(&mut self, line: Line<N, T>) {
         use Line::*;
         match line {
@@ -670,12 +1219,16 @@ impl<N, T: OptFields> GFA2<N, T> {
             Gap(g) => self.gaps.push(g),
             GroupO(o) => self.groups_o.push(o),
             GroupU(u) => self.groups_u.push(u),
+            Comment(c) => self.comments.push(c),
+            CustomRecord(r) => self.custom_records.push(r),
         }
     }
 
     /// Consume a GFA2 object to produce an iterator over all the lines
     /// contained within. The iterator first produces all headers then segments,
-    /// fragments, edges, gaps, groups, comments and finally custom records
+    /// fragments, edges, gaps, groups, comments and finally custom records.\
+    /// The lines are grouped by kind, so a comment doesn't keep its
+    /// position among the other lines of the parsed file
     pub fn lines_into_iter(self) -> impl Iterator<Item = Line<N, T>> {
         use Line::*;
         let heads = self.headers.into_iter().map(Header);
@@ -685,6 +1238,8 @@ impl<N, T: OptFields> GFA2<N, T> {
         let gaps = self.gaps.into_iter().map(Gap);
         let ogroups = self.groups_o.into_iter().map(GroupO);
         let ugroups = self.groups_u.into_iter().map(GroupU);
+        let comments = self.comments.into_iter().map(Comment);
+        let records = self.custom_records.into_iter().map(CustomRecord);
 
         heads
             .chain(segs)
@@ -693,9 +1248,32 @@ impl<N, T: OptFields> GFA2<N, T> {
             .chain(gaps)
             .chain(ogroups)
             .chain(ugroups)
+            .chain(comments)
+            .chain(records)
+    }
+
+    /// Appends all the lines of ```other``` to the graph, keeping only
+    /// the headers of the graph (or the ones of ```other```, if the
+    /// graph has none).\
+    /// The ids are not checked, see merge_renaming to rename the
+    /// segments of ```other``` whose ids are already used
+    pub fn merge(&mut self, other: GFA2<N, T>) {
+        if self.headers.is_empty() {
+            self.headers = other.headers;
+        }
+        self.segments.extend(other.segments);
+        self.fragments.extend(other.fragments);
+        self.edges.extend(other.edges);
+        self.gaps.extend(other.gaps);
+        self.groups_o.extend(other.groups_o);
+        self.groups_u.extend(other.groups_u);
+        self.comments.extend(other.comments);
+        self.custom_records.extend(other.custom_records);
     }
 
-    /// Return an iterator over references to the lines in the GFA2
+    /// Return an iterator over references to the lines in the GFA2, in
+    /// the same order of lines_into_iter (so the comments come after
+    /// all the groups)
     pub fn lines_iter(&'_ self) -> impl Iterator<Item = LineRef<'_, N, T>> {
         use LineRef::*;
         let heads = self.headers.iter().map(Header);
@@ -705,6 +1283,8 @@ impl<N, T: OptFields> GFA2<N, T> {
         let gaps = self.gaps.iter().map(Gap);
         let ogroups = self.groups_o.iter().map(GroupO);
         let ugroups = self.groups_u.iter().map(GroupU);
+        let comments = self.comments.iter().map(Comment);
+        let records = self.custom_records.iter().map(CustomRecord);
 
         heads
             .chain(segs)
@@ -713,6 +1293,134 @@ impl<N, T: OptFields> GFA2<N, T> {
             .chain(gaps)
             .chain(ogroups)
             .chain(ugroups)
+            .chain(comments)
+            .chain(records)
+    }
+}
+
+/// The number of lines of every kind in a GFA2 at some point, used to
+/// get back only the lines appended after it (see ```GFA2::lines_since```)
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct LinesMark {
+    pub headers: usize,
+    pub segments: usize,
+    pub fragments: usize,
+    pub edges: usize,
+    pub gaps: usize,
+    pub groups_o: usize,
+    pub groups_u: usize,
+    pub comments: usize,
+    pub custom_records: usize,
+}
+
+macro_rules! lines_since_fn {
+    ($name:ident, $field:ident, $tgt:ty) => {
+        /// Returns the lines appended from the given index on (empty
+        /// if the index is past the last line)
+        pub fn $name(&self, index: usize) -> &[$tgt] {
+            self.$field.get(index..).unwrap_or(&[])
+        }
+    };
+}
+
+impl<N, T: OptFields> GFA2<N, T> {
+    lines_since_fn!(headers_since, headers, Header<T>);
+    lines_since_fn!(segments_since, segments, Segment<N, T>);
+    lines_since_fn!(fragments_since, fragments, Fragment<N, T>);
+    lines_since_fn!(edges_since, edges, Edge<N, T>);
+    lines_since_fn!(gaps_since, gaps, Gap<N, T>);
+    lines_since_fn!(groups_o_since, groups_o, GroupO<N, T>);
+    lines_since_fn!(groups_u_since, groups_u, GroupU<N, T>);
+    lines_since_fn!(comments_since, comments, BString);
+    lines_since_fn!(custom_records_since, custom_records, CustomRecord);
+
+    /// Returns the number of bytes used by the optional fields of
+    /// every line (see OptField::memory_size), 0 if the optional
+    /// fields are not stored
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// let parser: GFA2Parser<BString, OptionalFields> = GFA2Parser::new();
+    /// let gfa2 = parser.parse_file("./tests/gfa2_files/big.gfa").unwrap();
+    ///
+    /// println!("the optional fields use {} bytes", gfa2.tag_memory());
+    /// ```
+    pub fn tag_memory(&self) -> usize {
+        self.lines_iter()
+            .map(|line| {
+                let fields = match line {
+                    LineRef::Header(h) => h.tag.fields(),
+                    LineRef::Segment(s) => s.tag.fields(),
+                    LineRef::Fragment(f) => f.tag.fields(),
+                    LineRef::Edge(e) => e.tag.fields(),
+                    LineRef::Gap(g) => g.tag.fields(),
+                    LineRef::GroupO(o) => o.tag.fields(),
+                    LineRef::GroupU(u) => u.tag.fields(),
+                    LineRef::Comment(_) | LineRef::CustomRecord(_) => &[],
+                };
+                fields.iter().map(OptField::memory_size).sum::<usize>()
+            })
+            .sum()
+    }
+
+    /// Returns the number of lines of every kind, to be passed later
+    /// to ```lines_since```
+    pub fn mark(&self) -> LinesMark {
+        LinesMark {
+            headers: self.headers.len(),
+            segments: self.segments.len(),
+            fragments: self.fragments.len(),
+            edges: self.edges.len(),
+            gaps: self.gaps.len(),
+            groups_o: self.groups_o.len(),
+            groups_u: self.groups_u.len(),
+            comments: self.comments.len(),
+            custom_records: self.custom_records.len(),
+        }
+    }
+
+    /// Return an iterator over references to the lines appended after
+    /// the mark, in the same order as ```lines_iter```.\
+    /// Only the appended lines are returned, so the lines that have been
+    /// modified or removed since the mark have to be handled by the caller
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// let mark = gfa2.mark();
+    /// gfa2.insert_line(line);
+    ///
+    /// for line in gfa2.lines_since(&mark) {
+    ///     if let Some(segment) = line.some_segment() {
+    ///         writeln!(file, "{}", segment)?;
+    ///     }
+    /// }
+    /// ```
+    pub fn lines_since(&'_ self, mark: &LinesMark) -> impl Iterator<Item = LineRef<'_, N, T>> {
+        use LineRef::*;
+        let heads = self.headers_since(mark.headers).iter().map(Header);
+        let segs = self.segments_since(mark.segments).iter().map(Segment);
+        let frags = self.fragments_since(mark.fragments).iter().map(Fragment);
+        let edges = self.edges_since(mark.edges).iter().map(Edge);
+        let gaps = self.gaps_since(mark.gaps).iter().map(Gap);
+        let ogroups = self.groups_o_since(mark.groups_o).iter().map(GroupO);
+        let ugroups = self.groups_u_since(mark.groups_u).iter().map(GroupU);
+        let comments = self.comments_since(mark.comments).iter().map(Comment);
+        let records = self
+            .custom_records_since(mark.custom_records)
+            .iter()
+            .map(CustomRecord);
+
+        heads
+            .chain(segs)
+            .chain(frags)
+            .chain(edges)
+            .chain(gaps)
+            .chain(ogroups)
+            .chain(ugroups)
+            .chain(comments)
+            .chain(records)
     }
 }
 
@@ -720,34 +1428,1804 @@ impl<N: SegmentId, T: OptFields> GFA2<N, T> {
     pub fn new() -> Self {
         Default::default()
     }
+
+    /// Returns the segments sorted from the longest to the shortest.\
+    /// The segments whose length is unknown (```*```) or not valid are
+    /// placed last, and segments with the same length keep their order
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// let parser: GFA2Parser<BString, ()> = GFA2Parser::new();
+    /// let gfa2 = parser.parse_file("./tests/gfa2_files/spec_q7.gfa").unwrap();
+    ///
+    /// for segment in gfa2.segments_by_length().iter().take(10) {
+    ///     println!("{}\t{}", segment.id, segment.len);
+    /// }
+    /// ```
+    pub fn segments_by_length(&self) -> Vec<&Segment<N, T>> {
+        let mut segments: Vec<_> = self.segments.iter().collect();
+        // None is smaller than any length, so it goes last once reversed
+        segments.sort_by_key(|s| std::cmp::Reverse(s.length()));
+        segments
+    }
+
+    /// Returns an iterator over the ids of the segments, in the order
+    /// they were inserted, without cloning them
+    pub fn segment_ids_iter(&self) -> impl Iterator<Item = &N> {
+        self.segments.iter().map(|s| &s.id)
+    }
+
+    /// Returns the ids of the segments, in the order they were inserted
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// let parser: GFA2Parser<BString, ()> = GFA2Parser::new();
+    /// let gfa2 = parser.parse_file("./tests/gfa2_files/spec_q7.gfa").unwrap();
+    ///
+    /// let ids: HashSet<_> = gfa2.segment_ids().into_iter().collect();
+    /// ```
+    pub fn segment_ids(&self) -> Vec<&N> {
+        self.segment_ids_iter().collect()
+    }
+
+    /// Removes the optional tags of every line, keeping the type of the
+    /// optional fields (see ```without_tags``` to drop it too)
+    pub fn strip_tags(&mut self) {
+        self.headers.iter_mut().for_each(|h| h.tag = T::default());
+        self.segments.iter_mut().for_each(|s| s.tag = T::default());
+        self.fragments.iter_mut().for_each(|f| f.tag = T::default());
+        self.edges.iter_mut().for_each(|e| e.tag = T::default());
+        self.gaps.iter_mut().for_each(|g| g.tag = T::default());
+        self.groups_o.iter_mut().for_each(|o| o.tag = T::default());
+        self.groups_u.iter_mut().for_each(|u| u.tag = T::default());
+    }
+
+    /// Writes the lines of the GFA2 object, in the same order as
+    /// ```lines_iter```, ending them as set in the options
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// let options = WriteOptions {
+    ///     newline: Newline::CrLf,
+    ///     trailing_newline: true,
+    /// };
+    /// let mut file = BufWriter::new(File::create("graph.gfa")?);
+    /// gfa2.write_with(&mut file, &options)?;
+    /// ```
+    pub fn write_with<W: std::io::Write>(
+        &self,
+        out: &mut W,
+        options: &WriteOptions,
+    ) -> std::io::Result<()> {
+        write_lines(out, self.lines_iter(), options)
+    }
+
+    /// Writes every line followed by a newline, as the Display of the
+    /// GFA2 object but without building the whole text in memory first
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// let mut file = BufWriter::new(File::create("graph.gfa")?);
+    /// gfa2.write_to(&mut file)?;
+    /// ```
+    pub fn write_to<W: std::io::Write>(&self, out: &mut W) -> std::io::Result<()> {
+        let options = WriteOptions {
+            newline: Newline::Lf,
+            trailing_newline: true,
+        };
+        self.write_with(out, &options)
+    }
+}
+
+/// The characters that end each line when writing a GFA object
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Newline {
+    /// ```\n```, the default
+    #[default]
+    Lf,
+    /// ```\r\n```
+    CrLf,
+}
+
+impl Newline {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Newline::Lf => "\n",
+            Newline::CrLf => "\r\n",
+        }
+    }
+}
+
+/// Options to write a GFA or GFA2 object (see ```write_with```)
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WriteOptions {
+    /// The characters written between two lines.
+    pub newline: Newline,
+    /// Ends the last line with a newline too.
+    pub trailing_newline: bool,
+}
+
+/// writes the lines separated by the newline of the options
+pub(crate) fn write_lines<W, I>(
+    out: &mut W,
+    lines: I,
+    options: &WriteOptions,
+) -> std::io::Result<()>
+where
+    W: std::io::Write,
+    I: Iterator,
+    I::Item: fmt::Display,
+{
+    let newline = options.newline.as_str().as_bytes();
+    let mut empty = true;
+    for line in lines {
+        if !empty {
+            out.write_all(newline)?;
+        }
+        write!(out, "{}", line)?;
+        empty = false;
+    }
+    if options.trailing_newline && !empty {
+        out.write_all(newline)?;
+    }
+    Ok(())
+}
+
+impl<N: SegmentId + Ord, T: OptFields + PartialOrd> GFA2<N, T> {
+    /// Sorts the lines by their identifying fields, and the lines with
+    /// the same ones by all their other fields, so that the same lines
+    /// are always displayed in the same order, whatever their order in
+    /// the source file:
+    /// - headers with a version first
+    /// - segments by id
+    /// - fragments by (segment id, external reference)
+    /// - edges and gaps by (sid1, sid2), orientations included
+    /// - o-groups and u-groups by id
+    ///
+    /// the ids are compared as they are stored, so the BString ones are
+    /// sorted lexicographically and the usize ones numerically, by their
+    /// encoded value (so shorter names come first).\
+    /// Comments and custom records keep their order, and the optional
+    /// fields are not touched (see sort_canonical to sort them by tag)
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// let parser: GFA2Parser<usize, ()> = GFA2Parser::new();
+    /// let mut gfa2 = parser.parse_file("./tests/gfa2_files/spec_q7.gfa").unwrap();
+    /// gfa2.sort();
+    /// println!("{}", gfa2);
+    /// ```
+    pub fn sort(&mut self) {
+        self.headers.sort_by(|a, b| {
+            b.version
+                .is_some()
+                .cmp(&a.version.is_some())
+                .then_with(|| canonical_order(a, b))
+        });
+        self.segments
+            .sort_by(|a, b| a.id.cmp(&b.id).then_with(|| canonical_order(a, b)));
+        self.fragments.sort_by(|a, b| {
+            (&a.id, &a.ext_ref)
+                .cmp(&(&b.id, &b.ext_ref))
+                .then_with(|| canonical_order(a, b))
+        });
+        self.edges.sort_by(|a, b| {
+            (&a.sid1, &a.sid2)
+                .cmp(&(&b.sid1, &b.sid2))
+                .then_with(|| canonical_order(a, b))
+        });
+        self.gaps.sort_by(|a, b| {
+            (&a.sid1, &a.sid2)
+                .cmp(&(&b.sid1, &b.sid2))
+                .then_with(|| canonical_order(a, b))
+        });
+        self.groups_o
+            .sort_by(|a, b| a.id.cmp(&b.id).then_with(|| canonical_order(a, b)));
+        self.groups_u
+            .sort_by(|a, b| a.id.cmp(&b.id).then_with(|| canonical_order(a, b)));
+    }
+
+    /// Sorts the optional fields of every line by their tag, then the
+    /// lines as sort does, so that the same graph is always displayed in
+    /// the same way, whatever the order of its lines and tags
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// let parser: GFA2Parser<BString, OptionalFields> = GFA2Parser::new();
+    /// let mut gfa2 = parser.parse_file("./tests/gfa2_files/spec_q7.gfa").unwrap();
+    /// gfa2.sort_canonical();
+    /// println!("{}", gfa2);
+    /// ```
+    pub fn sort_canonical(&mut self) {
+        macro_rules! sort_tags {
+            ($($lines:expr),*) => {
+                $(for line in $lines.iter_mut() {
+                    line.tag = line.tag.sorted();
+                })*
+            };
+        }
+        sort_tags!(
+            self.headers,
+            self.segments,
+            self.fragments,
+            self.edges,
+            self.gaps,
+            self.groups_o,
+            self.groups_u
+        );
+        self.sort();
+    }
+}
+
+/// compares two lines of the same kind, field by field
+pub(crate) fn canonical_order<L: PartialOrd>(a: &L, b: &L) -> std::cmp::Ordering {
+    a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal)
+}
+
+/// The kind of line where a reference to a segment has been found
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RefSource {
+    Fragment,
+    Edge,
+    Gap,
+    GroupO,
+}
+
+impl<N: SegmentId + Clone, T: OptFields> GFA2<N, T> {
+    /// Returns every oriented reference to a segment in the graph,
+    /// together with the kind of line where it has been found:
+    /// - the segment of a fragment, with the orientation of its
+    ///   external reference
+    /// - both the segments of edges and gaps
+    /// - the members of o-groups
+    ///
+    /// the references that can't be parsed are skipped
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// let parser: GFA2Parser<BString, ()> = GFA2Parser::new();
+    /// let gfa2 = parser.parse_file("./tests/gfa2_files/spec_q7.gfa").unwrap();
+    ///
+    /// for (id, orient, source) in gfa2.all_references() {
+    ///     println!("{}{} in {:?}", id, orient, source);
+    /// }
+    /// ```
+    pub fn all_references(&self) -> impl Iterator<Item = (N, Orientation, RefSource)> + '_ {
+        let fragments = self.fragments.iter().filter_map(|f| {
+            let (_, orient) = f.ext_ref.split_ref()?;
+            Some((f.id.clone(), orient, RefSource::Fragment))
+        });
+        let edges = self
+            .edges
+            .iter()
+            .flat_map(|e| vec![&e.sid1, &e.sid2])
+            .filter_map(|sid| sid.split_ref())
+            .map(|(id, orient)| (id, orient, RefSource::Edge));
+        let gaps = self
+            .gaps
+            .iter()
+            .flat_map(|g| vec![&g.sid1, &g.sid2])
+            .filter_map(|sid| sid.split_ref())
+            .map(|(id, orient)| (id, orient, RefSource::Gap));
+        let ogroups = self
+            .groups_o
+            .iter()
+            .flat_map(GroupO::oriented_members)
+            .flatten()
+            .map(|(id, orient)| (id, orient, RefSource::GroupO));
+
+        fragments.chain(edges).chain(gaps).chain(ogroups)
+    }
+
+    /// Returns a copy of the graph without any optional tag, so that
+    /// graphs parsed with different optional fields can be compared
+    /// on their structure alone
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// let parser: GFA2Parser<BString, OptionalFields> = GFA2Parser::new();
+    /// let gfa2 = parser.parse_file("./tests/gfa2_files/spec_q7.gfa").unwrap();
+    ///
+    /// let parser: GFA2Parser<BString, ()> = GFA2Parser::new();
+    /// let no_tags = parser.parse_file("./tests/gfa2_files/spec_q7.gfa").unwrap();
+    /// assert_eq!(no_tags, gfa2.without_tags());
+    /// ```
+    pub fn without_tags(&self) -> GFA2<N, ()> {
+        GFA2 {
+            headers: self
+                .headers
+                .iter()
+                .map(|h| Header {
+                    version: h.version.clone(),
+                    tag: (),
+                })
+                .collect(),
+            segments: self
+                .segments
+                .iter()
+                .map(|s| Segment {
+                    id: s.id.clone(),
+                    len: s.len.clone(),
+                    sequence: s.sequence.clone(),
+                    tag: (),
+                })
+                .collect(),
+            fragments: self
+                .fragments
+                .iter()
+                .map(|f| Fragment {
+                    id: f.id.clone(),
+                    ext_ref: f.ext_ref.clone(),
+                    sbeg: f.sbeg.clone(),
+                    send: f.send.clone(),
+                    fbeg: f.fbeg.clone(),
+                    fend: f.fend.clone(),
+                    alignment: f.alignment.clone(),
+                    tag: (),
+                })
+                .collect(),
+            edges: self
+                .edges
+                .iter()
+                .map(|e| Edge {
+                    id: e.id.clone(),
+                    sid1: e.sid1.clone(),
+                    sid2: e.sid2.clone(),
+                    beg1: e.beg1.clone(),
+                    end1: e.end1.clone(),
+                    beg2: e.beg2.clone(),
+                    end2: e.end2.clone(),
+                    alignment: e.alignment.clone(),
+                    tag: (),
+                })
+                .collect(),
+            gaps: self
+                .gaps
+                .iter()
+                .map(|g| Gap {
+                    id: g.id.clone(),
+                    sid1: g.sid1.clone(),
+                    sid2: g.sid2.clone(),
+                    dist: g.dist.clone(),
+                    var: g.var.clone(),
+                    tag: (),
+                })
+                .collect(),
+            groups_o: self
+                .groups_o
+                .iter()
+                .map(|o| GroupO::new(o.id.clone(), o.var_field.clone(), ()))
+                .collect(),
+            groups_u: self
+                .groups_u
+                .iter()
+                .map(|u| GroupU::new(u.id.clone(), u.var_field.clone(), ()))
+                .collect(),
+            comments: self.comments.clone(),
+            custom_records: self.custom_records.clone(),
+        }
+    }
+}
+
+impl<N: SegmentId + PartialEq, T: OptFields> GFA2<N, T> {
+    /// Like insert_line, but a segment whose id is already used by
+    /// another segment, or an edge with the same references, positions
+    /// and alignment of another edge (whatever their ids and tags), is
+    /// not inserted. Returns if the line has been inserted.\
+    /// Every other kind of line is always inserted.\
+    /// The existing lines are scanned at every insertion
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// let mut gfa2 = parser.parse_file("./tests/gfa2_files/spec_q7.gfa").unwrap();
+    /// for line in other.lines_into_iter() {
+    ///     gfa2.insert_line_dedup(line);
+    /// }
+    /// ```
+    pub fn insert_line_dedup(&mut self, line: Line<N, T>) -> bool {
+        let duplicate = match &line {
+            Line::Segment(s) => self.segments.iter().any(|x| x.id == s.id),
+            Line::Edge(e) => self.edges.iter().any(|x| {
+                x.sid1 == e.sid1
+                    && x.sid2 == e.sid2
+                    && x.beg1 == e.beg1
+                    && x.end1 == e.end1
+                    && x.beg2 == e.beg2
+                    && x.end2 == e.end2
+                    && x.alignment == e.alignment
+            }),
+            _ => false,
+        };
+        if !duplicate {
+            self.insert_line(line);
+        }
+        !duplicate
+    }
+
+    /// Returns the other end, with its orientation, of every edge that
+    /// reaches the segment ```seg_id```, in the order of the edges.\
+    /// A self-loop (an edge from the segment to itself) is returned once,
+    /// with the orientation of its second end; the edges whose references
+    /// can't be split are skipped
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// // E * 11+ 12- 1 5$ 2 6$ 4M
+    /// // E * 11+ 13+ 2 5$ 0 3 3M
+    /// let neighbors: Vec<_> = gfa2.neighbors(&"11".into()).collect();
+    /// assert_eq!(
+    ///     vec![("12".into(), Orientation::Backward), ("13".into(), Orientation::Forward)],
+    ///     neighbors
+    /// );
+    /// ```
+    pub fn neighbors<'a>(&'a self, seg_id: &'a N) -> impl Iterator<Item = (N, Orientation)> + 'a {
+        self.edges.iter().filter_map(move |edge| {
+            let (id1, orient1) = edge.sid1.split_ref()?;
+            let (id2, orient2) = edge.sid2.split_ref()?;
+            if id1 == *seg_id {
+                Some((id2, orient2))
+            } else if id2 == *seg_id {
+                Some((id1, orient1))
+            } else {
+                None
+            }
+        })
+    }
+
+    /// Returns the edge connecting each pair of consecutive members of
+    /// an o-group (None if no edge connects them).\
+    /// An edge matches a pair either in the same direction or, with
+    /// flipped orientations, in the opposite one.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// let parser: GFA2Parser<BString, OptionalFields> = GFA2Parser::new();
+    /// let gfa2 = parser.parse_file("./tests/gfa2_files/spec_q7.gfa").unwrap();
+    ///
+    /// // O 14 11+ 12- 13+
+    /// let alignments: Vec<_> = gfa2
+    ///     .path_edges(&gfa2.groups_o[0])
+    ///     .iter()
+    ///     .map(|e| e.map(|e| e.alignment.to_string()))
+    ///     .collect();
+    /// assert_eq!(vec![Some("4M".to_string()), Some("5M".to_string())], alignments);
+    /// ```
+    pub fn path_edges(&self, group: &GroupO<N, T>) -> Vec<Option<&Edge<N, T>>> {
+        let members: Vec<Option<(N, Orientation)>> = group.oriented_members().collect();
+
+        members
+            .windows(2)
+            .map(|pair| {
+                let (from, to) = match pair {
+                    [Some(from), Some(to)] => (from, to),
+                    _ => return None,
+                };
+                self.edges.iter().find(|edge| {
+                    let (sid1, sid2) = match (edge.sid1.split_ref(), edge.sid2.split_ref()) {
+                        (Some(sid1), Some(sid2)) => (sid1, sid2),
+                        _ => return false,
+                    };
+                    (sid1 == *from && sid2 == *to)
+                        || (sid1.0 == to.0
+                            && sid1.1 == to.1.flip()
+                            && sid2.0 == from.0
+                            && sid2.1 == from.1.flip())
+                })
+            })
+            .collect()
+    }
+}
+
+impl<T: OptFields> GFA2<usize, T> {
+    /// Converts a graph parsed with ```usize``` ids back to the names
+    /// the ids have been encoded from, keeping the orientation of the
+    /// references, so that it can be written in a readable form.\
+    /// The encoding of the ids can be inverted, so no table of the
+    /// names is needed.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// let parser: GFA2Parser<usize, ()> = GFA2Parser::new();
+    /// let gfa2 = parser.parse_file("./tests/gfa2_files/sample2.gfa").unwrap();
+    ///
+    /// let named: GFA2<BString, ()> = gfa2.restore_names();
+    /// println!("{}", named);
+    /// ```
+    pub fn restore_names(self) -> GFA2<BString, T> {
+        let name = |id: usize| BString::from(id.to_name());
+        let ref_name = |r: usize| match r.split_ref() {
+            Some((id, orient)) => BString::from(format!("{}{}", id.to_name(), orient)),
+            None => BString::from(r.to_string()),
+        };
+
+        GFA2 {
+            headers: self.headers,
+            segments: self
+                .segments
+                .into_iter()
+                .map(|s| Segment {
+                    id: name(s.id),
+                    len: s.len,
+                    sequence: s.sequence,
+                    tag: s.tag,
+                })
+                .collect(),
+            fragments: self
+                .fragments
+                .into_iter()
+                .map(|f| Fragment {
+                    id: name(f.id),
+                    ext_ref: ref_name(f.ext_ref),
+                    sbeg: f.sbeg,
+                    send: f.send,
+                    fbeg: f.fbeg,
+                    fend: f.fend,
+                    alignment: f.alignment,
+                    tag: f.tag,
+                })
+                .collect(),
+            edges: self
+                .edges
+                .into_iter()
+                .map(|e| Edge {
+                    id: name(e.id),
+                    sid1: ref_name(e.sid1),
+                    sid2: ref_name(e.sid2),
+                    beg1: e.beg1,
+                    end1: e.end1,
+                    beg2: e.beg2,
+                    end2: e.end2,
+                    alignment: e.alignment,
+                    tag: e.tag,
+                })
+                .collect(),
+            gaps: self
+                .gaps
+                .into_iter()
+                .map(|g| Gap {
+                    id: name(g.id),
+                    sid1: ref_name(g.sid1),
+                    sid2: ref_name(g.sid2),
+                    dist: g.dist,
+                    var: g.var,
+                    tag: g.tag,
+                })
+                .collect(),
+            // the members of the groups are stored with their names
+            groups_o: self
+                .groups_o
+                .into_iter()
+                .map(|o| GroupO::new(o.id, o.var_field, o.tag))
+                .collect(),
+            groups_u: self
+                .groups_u
+                .into_iter()
+                .map(|u| GroupU::new(u.id, u.var_field, u.tag))
+                .collect(),
+            comments: self.comments,
+            custom_records: self.custom_records,
+        }
+    }
+}
+
+impl<N: SegmentId + Clone + Eq + Hash, T: OptFields> GFA2<N, T> {
+    /// Returns the position in ```segments``` of every segment id, to
+    /// find the segments in constant time with segment_by_id.\
+    /// The index has to be built again if the segments change; if an id
+    /// is used by more than one segment, the first one is indexed
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// let index = gfa2.build_segment_index();
+    /// for edge in gfa2.edges.iter() {
+    ///     let (id, _) = edge.sid1.split_ref().unwrap();
+    ///     let segment = gfa2.segment_by_id(&id, &index);
+    /// }
+    /// ```
+    pub fn build_segment_index(&self) -> HashMap<N, usize> {
+        let mut index = HashMap::with_capacity(self.segments.len());
+        for (i, segment) in self.segments.iter().enumerate() {
+            index.entry(segment.id.clone()).or_insert(i);
+        }
+        index
+    }
+
+    /// Returns the segment with the given id, using an index built by
+    /// build_segment_index
+    pub fn segment_by_id(&self, id: &N, index: &HashMap<N, usize>) -> Option<&Segment<N, T>> {
+        self.segments.get(*index.get(id)?)
+    }
+
+    /// Keeps only the segments for which ```keep``` returns true, and
+    /// removes the lines that reference the removed ones:
+    /// - the fragments placed on them
+    /// - the edges and gaps that reach them
+    /// - the removed segments from the o-groups and the u-groups (and
+    ///   the groups that are left empty, while the groups that were
+    ///   already empty are kept)
+    ///
+    /// Returns the number of removed segments
+    pub fn retain_segments<F>(&mut self, mut keep: F) -> usize
+    where
+        F: FnMut(&Segment<N, T>) -> bool,
+    {
+        let mut removed: HashSet<N> = HashSet::new();
+        self.segments.retain(|s| {
+            keep(s) || {
+                removed.insert(s.id.clone());
+                false
+            }
+        });
+        if removed.is_empty() {
+            return 0;
+        }
+
+        let reaches_removed =
+            |sid: &N| matches!(sid.split_ref(), Some((id, _)) if removed.contains(&id));
+        self.fragments.retain(|f| !removed.contains(&f.id));
+        self.edges
+            .retain(|e| !reaches_removed(&e.sid1) && !reaches_removed(&e.sid2));
+        self.gaps
+            .retain(|g| !reaches_removed(&g.sid1) && !reaches_removed(&g.sid2));
+        // only the groups left empty by the removal are removed
+        self.groups_o.retain_mut(|group| {
+            if group.is_empty() {
+                return true;
+            }
+            let members: Vec<&[u8]> = group
+                .members()
+                .filter(|m| {
+                    !matches!(GroupO::<N, T>::parse_segment_id(m), Some((id, _)) if removed.contains(&id))
+                })
+                .collect();
+            group.var_field = members.join(&b' ').into();
+            !group.is_empty()
+        });
+        self.groups_u.retain_mut(|group| {
+            if group.is_empty() {
+                return true;
+            }
+            let members: Vec<&[u8]> = group
+                .members()
+                .filter(|m| !matches!(N::parse_opt_id(m), Some(id) if removed.contains(&id)))
+                .collect();
+            group.var_field = members.join(&b' ').into();
+            !group.is_empty()
+        });
+
+        removed.len()
+    }
+
+    /// Removes the segment with the given id (every segment with that
+    /// id, if it's duplicated) and the lines that reference it, as
+    /// retain_segments does: its fragments, edges and gaps are removed,
+    /// while the o-groups and the u-groups only lose the segment from
+    /// their members (and are removed if left empty).\
+    /// Returns false if there is no segment with the given id, leaving
+    /// the graph unchanged
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// let parser: GFA2Parser<BString, ()> = GFA2Parser::new();
+    /// let mut gfa2 = parser.parse_file("./tests/gfa2_files/spec_q7.gfa").unwrap();
+    ///
+    /// assert!(gfa2.remove_segment(&"12".into()));
+    /// assert!(!gfa2.remove_segment(&"12".into()));
+    /// ```
+    pub fn remove_segment(&mut self, id: &N) -> bool {
+        self.retain_segments(|s| &s.id != id) > 0
+    }
+
+    /// Returns a copy of the graph without the segments shorter than
+    /// ```min_len``` (a segment whose length is unknown counts as 0 long)
+    /// and the lines that reference them (see retain_segments)
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// let parser: GFA2Parser<BString, OptionalFields> = GFA2Parser::new();
+    /// let gfa2 = parser.parse_file("./tests/gfa2_files/big.gfa").unwrap();
+    ///
+    /// println!("{}", gfa2.filter_by_length(1000));
+    /// ```
+    pub fn filter_by_length(&self, min_len: u64) -> GFA2<N, T> {
+        let mut filtered = self.clone();
+        filtered.retain_segments(|s| s.length().unwrap_or(0) >= min_len);
+        filtered
+    }
+}
+
+/// returns the interval ```beg..end``` of a segment long ```len``` on
+/// the opposite strand, or the same interval if its positions (or the
+/// length) are unknown or out of the segment
+fn reverse_interval(beg: &[u8], end: &[u8], len: Option<u64>) -> (BString, BString) {
+    let reversed = || {
+        let len = len? as usize;
+        let (beg, _) = parse_position(beg)?;
+        let (end, _) = parse_position(end)?;
+        let position = |pos: usize| {
+            let pos = len.checked_sub(pos)?;
+            let end_marker = if pos == len { "$" } else { "" };
+            Some(BString::from(format!("{}{}", pos, end_marker)))
+        };
+        Some((position(end)?, position(beg)?))
+    };
+    reversed().unwrap_or_else(|| (beg.into(), end.into()))
 }
 
+/// returns a reference with the opposite orientation
+fn flip_ref<N: SegmentId>(sid: &N) -> Option<N> {
+    let (id, orient) = sid.split_ref()?;
+    N::parse_ref(format!("{}{}", id.to_name(), orient.flip()).as_bytes())
+}
+
+impl<N: SegmentId + Clone + Eq + Hash, T: OptFields> GFA2<N, T> {
+    /// Returns the graph of the opposite strand, where every segment has
+    /// the reverse complement of its sequence. To describe the same
+    /// alignments:
+    /// - the references of the fragments, edges and gaps are flipped
+    /// - the positions on the segments are recomputed on the opposite
+    ///   strand (the positions on the external fragments are kept)
+    /// - the CIGAR of the fragments is reversed, as both the aligned
+    ///   sequences are reverse complemented (a trace is kept as it is)
+    /// - the members of the o-groups are listed in the reverse order,
+    ///   so each path spells the reverse complement of its sequence
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// let parser: GFA2Parser<BString, OptionalFields> = GFA2Parser::new();
+    /// let gfa2 = parser.parse_file("./tests/gfa2_files/spec_q7.gfa").unwrap();
+    ///
+    /// let opposite = gfa2.reverse_complement();
+    /// assert_eq!(gfa2, opposite.reverse_complement());
+    /// ```
+    pub fn reverse_complement(&self) -> GFA2<N, T> {
+        let lengths: HashMap<N, Option<u64>> = self
+            .segments
+            .iter()
+            .map(|s| (s.id.clone(), s.length()))
+            .collect();
+        let length_of = |sid: &N| {
+            sid.split_ref()
+                .and_then(|(id, _)| lengths.get(&id).copied())
+                .flatten()
+        };
+        let flip = |sid: &N| flip_ref(sid).unwrap_or_else(|| sid.clone());
+
+        let mut reversed = self.clone();
+        for segment in reversed.segments.iter_mut() {
+            *segment = segment.reverse_complement();
+        }
+        for fragment in reversed.fragments.iter_mut() {
+            let len = lengths.get(&fragment.id).copied().flatten();
+            let (sbeg, send) = reverse_interval(&fragment.sbeg, &fragment.send, len);
+            fragment.sbeg = sbeg;
+            fragment.send = send;
+            fragment.ext_ref = flip(&fragment.ext_ref);
+            if let Ok(mut cigar) = Cigar::parse(&fragment.alignment) {
+                cigar.0.reverse();
+                fragment.alignment = cigar.to_string().into();
+            }
+        }
+        for edge in reversed.edges.iter_mut() {
+            let (beg1, end1) = reverse_interval(&edge.beg1, &edge.end1, length_of(&edge.sid1));
+            let (beg2, end2) = reverse_interval(&edge.beg2, &edge.end2, length_of(&edge.sid2));
+            edge.beg1 = beg1;
+            edge.end1 = end1;
+            edge.beg2 = beg2;
+            edge.end2 = end2;
+            edge.sid1 = flip(&edge.sid1);
+            edge.sid2 = flip(&edge.sid2);
+        }
+        for gap in reversed.gaps.iter_mut() {
+            gap.sid1 = flip(&gap.sid1);
+            gap.sid2 = flip(&gap.sid2);
+        }
+        for group in reversed.groups_o.iter_mut() {
+            let mut members: Vec<&[u8]> = group.members().collect();
+            members.reverse();
+            group.var_field = members.join(&b' ').into();
+        }
+        reversed
+    }
+
+    /// Like merge, but every segment of ```other``` whose id is already
+    /// used by a segment of the graph gets the id returned by ```rename```,
+    /// and the fragments, edges, gaps and groups of ```other``` that
+    /// reference it are updated too.\
+    /// The new ids are not checked, so ```rename``` has to return ids
+    /// not used by the segments of either graph
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// let mut pangenome = parser.parse_file("./chr1.gfa2").unwrap();
+    /// let chr2 = parser.parse_file("./chr2.gfa2").unwrap();
+    /// pangenome.merge_renaming(chr2, |id| format!("chr2_{}", id).into());
+    /// ```
+    pub fn merge_renaming<F>(&mut self, mut other: GFA2<N, T>, mut rename: F)
+    where
+        F: FnMut(&N) -> N,
+    {
+        let used: HashSet<&N> = self.segments.iter().map(|s| &s.id).collect();
+        let renamed: HashMap<N, N> = other
+            .segments
+            .iter()
+            .filter(|s| used.contains(&s.id))
+            .map(|s| (s.id.clone(), rename(&s.id)))
+            .collect();
+
+        if !renamed.is_empty() {
+            let rename_id = |id: &mut N| {
+                if let Some(new) = renamed.get(id) {
+                    *id = new.clone();
+                }
+            };
+            let rename_ref = |sid: &mut N| {
+                let new_ref = sid.split_ref().and_then(|(id, orient)| {
+                    let new = renamed.get(&id)?;
+                    N::parse_ref(format!("{}{}", new.to_name(), orient).as_bytes())
+                });
+                if let Some(new_ref) = new_ref {
+                    *sid = new_ref;
+                }
+            };
+            let rename_name = |name: &[u8]| match N::parse_id(name).and_then(|id| renamed.get(&id))
+            {
+                Some(new) => BString::from(new.to_name()),
+                None => BString::from(name),
+            };
+
+            for segment in other.segments.iter_mut() {
+                rename_id(&mut segment.id);
+            }
+            for fragment in other.fragments.iter_mut() {
+                rename_id(&mut fragment.id);
+            }
+            for edge in other.edges.iter_mut() {
+                rename_ref(&mut edge.sid1);
+                rename_ref(&mut edge.sid2);
+            }
+            for gap in other.gaps.iter_mut() {
+                rename_ref(&mut gap.sid1);
+                rename_ref(&mut gap.sid2);
+            }
+            for group in other.groups_o.iter_mut() {
+                let members: Vec<BString> = group
+                    .members()
+                    .map(|member| match member.split_last() {
+                        Some((&orient, name)) if orient == b'+' || orient == b'-' => {
+                            let mut member = rename_name(name);
+                            member.push(orient);
+                            member
+                        }
+                        _ => BString::from(member),
+                    })
+                    .collect();
+                group.var_field = bstr::join(" ", members).into();
+            }
+            for group in other.groups_u.iter_mut() {
+                let members: Vec<BString> = group.members().map(rename_name).collect();
+                group.var_field = bstr::join(" ", members).into();
+            }
+        }
+        self.merge(other);
+    }
+}
+
+impl<N: SegmentId + Eq + Hash, T: OptFields> GFA2<N, T> {
+    /// Removes the edges identical to a previous one in both the
+    /// segment references and all the positions and alignment (the id
+    /// and the tags are not compared), returning how many have been
+    /// removed. The first of the identical edges is kept
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// let removed = gfa2.dedup_edges();
+    /// println!("{} duplicated edges removed", removed);
+    /// ```
+    pub fn dedup_edges(&mut self) -> usize {
+        let mut seen = HashSet::new();
+        let keep: Vec<bool> = self
+            .edges
+            .iter()
+            .map(|e| {
+                seen.insert((
+                    &e.sid1,
+                    &e.sid2,
+                    &e.beg1,
+                    &e.end1,
+                    &e.beg2,
+                    &e.end2,
+                    &e.alignment,
+                ))
+            })
+            .collect();
+
+        let before = self.edges.len();
+        let mut keep = keep.into_iter();
+        self.edges.retain(|_| keep.next().unwrap_or(true));
+        before - self.edges.len()
+    }
+}
+
+/// Writes every line followed by a newline, so an empty GFA2 (e.g. a
+/// new one) is written as an empty string, without a default header
 impl<N: SegmentId, T: OptFields> fmt::Display for GFA2<N, T> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(
-            f,
-            "{}{}{}{}{}{}{}",
-            self.headers
-                .iter()
-                .fold(String::new(), |acc, str| acc + &str.to_string() + "\n"),
-            self.segments
+        for line in self.lines_iter() {
+            writeln!(f, "{}", line)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser_gfa2::GFA2Parser;
+
+    #[test]
+    fn edge_geometry() {
+        let dovetail: Edge<BString, ()> =
+            Edge::new(b"*", b"1+", b"2+", b"2", b"10$", b"0", b"8", b"8M");
+        assert!(dovetail.is_dovetail());
+        assert!(!dovetail.is_containment());
+
+        let containment: Edge<BString, ()> =
+            Edge::new(b"*", b"1+", b"2+", b"2", b"6", b"0", b"4$", b"4M");
+        assert!(!containment.is_dovetail());
+        assert!(containment.is_containment());
+
+        let internal: Edge<BString, ()> =
+            Edge::new(b"*", b"1+", b"2+", b"2", b"6", b"0", b"4", b"4M");
+        assert!(!internal.is_dovetail());
+        assert!(!internal.is_containment());
+    }
+
+    #[test]
+    fn segments_sorted_by_length() {
+        let gfa2: GFA2<BString, ()> = GFA2 {
+            segments: vec![
+                Segment::new(b"1", b"4", b"ACGT"),
+                Segment::new(b"2", b"*", b"ACGTACGT"),
+                Segment::new(b"3", b"8", b"ACGTACGT"),
+                Segment::new(b"4", b"4", b"TTTT"),
+            ],
+            ..GFA2::new()
+        };
+        let ids: Vec<_> = gfa2
+            .segments_by_length()
+            .iter()
+            .map(|s| s.id.to_string())
+            .collect();
+        assert_eq!(vec!["3", "1", "4", "2"], ids);
+        assert_eq!(
+            vec!["1", "2", "3", "4"],
+            gfa2.segment_ids()
                 .iter()
-                .fold(String::new(), |acc, str| acc + &str.to_string() + "\n"),
-            self.fragments
+                .map(|id| id.to_string())
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn lines_since_mark() {
+        let mut gfa2: GFA2<BString, ()> = GFA2::new();
+        gfa2.insert_line(Line::Segment(Segment::new(b"1", b"4", b"ACGT")));
+        let mark = gfa2.mark();
+        assert_eq!(0, gfa2.lines_since(&mark).count());
+
+        gfa2.insert_line(Line::Segment(Segment::new(b"2", b"4", b"TTTT")));
+        gfa2.insert_line(Line::Gap(Gap::new(b"g1", b"1+", b"2-", b"10", b"*")));
+        let segments: Vec<_> = gfa2.segments_since(mark.segments).iter().collect();
+        assert_eq!(vec![&gfa2.segments[1]], segments);
+
+        let lines: Vec<_> = gfa2.lines_since(&mark).collect();
+        assert_eq!(
+            vec![
+                LineRef::Segment(&gfa2.segments[1]),
+                LineRef::Gap(&gfa2.gaps[0])
+            ],
+            lines
+        );
+        assert!(gfa2.edges_since(5).is_empty());
+    }
+
+    #[test]
+    fn can_strip_tags() {
+        let parser: GFA2Parser<BString, OptionalFields> = GFA2Parser::new();
+        let mut gfa2 = parser.parse_file("./tests/gfa2_files/spec_q7.gfa").unwrap();
+        assert!(gfa2.segments.iter().any(|s| !s.tag.is_empty()));
+
+        let parser: GFA2Parser<BString, ()> = GFA2Parser::new();
+        let no_tags = parser.parse_file("./tests/gfa2_files/spec_q7.gfa").unwrap();
+        assert_eq!(no_tags, gfa2.without_tags());
+
+        gfa2.strip_tags();
+        assert!(gfa2.lines_iter().all(|line| match line {
+            LineRef::Header(h) => h.tag.is_empty(),
+            LineRef::Segment(s) => s.tag.is_empty(),
+            LineRef::Fragment(f) => f.tag.is_empty(),
+            LineRef::Edge(e) => e.tag.is_empty(),
+            LineRef::Gap(g) => g.tag.is_empty(),
+            LineRef::GroupO(o) => o.tag.is_empty(),
+            LineRef::GroupU(u) => u.tag.is_empty(),
+            LineRef::Comment(_) | LineRef::CustomRecord(_) => true,
+        }));
+    }
+
+    #[test]
+    fn can_dedup_edges() {
+        let mut gfa2: GFA2<BString, ()> = GFA2::new();
+        let edges = [
+            Edge::new(b"e1", b"1+", b"2+", b"2", b"10$", b"0", b"8", b"8M"),
+            Edge::new(b"*", b"1+", b"2+", b"2", b"10$", b"0", b"8", b"8M"),
+            Edge::new(b"*", b"1+", b"2+", b"2", b"10$", b"0", b"8", b"*"),
+            Edge::new(b"e2", b"1+", b"2+", b"2", b"10$", b"0", b"8", b"8M"),
+            Edge::new(b"*", b"2-", b"1-", b"0", b"8", b"2", b"10$", b"8M"),
+        ];
+        gfa2.edges.extend(edges.iter().cloned());
+
+        assert_eq!(2, gfa2.dedup_edges());
+        assert_eq!(
+            vec![edges[0].clone(), edges[2].clone(), edges[4].clone()],
+            gfa2.edges
+        );
+        assert_eq!(0, gfa2.dedup_edges());
+    }
+
+    #[test]
+    fn can_write_with_options() {
+        let mut gfa2: GFA2<BString, ()> = GFA2::new();
+        gfa2.insert_line(Line::Segment(Segment::new(b"1", b"4", b"ACGT")));
+        gfa2.insert_line(Line::Segment(Segment::new(b"2", b"4", b"TTTT")));
+
+        let mut out = vec![];
+        gfa2.write_with(&mut out, &WriteOptions::default()).unwrap();
+        assert_eq!("S\t1\t4\tACGT\nS\t2\t4\tTTTT", out.to_str().unwrap());
+
+        let options = WriteOptions {
+            newline: Newline::CrLf,
+            trailing_newline: true,
+        };
+        let mut out = vec![];
+        gfa2.write_with(&mut out, &options).unwrap();
+        assert_eq!("S\t1\t4\tACGT\r\nS\t2\t4\tTTTT\r\n", out.to_str().unwrap());
+
+        let mut out = vec![];
+        GFA2::<BString, ()>::new()
+            .write_with(&mut out, &options)
+            .unwrap();
+        assert!(out.is_empty());
+    }
+
+    #[test]
+    fn write_to_matches_display() {
+        let parser: GFA2Parser<BString, OptionalFields> = GFA2Parser::new();
+        let gfa2 = parser.parse_file("./tests/gfa2_files/sample2.gfa").unwrap();
+
+        let mut out = vec![];
+        gfa2.write_to(&mut out).unwrap();
+        assert_eq!(gfa2.to_string(), out.to_str().unwrap());
+    }
+
+    #[test]
+    fn can_restore_names() {
+        let parser: GFA2Parser<BString, OptionalFields> = GFA2Parser::new();
+        let named = parser.parse_file("./tests/gfa2_files/sample2.gfa").unwrap();
+        let parser: GFA2Parser<usize, OptionalFields> = GFA2Parser::new();
+        let gfa2 = parser.parse_file("./tests/gfa2_files/sample2.gfa").unwrap();
+
+        assert_eq!(named, gfa2.restore_names());
+    }
+
+    #[test]
+    fn gc_skew_windows() {
+        let segment: Segment<BString, ()> = Segment::new(b"A", b"10", b"GGcAATCCAT");
+        assert_eq!(vec![1.0 / 3.0, 0.0, -1.0], segment.gc_skew_windows(3));
+        assert_eq!(vec![-0.2], segment.gc_skew_windows(10));
+        assert!(segment.gc_skew_windows(11).is_empty());
+        assert!(segment.gc_skew_windows(0).is_empty());
+
+        let segment: Segment<BString, ()> = Segment::new(b"B", b"4", b"ATAT");
+        assert_eq!(vec![0.0, 0.0], segment.gc_skew_windows(2));
+        let segment: Segment<BString, ()> = Segment::new(b"C", b"4", b"*");
+        assert!(segment.gc_skew_windows(1).is_empty());
+        let segment: Segment<BString, ()> = Segment::new(b"D", b"4", b"G=.C");
+        assert_eq!(vec![1.0, -1.0], segment.gc_skew_windows(2));
+    }
+
+    #[test]
+    fn segment_gc_content() {
+        let mut segment: Segment<BString, ()> = Segment::new(b"A", b"8", b"GGcaNtAC");
+        assert_eq!([2, 2, 2, 1, 1], segment.base_counts());
+        assert_eq!(Some(4.0 / 7.0), segment.gc_content());
+        segment.sequence = "GGCAATAC".into();
+        segment.pack_sequence();
+        assert_eq!([3, 2, 2, 1, 0], segment.base_counts());
+        assert_eq!(Some(0.5), segment.gc_content());
+
+        let segment: Segment<BString, ()> = Segment::new(b"B", b"4", b"*");
+        assert_eq!([0; 5], segment.base_counts());
+        assert_eq!(None, segment.gc_content());
+        let segment: Segment<BString, ()> = Segment::new(b"C", b"3", b"NNn");
+        assert_eq!([0, 0, 0, 0, 3], segment.base_counts());
+        assert_eq!(None, segment.gc_content());
+    }
+
+    #[test]
+    fn can_compute_tag_memory() {
+        let lines = [
+            "S\t1\t4\tACGT\tRC:i:4",
+            "E\t*\t1+\t1-\t0\t4$\t0\t4$\t4M\tid:Z:e1",
+        ];
+        let field = std::mem::size_of::<OptField>();
+
+        let parser: GFA2Parser<BString, OptionalFields> = GFA2Parser::new();
+        let gfa2 = parser.parse_lines(lines.iter()).unwrap();
+        assert_eq!(
+            2 * field + "RC:i:4".len() + "id:Z:e1".len(),
+            gfa2.tag_memory()
+        );
+
+        let parser: GFA2Parser<BString, ()> = GFA2Parser::new();
+        let gfa2 = parser.parse_lines(lines.iter()).unwrap();
+        assert_eq!(0, gfa2.tag_memory());
+    }
+
+    #[test]
+    fn can_reverse_complement_graph() {
+        let lines = [
+            "S\t1\t8\tACGTACGT",
+            "S\t2\t4\tAACC",
+            "F\t1\tr1+\t0\t3\t10\t13\t2M1I",
+            "E\te1\t1+\t2-\t6\t8$\t2\t4$\t2M",
+            "G\tg1\t1-\t2+\t10\t*",
+            "O\tp1\t1+ e1+ 2-",
+        ];
+        let parser: GFA2Parser<BString, ()> = GFA2Parser::new();
+        let gfa2: GFA2<BString, ()> = parser.parse_lines(lines.iter()).unwrap();
+
+        let reversed = gfa2.reverse_complement();
+        assert_eq!(
+            vec![
+                "S\t1\t8\tACGTACGT",
+                "S\t2\t4\tGGTT",
+                "F\t1\tr1-\t5\t8$\t10\t13\t1I2M",
+                "E\te1\t1-\t2+\t0\t2\t0\t2\t2M",
+                "G\tg1\t1+\t2-\t10\t*",
+                "O\tp1\t2- e1+ 1+",
+            ],
+            reversed
+                .lines_iter()
+                .map(|line| line.to_string())
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(gfa2, reversed.reverse_complement());
+    }
+
+    #[test]
+    fn can_index_segments() {
+        let parser: GFA2Parser<BString, ()> = GFA2Parser::new();
+        let gfa2: GFA2<BString, ()> = parser.parse_file("./tests/gfa2_files/spec_q7.gfa").unwrap();
+        let index = gfa2.build_segment_index();
+
+        assert_eq!(3, index.len());
+        for edge in gfa2.edges.iter() {
+            let (id, _) = edge.sid2.split_ref().unwrap();
+            assert_eq!(id, gfa2.segment_by_id(&id, &index).unwrap().id);
+        }
+        assert_eq!(None, gfa2.segment_by_id(&"14".into(), &index));
+
+        let parser: GFA2Parser<usize, ()> = GFA2Parser::new();
+        let gfa2: GFA2<usize, ()> = parser.parse_file("./tests/gfa2_files/spec_q7.gfa").unwrap();
+        let index = gfa2.build_segment_index();
+        let id = usize::parse_id(b"12").unwrap();
+        assert_eq!("TCAAGG", gfa2.segment_by_id(&id, &index).unwrap().sequence);
+    }
+
+    #[test]
+    fn can_iterate_neighbors() {
+        use Orientation::*;
+
+        let lines = [
+            "S\t1\t4\tACGT",
+            "S\t2\t4\tACGT",
+            "S\t3\t4\tACGT",
+            "E\te1\t1+\t2-\t2\t4$\t2\t4$\t2M",
+            "E\te2\t3+\t1+\t2\t4$\t0\t2\t2M",
+            "E\te3\t1-\t1+\t0\t2\t0\t2\t2M",
+        ];
+        let parser: GFA2Parser<BString, ()> = GFA2Parser::new();
+        let gfa2: GFA2<BString, ()> = parser.parse_lines(lines.iter()).unwrap();
+
+        let neighbors: Vec<_> = gfa2.neighbors(&"1".into()).collect();
+        assert_eq!(
+            vec![
+                ("2".into(), Backward),
+                ("3".into(), Forward),
+                ("1".into(), Forward)
+            ],
+            neighbors
+        );
+        let neighbors: Vec<_> = gfa2.neighbors(&"2".into()).collect();
+        assert_eq!(vec![("1".into(), Forward)], neighbors);
+        assert_eq!(0, gfa2.neighbors(&"4".into()).count());
+    }
+
+    #[test]
+    fn group_membership() {
+        let ogroup: GroupO<BString, ()> = GroupO::new("P1".into(), "36+  50- 7".into(), ());
+        assert_eq!(3, ogroup.len());
+        assert!(ogroup.contains(&"36".into()));
+        assert!(ogroup.contains(&"50".into()));
+        assert!(!ogroup.contains(&"7".into()));
+        assert!(!ogroup.contains(&"3".into()));
+
+        let ugroup: GroupU<BString, ()> = GroupU::new("SG1".into(), "16 24".into(), ());
+        assert!(ugroup.contains(&"24".into()));
+        assert!(!ugroup.contains(&"2".into()));
+
+        let ogroup: GroupO<usize, ()> = GroupO::new("P1".into(), "36+ 50-".into(), ());
+        assert!(ogroup.contains(&usize::parse_id(b"50").unwrap()));
+        assert!(!ogroup.contains(&usize::parse_id(b"5").unwrap()));
+        let ugroup: GroupU<usize, ()> = GroupU::new("SG1".into(), "16 24".into(), ());
+        assert!(ugroup.contains(&usize::parse_id(b"16").unwrap()));
+        assert!(!ugroup.contains(&usize::parse_id(b"1").unwrap()));
+    }
+
+    #[test]
+    fn edge_positions() {
+        let edge: Edge<BString, ()> =
+            Edge::new(b"*", b"2+", b"45+", b"2531", b"2591$", b"0", b"60", b"60M");
+        assert_eq!(Some((2531, false)), edge.beg1_pos().ok());
+        assert_eq!(Some((2591, true)), edge.end1_pos().ok());
+        assert_eq!(Some((0, false)), edge.beg2_pos().ok());
+        assert_eq!(Some((60, false)), edge.end2_pos().ok());
+        assert_eq!(Some((60, 60)), edge.overlap_lengths().ok());
+
+        let edge: Edge<BString, ()> =
+            Edge::new(b"*", b"2+", b"45+", b"10", b"5", b"-1", b"60", b"*");
+        assert!(matches!(
+            edge.beg2_pos(),
+            Err(ParseFieldError::InvalidField("beg2"))
+        ));
+        assert!(matches!(
+            edge.overlap_lengths(),
+            Err(ParseFieldError::InvalidField("beg2"))
+        ));
+        let edge: Edge<BString, ()> = Edge::new(b"*", b"2+", b"45+", b"10", b"5", b"0", b"5", b"*");
+        assert!(matches!(
+            edge.overlap_lengths(),
+            Err(ParseFieldError::InvalidField("end1"))
+        ));
+    }
+
+    #[test]
+    fn placeholder_sequence() {
+        let mut segments: Vec<Segment<BString, ()>> = vec![
+            Segment::new(b"A", b"4", b"ACGT"),
+            Segment::new(b"B", b"10", b"*"),
+            Segment::new(b"C", b"3", b"AAC"),
+        ];
+        segments[2].pack_sequence();
+        let has_sequence: Vec<_> = segments.iter().map(|s| s.has_sequence()).collect();
+        assert_eq!(vec![true, false, true], has_sequence);
+        assert_eq!(None, segments[1].known_sequence());
+        assert_eq!(Some(&b"AAC"[..]), segments[2].known_sequence().as_deref());
+        // the placeholder is still written back
+        assert_eq!("S\tB\t10\t*", segments[1].to_string());
+    }
+
+    #[test]
+    fn can_spell_group() {
+        let mut segments: Vec<Segment<BString, ()>> = vec![
+            Segment::new(b"1", b"4", b"ACGT"),
+            Segment::new(b"2", b"3", b"AAC"),
+            Segment::new(b"3", b"*", b"*"),
+        ];
+        segments[1].pack_sequence();
+        let index: HashMap<BString, &Segment<BString, ()>> =
+            segments.iter().map(|s| (s.id.clone(), s)).collect();
+
+        let ogroup: GroupO<BString, ()> = GroupO::new("P1".into(), "1+ 2- 1-".into(), ());
+        assert_eq!(Some("ACGTGTTACGT".into()), ogroup.spell(&index));
+        let ogroup: GroupO<BString, ()> = GroupO::new("P2".into(), "1+ 3+".into(), ());
+        assert_eq!(None, ogroup.spell(&index));
+        let ogroup: GroupO<BString, ()> = GroupO::new("P3".into(), "1+ e1+".into(), ());
+        assert_eq!(None, ogroup.spell(&index));
+    }
+
+    #[test]
+    fn can_merge_graphs() {
+        let parser: GFA2Parser<BString, ()> = GFA2Parser::new();
+        let first = ["H\tVN:Z:2.0", "S\t1\t4\tACGT", "S\t2\t4\tACGT"];
+        let second = [
+            "H\tVN:Z:2.0",
+            "S\t1\t2\tAC",
+            "S\t3\t2\tGT",
+            "F\t1\tr1+\t0\t2$\t0\t2\t*",
+            "E\t*\t1+\t3-\t0\t2$\t0\t2$\t2M",
+            "G\t*\t3+\t1-\t10\t*",
+            "O\tP1\t1+ 3-",
+            "U\tS1\t1 3",
+        ];
+        let mut gfa2: GFA2<BString, ()> = parser.parse_lines(first.iter()).unwrap();
+        let other: GFA2<BString, ()> = parser.parse_lines(second.iter()).unwrap();
+
+        let mut merged = gfa2.clone();
+        merged.merge(other.clone());
+        assert_eq!(1, merged.headers.len());
+        assert_eq!(4, merged.segments.len());
+        assert_eq!(1, merged.edges.len());
+
+        gfa2.merge_renaming(other, |id| format!("b{}", id).into());
+        assert_eq!(1, gfa2.headers.len());
+        let ids: Vec<_> = gfa2.segments.iter().map(|s| s.id.to_string()).collect();
+        assert_eq!(vec!["1", "2", "b1", "3"], ids);
+        assert_eq!("b1", gfa2.fragments[0].id);
+        assert_eq!("b1+", gfa2.edges[0].sid1);
+        assert_eq!("3-", gfa2.edges[0].sid2);
+        assert_eq!("b1-", gfa2.gaps[0].sid2);
+        assert_eq!("b1+ 3-", gfa2.groups_o[0].var_field);
+        assert_eq!("b1 3", gfa2.groups_u[0].var_field);
+    }
+
+    #[test]
+    fn can_insert_lines_without_duplicates() {
+        let lines = [
+            "S\t1\t4\tACGT",
+            "S\t2\t4\tACGT",
+            "E\te1\t1+\t2-\t2\t4$\t2\t4$\t2M",
+        ];
+        let parser: GFA2Parser<BString, ()> = GFA2Parser::new();
+        let mut gfa2: GFA2<BString, ()> = parser.parse_lines(lines.iter()).unwrap();
+
+        let other = [
+            "S\t2\t5\tACGTT",
+            "S\t3\t4\tACGT",
+            "E\te2\t1+\t2-\t2\t4$\t2\t4$\t2M",
+            "E\t*\t1+\t2+\t2\t4$\t2\t4$\t2M",
+            "G\t*\t1+\t2+\t10\t*",
+        ];
+        let other: GFA2<BString, ()> = parser.parse_lines(other.iter()).unwrap();
+        let inserted: Vec<bool> = other
+            .lines_into_iter()
+            .map(|line| gfa2.insert_line_dedup(line))
+            .collect();
+        assert_eq!(vec![false, true, false, true, true], inserted);
+        assert_eq!(3, gfa2.segments.len());
+        assert_eq!("ACGT", gfa2.segments[1].sequence);
+        assert_eq!(
+            vec!["e1", "*"],
+            gfa2.edges
                 .iter()
-                .fold(String::new(), |acc, str| acc + &str.to_string() + "\n"),
-            self.edges
+                .map(|e| e.id.to_string())
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(1, gfa2.gaps.len());
+    }
+
+    #[test]
+    fn can_filter_by_length() {
+        let lines = [
+            "S\t1\t8\tACGTACGT",
+            "S\t2\t2\tAC",
+            "S\t3\t6\tACGTAC",
+            "S\t4\t*\tACGT",
+            "F\t2\tr1+\t0\t2$\t0\t2\t*",
+            "F\t3\tr2+\t0\t2\t0\t2\t*",
+            "E\te1\t1+\t2+\t6\t8$\t0\t2$\t2M",
+            "E\te2\t1+\t3+\t6\t8$\t0\t2\t2M",
+            "G\tg1\t2+\t3+\t10\t*",
+            "O\tp1\t1+ 2+",
+            "O\tp2\t1+ e2+ 3+",
+            "U\tu1\t1 2 4",
+            "U\tu2\t2",
+        ];
+        let parser: GFA2Parser<BString, ()> = GFA2Parser::new();
+        let gfa2: GFA2<BString, ()> = parser.parse_lines(lines.iter()).unwrap();
+
+        let filtered = gfa2.filter_by_length(5);
+        assert_eq!(
+            vec!["1", "3"],
+            filtered
+                .segments
                 .iter()
-                .fold(String::new(), |acc, str| acc + &str.to_string() + "\n"),
-            self.gaps
+                .map(|s| s.id.to_string())
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(1, filtered.fragments.len());
+        assert_eq!("e2", filtered.edges[0].id);
+        assert_eq!(1, filtered.edges.len());
+        assert!(filtered.gaps.is_empty());
+        assert_eq!(
+            vec!["O\tp1\t1+", "O\tp2\t1+ e2+ 3+"],
+            filtered
+                .groups_o
                 .iter()
-                .fold(String::new(), |acc, str| acc + &str.to_string() + "\n"),
-            self.groups_o
+                .map(|o| o.to_string())
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(
+            vec!["U\tu1\t1"],
+            filtered
+                .groups_u
                 .iter()
-                .fold(String::new(), |acc, str| acc + &str.to_string() + "\n"),
-            self.groups_u
+                .map(|u| u.to_string())
+                .collect::<Vec<_>>()
+        );
+
+        assert_eq!(gfa2, gfa2.filter_by_length(0));
+
+        let mut removed = gfa2.clone();
+        assert!(removed.remove_segment(&"2".into()));
+        assert_eq!(3, removed.segments.len());
+        assert_eq!(1, removed.fragments.len());
+        assert_eq!(1, removed.edges.len());
+        assert!(removed.gaps.is_empty());
+        assert_eq!(2, removed.groups_o.len());
+        assert_eq!("1+", removed.groups_o[0].var_field);
+        assert_eq!(1, removed.groups_u.len());
+        assert_eq!("1 4", removed.groups_u[0].var_field);
+        assert!(!removed.remove_segment(&"2".into()));
+        assert!(!removed.remove_segment(&"e1".into()));
+        assert_eq!(3, removed.segments.len());
+    }
+
+    #[test]
+    fn remove_segment_keeps_the_other_ogroup_members() {
+        let lines = [
+            "S\t1\t4\tACGT",
+            "S\t2\t4\tACGT",
+            "O\tp1\t1+ 2-",
+            "O\tp2\t2+",
+        ];
+        let parser: GFA2Parser<BString, ()> = GFA2Parser::new();
+        let mut gfa2: GFA2<BString, ()> = parser.parse_lines(lines.iter()).unwrap();
+
+        assert!(gfa2.remove_segment(&"2".into()));
+        assert_eq!(1, gfa2.groups_o.len());
+        assert_eq!("p1", gfa2.groups_o[0].id);
+        assert_eq!("1+", gfa2.groups_o[0].var_field);
+    }
+
+    #[test]
+    fn group_members_allow_multiple_spaces() {
+        let lines = ["S\t1\t4\tACGT", "S\t2\t3\tAAC", "S\t3\t2\tGT"];
+        let parser: GFA2Parser<BString, ()> = GFA2Parser::new();
+        let mut gfa2: GFA2<BString, ()> = parser.parse_lines(lines.iter()).unwrap();
+        gfa2.groups_o
+            .push(GroupO::new("p1".into(), "1+  2-   3+".into(), ()));
+        gfa2.groups_u
+            .push(GroupU::new("u1".into(), "1  3".into(), ()));
+        // the groups that were already empty are kept
+        gfa2.groups_o.push(GroupO::new("p2".into(), "".into(), ()));
+        gfa2.groups_u.push(GroupU::new("u2".into(), " ".into(), ()));
+
+        let ogroup = &gfa2.groups_o[0];
+        assert_eq!(3, ogroup.len());
+        assert!(ogroup.oriented_members().all(|member| member.is_some()));
+        let segments: HashMap<BString, &Segment<BString, ()>> =
+            gfa2.segments.iter().map(|s| (s.id.clone(), s)).collect();
+        assert_eq!(Some("ACGTGTTGT".into()), ogroup.spell(&segments));
+        assert_eq!(
+            vec!["3+", "2-", "1+"],
+            gfa2.reverse_complement().groups_o[0]
                 .iter()
-                .fold(String::new(), |acc, str| acc + &str.to_string() + "\n"),
-        )
+                .map(|(id, orient)| format!("{}{}", id, orient))
+                .collect::<Vec<_>>()
+        );
+
+        assert!(gfa2.remove_segment(&"2".into()));
+        assert_eq!("1+ 3+", gfa2.groups_o[0].var_field);
+        assert_eq!("1 3", gfa2.groups_u[0].var_field);
+        assert_eq!(2, gfa2.groups_o.len());
+        assert_eq!(2, gfa2.groups_u.len());
+    }
+
+    #[test]
+    fn line_as_ref() {
+        let segment: Segment<BString, ()> = Segment::new(b"1", b"4", b"ACGT");
+        let line = Line::Segment(segment.clone());
+
+        assert_eq!(Some(&segment), line.as_ref().some_segment());
+        assert_eq!(None, LineRef::from(&line).some_edge());
+        let gfa2: GFA2<BString, ()> = GFA2 {
+            segments: vec![segment],
+            ..GFA2::new()
+        };
+        assert_eq!(gfa2.lines_iter().next(), Some(line.as_ref()));
+    }
+
+    #[test]
+    fn blunt_edges() {
+        let blunt: Edge<BString, ()> =
+            Edge::new(b"*", b"1+", b"2+", b"4$", b"4$", b"0", b"0", b"*");
+        assert!(blunt.is_blunt());
+        let blunt: Edge<BString, ()> = Edge::new(b"*", b"1-", b"2+", b"0", b"0", b"0", b"0", b"0M");
+        assert!(blunt.is_blunt());
+
+        let overlap: Edge<BString, ()> =
+            Edge::new(b"*", b"1+", b"2+", b"2", b"4$", b"0", b"2", b"2M");
+        assert!(!overlap.is_blunt());
+        let mismatch: Edge<BString, ()> =
+            Edge::new(b"*", b"1+", b"2+", b"4$", b"4$", b"0", b"0", b"2M");
+        assert!(!mismatch.is_blunt());
+        // both the intervals are at the start of their segment
+        let internal: Edge<BString, ()> =
+            Edge::new(b"*", b"1+", b"2+", b"0", b"0", b"3", b"3", b"*");
+        assert!(!internal.is_blunt());
+    }
+
+    #[test]
+    fn groups_from_members() {
+        use Orientation::*;
+
+        let ogroup: GroupO<BString, ()> = GroupO::from_members(
+            "P1".into(),
+            &[
+                ("36".into(), Forward),
+                ("53_38".into(), Forward),
+                ("50".into(), Backward),
+            ],
+        );
+        assert_eq!("O\tP1\t36+ 53_38+ 50-", ogroup.to_string());
+        let members: Vec<_> = ogroup.iter().map(|(seg, o)| (seg.to_string(), o)).collect();
+        assert_eq!(
+            vec![
+                ("36".to_string(), Forward),
+                ("53_38".into(), Forward),
+                ("50".into(), Backward)
+            ],
+            members
+        );
+
+        let (s11, s13) = (
+            usize::parse_id(b"11").unwrap(),
+            usize::parse_id(b"13").unwrap(),
+        );
+        let ogroup: GroupO<usize, ()> =
+            GroupO::from_members("P1".into(), &[(s11, Forward), (s13, Backward)]);
+        assert_eq!(
+            vec![(s11, Forward), (s13, Backward)],
+            ogroup.iter().collect::<Vec<_>>()
+        );
+
+        let ugroup: GroupU<BString, ()> =
+            GroupU::from_members("SG1".into(), &["16".into(), "SG2".into(), "51_24".into()]);
+        assert_eq!("U\tSG1\t16 SG2 51_24", ugroup.to_string());
+        let (s16, s24) = (
+            usize::parse_id(b"16").unwrap(),
+            usize::parse_id(b"24").unwrap(),
+        );
+        let ugroup: GroupU<usize, ()> = GroupU::from_members("SG1".into(), &[s16, s24]);
+        assert_eq!("U\tSG1\t16 24", ugroup.to_string());
+        assert_eq!(vec![s16, s24], ugroup.iter().collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn can_find_path_edges() {
+        let lines = [
+            "S\t11\t5\tACCTT",
+            "S\t12\t6\tTCAAGG",
+            "S\t13\t7\tCTTGATT",
+            "S\t14\t4\tACGT",
+            "E\t*\t11+\t12-\t1\t5$\t2\t6$\t4M",
+            // the edge 12- -> 13+ written as 13- -> 12+
+            "E\t*\t13-\t12+\t0\t5\t0\t5\t5M",
+            "O\t15\t11+ 12- 13+ 14+",
+        ];
+        let parser: GFA2Parser<BString, ()> = GFA2Parser::new();
+        let gfa2: GFA2<BString, ()> = parser.parse_lines(lines.iter()).unwrap();
+
+        let alignments: Vec<_> = gfa2
+            .path_edges(&gfa2.groups_o[0])
+            .iter()
+            .map(|e| e.map(|e| e.alignment.to_string()))
+            .collect();
+        assert_eq!(
+            vec![Some("4M".to_string()), Some("5M".to_string()), None],
+            alignments
+        );
+
+        let parser: GFA2Parser<usize, ()> = GFA2Parser::new();
+        let gfa2: GFA2<usize, ()> = parser.parse_lines(lines.iter()).unwrap();
+        let found: Vec<_> = gfa2
+            .path_edges(&gfa2.groups_o[0])
+            .iter()
+            .map(Option::is_some)
+            .collect();
+        assert_eq!(vec![true, true, false], found);
+    }
+
+    #[test]
+    fn graphs_can_be_hashed() {
+        use std::collections::HashSet;
+
+        let parser: GFA2Parser<BString, OptionalFields> = GFA2Parser::new();
+        let gfa2 = parser.parse_file("./tests/gfa2_files/spec_q7.gfa").unwrap();
+        let same = parser.parse_file("./tests/gfa2_files/spec_q7.gfa").unwrap();
+        let other = parser.parse_file("./tests/gfa2_files/sample2.gfa").unwrap();
+
+        let graphs: HashSet<_> = vec![gfa2, same, other].into_iter().collect();
+        assert_eq!(2, graphs.len());
+    }
+
+    #[test]
+    fn segment_reverse_complement() {
+        let segment: Segment<BString, ()> = Segment::new(b"A", b"5", b"AACGN");
+        let rc: Segment<BString, ()> = Segment::new(b"B", b"5", b"ncgtt");
+        let same: Segment<BString, ()> = Segment::new(b"C", b"5", b"aacgn");
+        let other: Segment<BString, ()> = Segment::new(b"D", b"5", b"AACGT");
+        let empty: Segment<BString, ()> = Segment::new(b"E", b"5", b"*");
+
+        assert_eq!(
+            BString::from("NCGTT"),
+            reverse_complement(&segment.sequence_bytes())
+        );
+        assert!(segment.is_rc_of(&rc));
+        assert!(rc.is_rc_of(&segment));
+        assert!(!segment.is_rc_of(&same));
+        assert!(segment.is_same_or_rc(&same));
+        assert!(segment.is_same_or_rc(&rc));
+        assert!(!segment.has_reference_relative_bases());
+
+        let relative: Segment<BString, ()> = Segment::new(b"F", b"5", b"A=.CG");
+        assert!(relative.has_reference_relative_bases());
+        assert_eq!(
+            BString::from("CGN=T"),
+            relative.reverse_complement().sequence
+        );
+        assert!(!segment.is_same_or_rc(&other));
+        assert!(!empty.is_same_or_rc(&empty));
+    }
+
+    #[test]
+    fn can_iterate_all_references() {
+        use Orientation::*;
+
+        let lines = [
+            "S\t1\t4\tACGT",
+            "S\t2\t4\tACGT",
+            "F\t1\tread-\t0\t4$\t0\t4\t*",
+            "E\t*\t1+\t2-\t2\t4$\t2\t4$\t2M",
+            "G\tg1\t2+\t1-\t10\t*",
+            "O\tp1\t1+ 2-",
+            "U\tu1\t1 2",
+        ];
+        let parser: GFA2Parser<BString, ()> = GFA2Parser::new();
+        let gfa2: GFA2<BString, ()> = parser.parse_lines(lines.iter()).unwrap();
+
+        let refs: Vec<_> = gfa2
+            .all_references()
+            .map(|(id, orient, source)| (id.to_string(), orient, source))
+            .collect();
+        let expected: Vec<(String, _, _)> = vec![
+            ("1".into(), Backward, RefSource::Fragment),
+            ("1".into(), Forward, RefSource::Edge),
+            ("2".into(), Backward, RefSource::Edge),
+            ("2".into(), Forward, RefSource::Gap),
+            ("1".into(), Backward, RefSource::Gap),
+            ("1".into(), Forward, RefSource::GroupO),
+            ("2".into(), Backward, RefSource::GroupO),
+        ];
+        assert_eq!(expected, refs);
+
+        let parser: GFA2Parser<usize, ()> = GFA2Parser::new();
+        let gfa2: GFA2<usize, ()> = parser.parse_lines(lines.iter()).unwrap();
+        let refs: Vec<_> = gfa2
+            .all_references()
+            .map(|(id, orient, source)| (id.to_name(), orient, source))
+            .collect();
+        assert_eq!(expected, refs);
+    }
+
+    #[test]
+    fn canonical_sort_ignores_line_order() {
+        let lines = [
+            "H\tVN:Z:2.0",
+            "H\tul:Z:https://github.com/pmelsted/GFA-spec/issues/7",
+            "S\t11\t5\tACCTT",
+            "S\t12\t6\tTCAAGG",
+            "E\t*\t11+\t12-\t1\t5$\t2\t6$\t4M",
+            "E\t*\t12-\t11+\t0\t5\t0\t5\t5M",
+            "O\t14\t11+ 12-",
+            "O\t13\t12+",
+        ];
+        let parser: GFA2Parser<BString, OptionalFields> = GFA2Parser::new();
+        let mut gfa2 = parser.parse_lines(lines.iter()).unwrap();
+        let mut shuffled = parser.parse_lines(lines.iter().rev()).unwrap();
+        assert_ne!(gfa2, shuffled);
+
+        gfa2.sort_canonical();
+        shuffled.sort_canonical();
+        assert_eq!(gfa2, shuffled);
+        assert_eq!(gfa2.to_string(), shuffled.to_string());
+        assert_eq!(Some("VN:Z:2.0".into()), gfa2.headers[0].version);
+        assert_eq!("13", gfa2.groups_o[0].id);
+        assert_eq!(Some(GFAVersion::GFA2), gfa2.headers[0].gfa_version());
+        // the default header stores only the value of the version
+        let header: Header<()> = Header::default();
+        assert_eq!(Some(GFAVersion::GFA2), header.gfa_version());
+        assert_eq!(None, Header::<()>::new(None).gfa_version());
+
+        let mut tags = parser
+            .parse_lines(["S\t11\t5\tACCTT\tzz:i:1\tRC:i:4"].iter())
+            .unwrap();
+        let mut other = parser
+            .parse_lines(["S\t11\t5\tACCTT\tRC:i:4\tzz:i:1"].iter())
+            .unwrap();
+        assert_ne!(tags, other);
+        tags.sort_canonical();
+        other.sort_canonical();
+        assert_eq!(tags, other);
+    }
+
+    #[test]
+    fn can_sort_lines() {
+        let lines = [
+            "S\t12\t6\tTCAAGG",
+            "S\t2\t5\tACCTT",
+            "S\t11\t5\tACCTT",
+            "E\t1\t12-\t11+\t0\t5\t0\t5\t5M",
+            "E\t2\t11+\t12-\t1\t5$\t2\t6$\t4M",
+            "E\t3\t11+\t12-\t0\t1\t0\t1\t1M",
+            "G\t*\t12-\t2+\t10\t*",
+            "G\t*\t11+\t2+\t10\t*",
+            "U\tb\t11 12",
+            "U\ta\t2",
+        ];
+        let parser: GFA2Parser<BString, ()> = GFA2Parser::new();
+        let mut gfa2 = parser.parse_lines(lines.iter()).unwrap();
+        let mut shuffled = parser.parse_lines(lines.iter().rev()).unwrap();
+        gfa2.sort();
+        shuffled.sort();
+
+        let ids: Vec<_> = gfa2.segments.iter().map(|s| s.id.to_string()).collect();
+        assert_eq!(vec!["11", "12", "2"], ids);
+        // the edges with the same segments are sorted by their other fields
+        let edges: Vec<_> = gfa2.edges.iter().map(|e| e.id.to_string()).collect();
+        assert_eq!(vec!["2", "3", "1"], edges);
+        assert_eq!("11+", gfa2.gaps[0].sid1);
+        assert_eq!("a", gfa2.groups_u[0].id);
+        assert_eq!(gfa2, shuffled);
+
+        let parser: GFA2Parser<usize, ()> = GFA2Parser::new();
+        let mut gfa2 = parser.parse_lines(lines.iter()).unwrap();
+        gfa2.sort();
+        // the encoded ids are sorted by length first
+        let ids: Vec<_> = gfa2.segments.iter().map(|s| s.id.to_name()).collect();
+        assert_eq!(vec!["2", "11", "12"], ids);
+    }
+
+    #[test]
+    fn segment_subsequence() {
+        let segment: Segment<BString, ()> = Segment::new(b"A", b"10", b"AAAAAAACGT");
+        assert_eq!(Some("ACG".into()), segment.subsequence(6, 9));
+        assert_eq!(Some("AAAAAAACGT".into()), segment.subsequence(0, 10));
+        assert_eq!(Some("".into()), segment.subsequence(10, 10));
+        assert_eq!(None, segment.subsequence(6, 11));
+        assert_eq!(None, segment.subsequence(9, 6));
+
+        let no_sequence: Segment<BString, ()> = Segment::new(b"B", b"10", b"*");
+        assert_eq!(None, no_sequence.subsequence(0, 1));
     }
 }
@@ -0,0 +1,243 @@
+/// file that defines the CIGAR strings used as alignments and
+/// overlaps, and how to use them to join the sequences they align
+use crate::gfa2::orientation::{reverse_complement, Orientation};
+use crate::parser_gfa2::ParseFieldError;
+
+use bstr::BString;
+
+/// The operations of a CIGAR string
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum CigarOp {
+    M,
+    I,
+    D,
+    N,
+    S,
+    H,
+    P,
+    X,
+    E, // '=' char
+}
+
+impl CigarOp {
+    /// Returns the operation of a CIGAR char, if it is one of
+    /// ```MIDNSHPX=```
+    pub fn from_byte(byte: u8) -> Option<Self> {
+        use CigarOp::*;
+        match byte {
+            b'M' => Some(M),
+            b'I' => Some(I),
+            b'D' => Some(D),
+            b'N' => Some(N),
+            b'S' => Some(S),
+            b'H' => Some(H),
+            b'P' => Some(P),
+            b'X' => Some(X),
+            b'=' => Some(E),
+            _ => None,
+        }
+    }
+
+    /// Returns the CIGAR char of the operation
+    pub fn as_byte(&self) -> u8 {
+        use CigarOp::*;
+        match self {
+            M => b'M',
+            I => b'I',
+            D => b'D',
+            N => b'N',
+            S => b'S',
+            H => b'H',
+            P => b'P',
+            X => b'X',
+            E => b'=',
+        }
+    }
+
+    /// Checks if the operation consumes bases of the reference (the
+    /// first sequence)
+    pub fn consumes_reference(&self) -> bool {
+        use CigarOp::*;
+        matches!(self, M | D | N | X | E)
+    }
+
+    /// Checks if the operation consumes bases of the query (the
+    /// second sequence)
+    pub fn consumes_query(&self) -> bool {
+        use CigarOp::*;
+        matches!(self, M | I | S | X | E)
+    }
+}
+
+impl std::fmt::Display for CigarOp {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.as_byte() as char)
+    }
+}
+
+/// A CIGAR string, as a list of (length, operation) pairs.\
+/// The ```*``` alignment is an empty CIGAR.
+#[derive(Debug, Clone, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Cigar(pub Vec<(u32, CigarOp)>);
+
+impl Cigar {
+    /// Parses a CIGAR string ```([0-9]+[MIDNSHPX=])+``` or ```*```
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// let cigar = Cigar::parse(b"4M1I").unwrap();
+    /// assert_eq!(Cigar(vec![(4, CigarOp::M), (1, CigarOp::I)]), cigar);
+    /// ```
+    pub fn parse(input: &[u8]) -> Result<Cigar, ParseFieldError> {
+        let invalid = || ParseFieldError::InvalidField("CIGAR");
+        if input == b"*" {
+            return Ok(Cigar::default());
+        }
+        if input.is_empty() {
+            return Err(invalid());
+        }
+
+        let mut ops = vec![];
+        let mut len: Option<u32> = None;
+        for &byte in input {
+            if byte.is_ascii_digit() {
+                let digit = u32::from(byte - b'0');
+                let value = len
+                    .unwrap_or(0)
+                    .checked_mul(10)
+                    .and_then(|l| l.checked_add(digit));
+                len = Some(value.ok_or_else(invalid)?);
+            } else {
+                let op = CigarOp::from_byte(byte).ok_or_else(invalid)?;
+                ops.push((len.take().ok_or_else(invalid)?, op));
+            }
+        }
+        // a trailing length without operation
+        if len.is_some() {
+            return Err(invalid());
+        }
+        Ok(Cigar(ops))
+    }
+
+    /// Returns the number of bases of the reference (the first
+    /// sequence) covered by the alignment
+    pub fn reference_len(&self) -> usize {
+        self.0
+            .iter()
+            .filter(|(_, op)| op.consumes_reference())
+            .map(|(len, _)| *len as usize)
+            .sum()
+    }
+
+    /// Returns the number of bases of the query (the second sequence)
+    /// covered by the alignment
+    pub fn query_len(&self) -> usize {
+        self.0
+            .iter()
+            .filter(|(_, op)| op.consumes_query())
+            .map(|(len, _)| *len as usize)
+            .sum()
+    }
+}
+
+impl std::fmt::Display for Cigar {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.0.is_empty() {
+            return write!(f, "*");
+        }
+        for (len, op) in self.0.iter() {
+            write!(f, "{}{}", len, op)?;
+        }
+        Ok(())
+    }
+}
+
+/// returns a sequence in the given orientation
+fn oriented(seq: &[u8], orient: Orientation) -> BString {
+    match orient {
+        Orientation::Forward => BString::from(seq),
+        Orientation::Backward => reverse_complement(seq),
+    }
+}
+
+/// Joins the sequences of two adjacent segments, taking each one in
+/// its orientation, and removing from the second one the bases that
+/// overlap the first one (the query bases of the overlap CIGAR).\
+/// A ```*``` (empty) overlap joins the sequences as they are.
+///
+/// # Examples
+///
+/// ```ignore
+/// let overlap = Cigar::parse(b"2M").unwrap();
+/// let joined = join_segments(b"ACCT", Orientation::Forward, b"GTTT", Orientation::Backward, &overlap);
+/// assert_eq!(BString::from("ACCTAC"), joined);
+/// ```
+pub fn join_segments(
+    a: &[u8],
+    a_orient: Orientation,
+    b: &[u8],
+    b_orient: Orientation,
+    overlap: &Cigar,
+) -> BString {
+    let mut joined = oriented(a, a_orient);
+    let b = oriented(b, b_orient);
+    let trim = overlap.query_len().min(b.len());
+    joined.extend_from_slice(&b[trim..]);
+    joined
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn can_parse_cigar() {
+        use CigarOp::*;
+
+        let cigar = Cigar::parse(b"10M2I3D1=").unwrap();
+        assert_eq!(Cigar(vec![(10, M), (2, I), (3, D), (1, E)]), cigar);
+        assert_eq!(14, cigar.reference_len());
+        assert_eq!(13, cigar.query_len());
+
+        assert_eq!(Cigar(vec![]), Cigar::parse(b"*").unwrap());
+        for invalid in [&b""[..], b"M", b"10", b"10Q", b"99999999999M"].iter() {
+            assert!(Cigar::parse(invalid).is_err());
+        }
+    }
+
+    #[test]
+    fn can_join_segments() {
+        use Orientation::*;
+
+        let blunt = Cigar::parse(b"*").unwrap();
+        assert_eq!(
+            BString::from("ACCTGTTT"),
+            join_segments(b"ACCT", Forward, b"GTTT", Forward, &blunt)
+        );
+
+        let overlap = Cigar::parse(b"2M").unwrap();
+        assert_eq!(
+            BString::from("ACCTTT"),
+            join_segments(b"ACCT", Forward, b"CTTT", Forward, &overlap)
+        );
+        // the reverse complement of GTTT is AAAC, the one of CCT is AGG
+        assert_eq!(
+            BString::from("AGGAC"),
+            join_segments(
+                b"CCT",
+                Backward,
+                b"GTTT",
+                Backward,
+                &Cigar::parse(b"1M1I").unwrap()
+            )
+        );
+
+        // an overlap longer than the second segment drops it entirely
+        let long = Cigar::parse(b"10M").unwrap();
+        assert_eq!(
+            BString::from("ACCT"),
+            join_segments(b"ACCT", Forward, b"CT", Forward, &long)
+        );
+    }
+}
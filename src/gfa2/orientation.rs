@@ -1,5 +1,6 @@
 use crate::parser_gfa2::ParseFieldError;
 /// I don't think this file could be as useful as the original.
+use bstr::BString;
 use serde::{Deserialize, Serialize};
 
 /// Represents segment orientation/strand
@@ -79,6 +80,14 @@ impl Orientation {
     pub fn is_reverse(&self) -> bool {
         !bool::from(*self)
     }
+
+    /// Returns the opposite orientation
+    pub fn flip(&self) -> Self {
+        match self {
+            Self::Forward => Self::Backward,
+            Self::Backward => Self::Forward,
+        }
+    }
 }
 
 /// The default parser uses the GFA spec with + as Forward, - as Backward
@@ -116,3 +125,137 @@ impl std::fmt::Display for Orientation {
         }
     }
 }
+
+/// Returns the complement of a base, keeping its case: A-T, C-G and
+/// the IUPAC ambiguity codes (R-Y, K-M, B-V, D-H, while S, W and N are
+/// their own complement).\
+/// ```U``` is complemented as ```A```, the unknown base ```.``` becomes
+/// an ```N```, while any other character (e.g. the ```=``` base that
+/// matches the reference) is left as it is
+pub fn complement(base: u8) -> u8 {
+    let complement = match base.to_ascii_uppercase() {
+        b'A' => b'T',
+        b'T' | b'U' => b'A',
+        b'C' => b'G',
+        b'G' => b'C',
+        b'R' => b'Y',
+        b'Y' => b'R',
+        b'K' => b'M',
+        b'M' => b'K',
+        b'B' => b'V',
+        b'V' => b'B',
+        b'D' => b'H',
+        b'H' => b'D',
+        b'S' | b'W' | b'N' => base.to_ascii_uppercase(),
+        b'.' => return b'N',
+        _ => return base,
+    };
+    if base.is_ascii_lowercase() {
+        complement.to_ascii_lowercase()
+    } else {
+        complement
+    }
+}
+
+/// Returns the reverse complement of a sequence, complementing each
+/// base with [`complement`]
+///
+/// # Examples
+///
+/// ```ignore
+/// assert_eq!(BString::from("NCGTt"), reverse_complement(b"aACGN"));
+/// assert_eq!(BString::from("T=N"), reverse_complement(b".=A"));
+/// assert_eq!(BString::from("kYRm"), reverse_complement(b"kYRm"));
+/// ```
+pub fn reverse_complement(seq: &[u8]) -> BString {
+    seq.iter()
+        .rev()
+        .map(|base| complement(*base))
+        .collect::<Vec<u8>>()
+        .into()
+}
+
+/// Returns the number of ```A```, ```C```, ```G``` and ```T``` bases of
+/// a sequence, ignoring their case, followed by the number of the other
+/// characters (```N```, the IUPAC ambiguity codes, ```=```, ```.``` and
+/// so on)
+///
+/// # Examples
+///
+/// ```ignore
+/// assert_eq!([2, 1, 1, 0, 2], base_counts(b"aAcGNn"));
+/// ```
+pub fn base_counts(seq: &[u8]) -> [u64; 5] {
+    let mut counts = [0; 5];
+    for base in seq {
+        let index = match base.to_ascii_uppercase() {
+            b'A' => 0,
+            b'C' => 1,
+            b'G' => 2,
+            b'T' => 3,
+            _ => 4,
+        };
+        counts[index] += 1;
+    }
+    counts
+}
+
+/// Returns the fraction of ```G``` and ```C``` bases among the ```ACGT```
+/// ones of a sequence, ignoring their case, so the ```N``` and the other
+/// characters are not counted.\
+/// Returns None if the sequence has no ```ACGT``` bases
+///
+/// # Examples
+///
+/// ```ignore
+/// assert_eq!(Some(0.5), gc_content(b"ACgtNN"));
+/// assert_eq!(None, gc_content(b"NNN"));
+/// ```
+pub fn gc_content(seq: &[u8]) -> Option<f64> {
+    let [a, c, g, t, _] = base_counts(seq);
+    let total = a + c + g + t;
+    if total == 0 {
+        None
+    } else {
+        Some((c + g) as f64 / total as f64)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn can_flip_orientation() {
+        assert_eq!(Orientation::Backward, Orientation::Forward.flip());
+        assert_eq!(Orientation::Forward, Orientation::Backward.flip());
+    }
+
+    #[test]
+    fn can_reverse_complement() {
+        assert_eq!(BString::from("NCGTt"), reverse_complement(b"aACGN"));
+        assert_eq!(BString::from("T=N"), reverse_complement(b".=A"));
+        assert_eq!(
+            BString::from("NHDVBKMRYSWnhdvbkmryswa"),
+            reverse_complement(b"uwsrykmvbhdnWSRYKMVBHDN")
+        );
+        let seq = b"ACGTRYSWKMBDHVNacgtryswkmbdhvn";
+        assert_eq!(
+            BString::from(&seq[..]),
+            reverse_complement(&reverse_complement(seq))
+        );
+    }
+
+    #[test]
+    fn can_count_bases() {
+        assert_eq!([2, 1, 1, 0, 2], base_counts(b"aAcGNn"));
+        assert_eq!([1, 1, 1, 1, 4], base_counts(b"ACGTRY=."));
+        assert_eq!([0; 5], base_counts(b""));
+
+        assert_eq!(Some(0.5), gc_content(b"ACgtNN"));
+        assert_eq!(Some(1.0), gc_content(b"gcNGC"));
+        assert_eq!(Some(0.0), gc_content(b"ATat"));
+        assert_eq!(None, gc_content(b"NNN"));
+        assert_eq!(None, gc_content(b""));
+    }
+}
@@ -0,0 +1,302 @@
+/// define a compact representation of the sequences made only of
+/// the ```A```, ```C```, ```G``` and ```T``` bases
+use bstr::{BString, ByteSlice};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::{
+    borrow::Cow,
+    cmp::Ordering,
+    fmt,
+    hash::{Hash, Hasher},
+};
+
+/// A sequence of ```ACGT``` bases stored on 2 bits each, so 4 bases
+/// take a single byte
+///
+/// # Examples
+///
+/// ```ignore
+/// let packed = PackedSeq::pack(b"ACGTTGCA").unwrap();
+/// assert_eq!(8, packed.len());
+/// assert_eq!(BString::from("ACGTTGCA"), packed.unpack());
+/// // only the uppercase ACGT bases can be packed
+/// assert_eq!(None, PackedSeq::pack(b"ACGN"));
+/// ```
+#[derive(Default, Debug, Clone, PartialEq, Eq, PartialOrd, Serialize, Deserialize, Hash)]
+pub struct PackedSeq {
+    len: usize,
+    bytes: Vec<u8>,
+}
+
+const BASES: [u8; 4] = [b'A', b'C', b'G', b'T'];
+
+impl PackedSeq {
+    /// Packs a sequence, or returns None if it contains anything other
+    /// than the uppercase ```ACGT``` bases (or it's empty)
+    pub fn pack(sequence: &[u8]) -> Option<Self> {
+        if sequence.is_empty() {
+            return None;
+        }
+        let mut bytes = vec![0; sequence.len().div_ceil(4)];
+        for (i, base) in sequence.iter().enumerate() {
+            let code = BASES.iter().position(|b| b == base)? as u8;
+            bytes[i / 4] |= code << ((i % 4) * 2);
+        }
+        Some(PackedSeq {
+            len: sequence.len(),
+            bytes,
+        })
+    }
+
+    /// Returns the number of bases of the sequence
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns the base at the given position, if it exists
+    pub fn get(&self, index: usize) -> Option<u8> {
+        if index >= self.len {
+            return None;
+        }
+        let code = (self.bytes[index / 4] >> ((index % 4) * 2)) & 0b11;
+        Some(BASES[code as usize])
+    }
+
+    /// Returns the sequence as a string of bases
+    pub fn unpack(&self) -> BString {
+        (0..self.len)
+            .filter_map(|i| self.get(i))
+            .collect::<Vec<u8>>()
+            .into()
+    }
+}
+
+/// The sequence of a segment, stored as it is or packed on 2 bits per
+/// base (see Segment::pack_sequence).\
+/// Two sequences are compared, ordered and hashed by their bases, so a
+/// packed sequence is equal to the same sequence stored as it is
+///
+/// # Examples
+///
+/// ```ignore
+/// let mut sequence = SegmentSequence::from("ACGT");
+/// assert!(sequence.pack());
+/// assert_eq!(sequence, SegmentSequence::from("ACGT"));
+/// assert_eq!("ACGT", sequence);
+/// ```
+#[derive(Clone)]
+pub struct SegmentSequence(SequenceRepr);
+
+#[derive(Clone)]
+enum SequenceRepr {
+    Plain(BString),
+    Packed(PackedSeq),
+}
+
+impl SegmentSequence {
+    /// Returns the bases of the sequence, unpacking them if the
+    /// sequence is packed
+    pub fn bytes(&self) -> Cow<'_, [u8]> {
+        match &self.0 {
+            SequenceRepr::Plain(sequence) => Cow::Borrowed(sequence),
+            SequenceRepr::Packed(packed) => Cow::Owned(packed.unpack().into()),
+        }
+    }
+
+    /// Returns the number of bases of the sequence
+    pub fn len(&self) -> usize {
+        match &self.0 {
+            SequenceRepr::Plain(sequence) => sequence.len(),
+            SequenceRepr::Packed(packed) => packed.len(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Checks if the sequence is stored on 2 bits per base
+    pub fn is_packed(&self) -> bool {
+        matches!(self.0, SequenceRepr::Packed(_))
+    }
+
+    /// Packs the sequence, if it's made only of the uppercase ```ACGT```
+    /// bases, returning if the sequence is packed
+    pub fn pack(&mut self) -> bool {
+        if let SequenceRepr::Plain(sequence) = &self.0 {
+            if let Some(packed) = PackedSeq::pack(sequence) {
+                self.0 = SequenceRepr::Packed(packed);
+            }
+        }
+        self.is_packed()
+    }
+}
+
+impl Default for SegmentSequence {
+    fn default() -> Self {
+        SegmentSequence(SequenceRepr::Plain(BString::default()))
+    }
+}
+
+impl From<BString> for SegmentSequence {
+    fn from(sequence: BString) -> Self {
+        SegmentSequence(SequenceRepr::Plain(sequence))
+    }
+}
+
+impl From<Vec<u8>> for SegmentSequence {
+    fn from(sequence: Vec<u8>) -> Self {
+        BString::from(sequence).into()
+    }
+}
+
+impl From<&[u8]> for SegmentSequence {
+    fn from(sequence: &[u8]) -> Self {
+        BString::from(sequence).into()
+    }
+}
+
+impl From<&str> for SegmentSequence {
+    fn from(sequence: &str) -> Self {
+        BString::from(sequence).into()
+    }
+}
+
+impl PartialEq for SegmentSequence {
+    fn eq(&self, other: &Self) -> bool {
+        match (&self.0, &other.0) {
+            (SequenceRepr::Packed(a), SequenceRepr::Packed(b)) => a == b,
+            _ => self.bytes() == other.bytes(),
+        }
+    }
+}
+
+impl Eq for SegmentSequence {}
+
+impl PartialEq<str> for SegmentSequence {
+    fn eq(&self, other: &str) -> bool {
+        *self.bytes() == *other.as_bytes()
+    }
+}
+
+impl PartialEq<&str> for SegmentSequence {
+    fn eq(&self, other: &&str) -> bool {
+        self == *other
+    }
+}
+
+impl PartialEq<SegmentSequence> for &str {
+    fn eq(&self, other: &SegmentSequence) -> bool {
+        other == *self
+    }
+}
+
+impl PartialEq<BString> for SegmentSequence {
+    fn eq(&self, other: &BString) -> bool {
+        *self.bytes() == *other.as_slice()
+    }
+}
+
+impl PartialEq<SegmentSequence> for BString {
+    fn eq(&self, other: &SegmentSequence) -> bool {
+        other == self
+    }
+}
+
+impl PartialOrd for SegmentSequence {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for SegmentSequence {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.bytes().cmp(&other.bytes())
+    }
+}
+
+impl Hash for SegmentSequence {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.bytes().hash(state)
+    }
+}
+
+impl fmt::Debug for SegmentSequence {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?}", self.bytes().as_bstr())
+    }
+}
+
+impl fmt::Display for SegmentSequence {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.bytes().as_bstr())
+    }
+}
+
+// the sequences are always written unpacked
+impl Serialize for SegmentSequence {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.bytes().as_bstr().serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for SegmentSequence {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        BString::deserialize(deserializer).map(SegmentSequence::from)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn can_pack_sequence() {
+        for sequence in ["A", "ACGT", "ACGTTGCAC", "TTTTTTTTTTTTT"].iter() {
+            let packed = PackedSeq::pack(sequence.as_bytes()).unwrap();
+            assert_eq!(sequence.len(), packed.len());
+            assert_eq!(sequence.len().div_ceil(4), packed.bytes.len());
+            assert_eq!(BString::from(*sequence), packed.unpack());
+        }
+
+        let packed = PackedSeq::pack(b"ACGTTGCAC").unwrap();
+        assert_eq!(Some(b'T'), packed.get(4));
+        assert_eq!(None, packed.get(9));
+    }
+
+    #[test]
+    fn only_acgt_can_be_packed() {
+        assert_eq!(None, PackedSeq::pack(b"ACGN"));
+        assert_eq!(None, PackedSeq::pack(b"acgt"));
+        assert_eq!(None, PackedSeq::pack(b"*"));
+        assert_eq!(None, PackedSeq::pack(b""));
+    }
+
+    #[test]
+    fn packed_sequences_equal_their_bases() {
+        use std::collections::hash_map::DefaultHasher;
+
+        let hash = |sequence: &SegmentSequence| {
+            let mut hasher = DefaultHasher::new();
+            sequence.hash(&mut hasher);
+            hasher.finish()
+        };
+        let plain = SegmentSequence::from("ACGTTGCA");
+        let mut packed = plain.clone();
+        assert!(packed.pack());
+        assert!(!plain.is_packed());
+        assert_eq!(plain, packed);
+        assert_eq!(hash(&plain), hash(&packed));
+        assert_eq!(8, packed.len());
+        assert_eq!("ACGTTGCA", packed);
+        assert_eq!("ACGTTGCA", packed.to_string());
+        assert!(packed < SegmentSequence::from("ACGTTGCC"));
+
+        let mut other = SegmentSequence::from("ACGN");
+        assert!(!other.pack());
+        assert_eq!("ACGN", other);
+    }
+}
@@ -0,0 +1,42 @@
+/// file that defines how the position fields of the GFA2 lines
+/// (sbeg, send, fbeg, fend, beg1, end1, beg2, end2) are parsed
+/// into usable numbers
+use bstr::ByteSlice;
+
+/// Parses a position field in the format\
+/// ```<pos> <- {-}[0-9]+{$}```\
+/// returning the position and whether it's marked with the final `$`
+/// char (i.e. it's the end of the segment).\
+/// Negative positions can't be used as coordinates, so they return None.
+///
+/// # Examples
+///
+/// ```ignore
+/// use gfa2::gfa2::parse_position;
+///
+/// assert_eq!(Some((42, false)), parse_position(b"42"));
+/// assert_eq!(Some((122, true)), parse_position(b"122$"));
+/// assert_eq!(None, parse_position(b"-1"));
+/// ```
+pub fn parse_position(input: &[u8]) -> Option<(usize, bool)> {
+    let (pos, is_end) = match input.last() {
+        Some(b'$') => (&input[..input.len() - 1], true),
+        _ => (input, false),
+    };
+    let pos = pos.to_str().ok()?.parse::<usize>().ok()?;
+    Some((pos, is_end))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn can_parse_position() {
+        assert_eq!(Some((42, false)), parse_position(b"42"));
+        assert_eq!(Some((122, true)), parse_position(b"122$"));
+        assert_eq!(None, parse_position(b"-1"));
+        assert_eq!(None, parse_position(b"$"));
+        assert_eq!(None, parse_position(b""));
+    }
+}
@@ -0,0 +1,394 @@
+/// file that collects the functions used to compute some statistics
+/// and analysis on a GFA2 object
+use crate::gfa2::*;
+use crate::tag::*;
+
+use std::{collections::HashMap, hash::Hash};
+
+impl<N: SegmentId + PartialEq, T: OptFields> GFA2<N, T> {
+    /// Returns the per-base coverage of a segment, computed by accumulating
+    /// the ```sbeg..send``` span of every fragment placed on that segment.\
+    /// The length of the result is the `len` of the segment (or the length of
+    /// its sequence, if `len` can't be parsed); an empty vector is returned
+    /// if the segment does not exist.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// let parser: GFA2Parser<BString, ()> = GFA2Parser::new();
+    /// let gfa2: GFA2<BString, ()> = parser
+    ///     .parse_lines(["S\tA\t4\tACGT", "F\tA\tr1+\t0\t2\t0\t2\t*"].iter())
+    ///     .unwrap();
+    ///
+    /// assert_eq!(vec![1, 1, 0, 0], gfa2.segment_coverage(&"A".into()));
+    /// ```
+    pub fn segment_coverage(&self, seg: &N) -> Vec<u32> {
+        let segment = match self.segments.iter().find(|s| s.id == *seg) {
+            Some(segment) => segment,
+            None => return vec![],
+        };
+        let len = segment
+            .length()
+            .map_or_else(|| segment.sequence_bytes().len(), |len| len as usize);
+        let mut coverage = vec![0; len];
+
+        for fragment in self.fragments.iter().filter(|f| f.id == *seg) {
+            let sbeg = parse_position(&fragment.sbeg);
+            let send = parse_position(&fragment.send);
+            if let (Some((beg, _)), Some((end, _))) = (sbeg, send) {
+                // a span exceeding the segment is clamped to its length
+                let end = end.min(len);
+                for base in coverage.iter_mut().take(end).skip(beg) {
+                    *base += 1;
+                }
+            }
+        }
+
+        coverage
+    }
+
+    /// Returns the fragments placed on a segment, sorted by their
+    /// ```sbeg``` position (the ones with an invalid position last)
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// let parser: GFA2Parser<BString, ()> = GFA2Parser::new();
+    /// let gfa2: GFA2<BString, ()> = parser
+    ///     .parse_file("./tests/gfa2_files/sample2.gfa")
+    ///     .unwrap();
+    ///
+    /// for fragment in gfa2.fragments_on(&"12".into()) {
+    ///     println!("{}", fragment);
+    /// }
+    /// ```
+    pub fn fragments_on(&self, seg: &N) -> Vec<&Fragment<N, T>> {
+        let mut fragments: Vec<_> = self.fragments.iter().filter(|f| f.id == *seg).collect();
+        fragments.sort_by_key(|f| parse_position(&f.sbeg).map_or(usize::MAX, |(pos, _)| pos));
+        fragments
+    }
+}
+
+impl<N: SegmentId, T: OptFields> GFA2<N, T> {
+    /// returns the N50 and the L50 of the segment lengths (the `len`
+    /// field, the segments with an unknown or invalid `len` are ignored)
+    fn n50_l50(&self) -> Option<(u64, usize)> {
+        let mut lengths: Vec<u64> = self.segments.iter().filter_map(Segment::length).collect();
+        // the longest segments first
+        lengths.sort_unstable_by(|a, b| b.cmp(a));
+
+        let total: u64 = lengths.iter().sum();
+        let mut cumulative = 0;
+        for (i, len) in lengths.iter().enumerate() {
+            cumulative += len;
+            if cumulative * 2 >= total {
+                return Some((*len, i + 1));
+            }
+        }
+        None
+    }
+
+    /// Returns the N50 of the segment lengths, i.e. the length of the
+    /// shortest segment among the longest ones that together cover at
+    /// least half of the total length, or None for an empty graph
+    pub fn n50(&self) -> Option<u64> {
+        self.n50_l50().map(|(n50, _)| n50)
+    }
+
+    /// Returns the L50 of the segment lengths, i.e. the number of the
+    /// longest segments that together cover at least half of the total
+    /// length, or None for an empty graph
+    pub fn l50(&self) -> Option<usize> {
+        self.n50_l50().map(|(_, l50)| l50)
+    }
+}
+
+impl<N: SegmentId + Clone + Eq + Hash, T: OptFields> GFA2<N, T> {
+    /// Counts, for every segment, the dovetail edges reaching its
+    /// start and its end
+    fn end_degrees(&self) -> HashMap<N, (usize, usize)> {
+        let mut degrees: HashMap<N, (usize, usize)> = self
+            .segments
+            .iter()
+            .map(|s| (s.id.clone(), (0, 0)))
+            .collect();
+
+        for edge in self.edges.iter().filter(|e| e.is_dovetail()) {
+            let sides = [
+                (&edge.sid1, &edge.beg1, &edge.end1),
+                (&edge.sid2, &edge.beg2, &edge.end2),
+            ];
+            for (sid, beg, end) in sides.iter() {
+                let id = match sid.split_ref() {
+                    Some((id, _)) => id,
+                    None => continue,
+                };
+                let ends = Edge::<N, T>::reached_ends(beg, end);
+                if let (Some(degree), Some((start, end))) = (degrees.get_mut(&id), ends) {
+                    if start {
+                        degree.0 += 1;
+                    }
+                    if end {
+                        degree.1 += 1;
+                    }
+                }
+            }
+        }
+
+        degrees
+    }
+
+    /// Returns the number of dovetail edges reaching the start and the
+    /// end of a segment, or None if the segment does not exist
+    pub fn segment_degree(&self, seg: &N) -> Option<(usize, usize)> {
+        self.end_degrees().get(seg).copied()
+    }
+
+    /// Returns the tips (dead ends) of the graph, that are the segment
+    /// ends without any dovetail edge.\
+    /// A segment without edges on its end is returned as
+    /// ```(id, Orientation::Forward)```, one without edges on its start
+    /// as ```(id, Orientation::Backward)```, so an isolated segment
+    /// appears in both orientations.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// let parser: GFA2Parser<BString, ()> = GFA2Parser::new();
+    /// let gfa2: GFA2<BString, ()> = parser
+    ///     .parse_lines(["S\tA\t4\tACGT", "S\tB\t4\tACGT", "E\t*\tA+\tB+\t2\t4$\t0\t2\t2M"].iter())
+    ///     .unwrap();
+    ///
+    /// assert_eq!(
+    ///     vec![("A".into(), Orientation::Backward), ("B".into(), Orientation::Forward)],
+    ///     gfa2.tips()
+    /// );
+    /// ```
+    pub fn tips(&self) -> Vec<(N, Orientation)> {
+        let degrees = self.end_degrees();
+        let mut tips = vec![];
+        for segment in self.segments.iter() {
+            let (start, end) = degrees[&segment.id];
+            if start == 0 {
+                tips.push((segment.id.clone(), Orientation::Backward));
+            }
+            if end == 0 {
+                tips.push((segment.id.clone(), Orientation::Forward));
+            }
+        }
+        tips
+    }
+
+    /// Indexes the edges by the segments they connect, so that the
+    /// edges of a segment don't have to be searched among all of them
+    fn edges_index(&self) -> HashMap<N, Vec<&Edge<N, T>>> {
+        let mut index: HashMap<N, Vec<&Edge<N, T>>> = HashMap::new();
+        for edge in self.edges.iter() {
+            let id1 = edge.sid1.split_ref().map(|(id, _)| id);
+            let id2 = edge.sid2.split_ref().map(|(id, _)| id);
+            if let Some(id1) = id1.clone() {
+                index.entry(id1).or_default().push(edge);
+            }
+            // a self loop is listed only once
+            if let Some(id2) = id2.filter(|id2| Some(id2) != id1.as_ref()) {
+                index.entry(id2).or_default().push(edge);
+            }
+        }
+        index
+    }
+
+    /// Returns every segment together with the edges that reach it,
+    /// in the order of the segments and, for each segment, of the edges.\
+    /// The edges are indexed once, then collected lazily for each segment
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// let parser: GFA2Parser<BString, ()> = GFA2Parser::new();
+    /// let gfa2 = parser.parse_file("./tests/gfa2_files/spec_q7.gfa").unwrap();
+    ///
+    /// for (segment, edges) in gfa2.segments_with_edges() {
+    ///     println!("{} has {} edges", segment.id, edges.len());
+    /// }
+    /// ```
+    pub fn segments_with_edges(
+        &self,
+    ) -> impl Iterator<Item = (&Segment<N, T>, Vec<&Edge<N, T>>)> + '_ {
+        let index = self.edges_index();
+        self.segments
+            .iter()
+            .map(move |s| (s, index.get(&s.id).cloned().unwrap_or_default()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        gfa2::{Orientation, SegmentId, GFA2},
+        parser_gfa2::GFA2Parser,
+    };
+    use bstr::BString;
+
+    #[test]
+    fn can_compute_segment_coverage() {
+        let lines = [
+            "S\tA\t8\tACGTACGT",
+            "S\tB\t4\tACGT",
+            "F\tA\tr1+\t0\t4\t0\t4\t*",
+            "F\tA\tr2-\t2\t8$\t0\t6\t*",
+            "F\tB\tr3+\t1\t3\t0\t2\t*",
+        ];
+        let parser: GFA2Parser<BString, ()> = GFA2Parser::new();
+        let gfa2: GFA2<BString, ()> = parser.parse_lines(lines.iter()).unwrap();
+
+        assert_eq!(
+            vec![1, 1, 2, 2, 1, 1, 1, 1],
+            gfa2.segment_coverage(&"A".into())
+        );
+        assert_eq!(vec![0, 1, 1, 0], gfa2.segment_coverage(&"B".into()));
+        assert!(gfa2.segment_coverage(&"C".into()).is_empty());
+    }
+
+    #[test]
+    fn can_find_fragments_on_segment() {
+        let lines = [
+            "S\tA\t8\tACGTACGT",
+            "S\tB\t4\tACGT",
+            "F\tA\tr1+\t4\t8$\t0\t4\t*",
+            "F\tB\tr2+\t0\t2\t0\t2\t*",
+            "F\tA\tr3-\t0\t4\t0\t4\t*",
+            "F\tA\tr4+\t2\t6\t0\t4\t*",
+        ];
+        let parser: GFA2Parser<BString, ()> = GFA2Parser::new();
+        let gfa2: GFA2<BString, ()> = parser.parse_lines(lines.iter()).unwrap();
+
+        let refs: Vec<_> = gfa2
+            .fragments_on(&"A".into())
+            .iter()
+            .map(|f| f.ext_ref.to_string())
+            .collect();
+        assert_eq!(vec!["r3-", "r4+", "r1+"], refs);
+        assert_eq!(1, gfa2.fragments_on(&"B".into()).len());
+        assert!(gfa2.fragments_on(&"C".into()).is_empty());
+    }
+
+    #[test]
+    fn can_compute_n50_l50() {
+        let lines = [
+            "S\tA\t2\t*",
+            "S\tB\t3\t*",
+            "S\tC\t4\t*",
+            "S\tD\t5\t*",
+            "S\tE\t6\t*",
+        ];
+        let parser: GFA2Parser<BString, ()> = GFA2Parser::new();
+        let gfa2: GFA2<BString, ()> = parser.parse_lines(lines.iter()).unwrap();
+        // total 20: 6 + 5 = 11 is the first cumulative length >= 10
+        assert_eq!(Some(5), gfa2.n50());
+        assert_eq!(Some(2), gfa2.l50());
+
+        let gfa2: GFA2<BString, ()> = parser.parse_lines(lines[..1].iter()).unwrap();
+        assert_eq!(Some(2), gfa2.n50());
+        assert_eq!(Some(1), gfa2.l50());
+
+        let empty: GFA2<BString, ()> = GFA2::new();
+        assert_eq!(None, empty.n50());
+        assert_eq!(None, empty.l50());
+    }
+
+    #[test]
+    fn empty_graph_analysis() {
+        let empty: GFA2<BString, ()> = GFA2::new();
+        assert_eq!("", empty.to_string());
+        assert_eq!(None, empty.n50());
+        assert_eq!(None, empty.l50());
+        assert!(empty.segment_coverage(&"A".into()).is_empty());
+        assert!(empty.fragments_on(&"A".into()).is_empty());
+        assert_eq!(None, empty.segment_degree(&"A".into()));
+        assert!(empty.tips().is_empty());
+        assert_eq!(0, empty.segments_with_edges().count());
+        assert!(empty.segments_by_length().is_empty());
+        assert_eq!(0, empty.all_references().count());
+        assert!(empty.is_gfa1_convertible());
+
+        // segments without a length are ignored, as if there were none
+        let parser: GFA2Parser<BString, ()> = GFA2Parser::new();
+        let gfa2: GFA2<BString, ()> = parser.parse_lines(["S\tA\t*\tACGT"].iter()).unwrap();
+        assert_eq!(None, gfa2.n50());
+        assert_eq!(None, gfa2.l50());
+    }
+
+    #[test]
+    fn segment_coverage_usize() {
+        let parser: GFA2Parser<usize, ()> = GFA2Parser::new();
+        let gfa2: GFA2<usize, ()> = parser.parse_file("./tests/gfa2_files/sample2.gfa").unwrap();
+
+        let coverage = gfa2.segment_coverage(&usize::parse_id(b"2").unwrap());
+        assert_eq!(120, coverage.len());
+        assert_eq!(1, coverage[0]);
+        assert_eq!(0, coverage[43]);
+        assert_eq!(1, coverage[50]);
+    }
+
+    #[test]
+    fn can_find_tips() {
+        use Orientation::*;
+
+        let lines = [
+            "S\tA\t4\tACGT",
+            "S\tB\t4\tACGT",
+            "S\tC\t4\tACGT",
+            "S\tD\t4\tACGT",
+            // A+ -> B+ and B+ -> C-, a path with a tip on both sides
+            "E\t*\tA+\tB+\t2\t4$\t0\t2\t2M",
+            "E\t*\tB+\tC-\t3\t4$\t3\t4$\t1M",
+            // a containment doesn't connect the ends of D
+            "E\t*\tC+\tD+\t0\t4$\t0\t4$\t4M",
+        ];
+        let parser: GFA2Parser<BString, ()> = GFA2Parser::new();
+        let gfa2: GFA2<BString, ()> = parser.parse_lines(lines.iter()).unwrap();
+
+        assert_eq!(Some((1, 1)), gfa2.segment_degree(&"B".into()));
+        assert_eq!(Some((0, 1)), gfa2.segment_degree(&"C".into()));
+        assert_eq!(None, gfa2.segment_degree(&"E".into()));
+        assert_eq!(
+            vec![
+                ("A".into(), Backward),
+                ("C".into(), Backward),
+                ("D".into(), Backward),
+                ("D".into(), Forward),
+            ],
+            gfa2.tips()
+        );
+    }
+
+    #[test]
+    fn can_iterate_segments_with_edges() {
+        let lines = [
+            "S\tA\t4\tACGT",
+            "S\tB\t4\tACGT",
+            "S\tC\t4\tACGT",
+            "E\te1\tA+\tB+\t2\t4$\t0\t2\t2M",
+            "E\te2\tB+\tB-\t3\t4$\t3\t4$\t1M",
+            "E\te3\tA-\tB+\t0\t1\t0\t1\t1M",
+        ];
+        let parser: GFA2Parser<BString, ()> = GFA2Parser::new();
+        let gfa2: GFA2<BString, ()> = parser.parse_lines(lines.iter()).unwrap();
+
+        let edges: Vec<(String, Vec<String>)> = gfa2
+            .segments_with_edges()
+            .map(|(s, edges)| {
+                let ids = edges.iter().map(|e| e.id.to_string()).collect();
+                (s.id.to_string(), ids)
+            })
+            .collect();
+        assert_eq!(
+            vec![
+                ("A".to_string(), vec!["e1".to_string(), "e3".into()]),
+                ("B".into(), vec!["e1".into(), "e2".into(), "e3".into()]),
+                ("C".into(), vec![]),
+            ],
+            edges
+        );
+    }
+}
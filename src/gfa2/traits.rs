@@ -1,8 +1,9 @@
 /// file that is used to define all the common types that can be
 /// parsed and used as SegmentId
+use crate::gfa2::orientation::Orientation;
 use crate::parser_gfa2::ParseFieldError;
 
-use bstr::{BString, ByteSlice};
+use bstr::BString;
 use lazy_static::lazy_static;
 use regex::bytes::Regex;
 
@@ -16,6 +17,29 @@ pub trait SegmentId: std::fmt::Display + Sized + Default {
     fn parse_id(input: &[u8]) -> Option<Self>;
     fn parse_ref(input: &[u8]) -> Option<Self>;
 
+    /// Splits a reference (a segment id followed by its orientation,
+    /// as returned by parse_ref) into the segment id and the orientation
+    fn split_ref(&self) -> Option<(Self, Orientation)>;
+
+    /// Returns the id used for the optional id `*` (anonymous lines)
+    fn anonymous() -> Self;
+
+    /// Checks if the id is the optional id `*`
+    fn is_anonymous(&self) -> bool;
+
+    /// Returns the name the id was parsed from, so that it can be
+    /// written back in a GFA file and parsed again to the same id
+    fn to_name(&self) -> String;
+
+    /// Returns the name of a reference (as returned by parse_ref), the
+    /// name of the segment followed by its orientation
+    fn to_ref_name(&self) -> String {
+        match self.split_ref() {
+            Some((id, orient)) => format!("{}{}", id.to_name(), orient),
+            None => self.to_name(),
+        }
+    }
+
     fn parse_next<I>(mut input: I) -> Result<Self, ParseFieldError>
     where
         I: Iterator,
@@ -44,232 +68,395 @@ pub trait SegmentId: std::fmt::Display + Sized + Default {
     }
 }
 
+/// encodes a name as a usize id, followed by the bit of the orientation
+/// for a reference. Returns None if the name is not a valid id, and the
+/// IdTooLong error if it can't be encoded in a usize
+fn usize_from_name(name: &[u8], orient: Option<usize>) -> Result<Option<usize>, ParseFieldError> {
+    if name.is_empty() || !name.iter().all(|c| (b'!'..=b'~').contains(c)) {
+        return Ok(None);
+    }
+    let too_long = || ParseFieldError::IdTooLong { name: name.into() };
+    let id = encode_usize_id(name).ok_or_else(too_long)?;
+    let id = match orient {
+        Some(orient) => id.checked_mul(2).and_then(|id| id.checked_add(orient)),
+        None => Some(id),
+    };
+    // usize::MAX is the anonymous id, so no name can be encoded as it
+    match id {
+        Some(id) if id != usize::MAX => Ok(Some(id)),
+        _ => Err(too_long()),
+    }
+}
+
+// a field that is not an id gives None, so the parser can report it
+// with the error of the line instead of stopping
+fn usize_id(input: &[u8]) -> Result<Option<usize>, ParseFieldError> {
+    lazy_static! {
+        static ref REX: Regex = Regex::new(r"(?-u)^[!-~]+$").unwrap();
+    }
+    if REX.is_match(input.as_ref()) {
+        usize_from_name(input, None)
+    } else {
+        Ok(None)
+    }
+}
+
+fn usize_opt_id(input: &[u8]) -> Result<Option<usize>, ParseFieldError> {
+    lazy_static! {
+        static ref REX: Regex = Regex::new(r"(?-u)^([!-~]+|\*)$").unwrap();
+    }
+    if input == b"*" {
+        return Ok(Some(usize::anonymous()));
+    }
+    if REX.is_match(input.as_ref()) {
+        usize_from_name(input, None)
+    } else {
+        Ok(None)
+    }
+}
+
+fn usize_ref(input: &[u8]) -> Result<Option<usize>, ParseFieldError> {
+    lazy_static! {
+        static ref REX: Regex = Regex::new(r"(?-u)^[!-~]+[+-]$").unwrap();
+    }
+    if REX.is_match(input.as_ref()) {
+        let last = input.len() - 1;
+
+        let orient = match input[last] {
+            b'+' => 0,
+            b'-' => 1,
+            _ => return Ok(None),
+        };
+        usize_from_name(&input[..last], Some(orient))
+    } else {
+        Ok(None)
+    }
+}
+
 impl SegmentId for usize {
     const ERROR: ParseFieldError = ParseFieldError::UintIdError;
 
     fn parse_id(input: &[u8]) -> Option<Self> {
-        lazy_static! {
-            static ref REX: Regex = Regex::new(r"(?-u)[!-~]+").unwrap();
-        }
-        if REX.is_match(input.as_ref()) {
-            //convert_alphanumeric(input)
-            let len = input.len();
-            let my_vec: Vec<char> = input.to_str().unwrap().chars().collect();
-            let mut x = 0;
-            let mut res: String = "".to_string();
-            while x < len {
-                res = format!(
-                    "{}{}",
-                    res,
-                    &get_code_from_char(&my_vec[x].to_string()).to_string()
-                );
-                x += 1;
-            }
-            match res.len() {
-                1..=20 => Some(res.parse::<usize>().unwrap()),
-                _ => panic!(
-                    "Error! the conversion of the string: {} (length: {}) into usize: {} (lenght {}) exceeds {} ",
-                    input.to_str().unwrap(), input.len(), res, res.len(), "the maximum length (20 digits)"
-                ),
-            }
-        } else {
-            panic!("Error! the id tag it's not correct")
-        }
+        usize_id(input).ok().flatten()
     }
 
     fn parse_opt_id(input: &[u8]) -> Option<Self> {
-        lazy_static! {
-            static ref REX: Regex = Regex::new(r"(?-u)[!-~]+|\*").unwrap();
-        }
-        if REX.is_match(input.as_ref()) {
-            //convert_alphanumeric(input)
-            let len = input.len();
-            let my_vec: Vec<char> = input.to_str().unwrap().chars().collect();
-            let mut x = 0;
-            let mut res: String = "".to_string();
-            while x < len {
-                res = format!(
-                    "{}{}",
-                    res,
-                    &get_code_from_char(&my_vec[x].to_string()).to_string()
-                );
-                x += 1;
-            }
-            match res.len() {
-                1..=20 => Some(res.parse::<usize>().unwrap()),
-                _ => panic!(
-                    "Error! the conversion of the string: {} (length: {}) into usize: {} (lenght {}) exceeds {} ",
-                    input.to_str().unwrap(), input.len(), res, res.len(), "the maximum length (20 digits)"
-                ),
-            }
-        } else {
-            panic!("Error! the optional id tag it's not correct")
-        }
+        usize_opt_id(input).ok().flatten()
     }
 
     fn parse_ref(input: &[u8]) -> Option<Self> {
-        lazy_static! {
-            static ref REX: Regex = Regex::new(r"(?-u)[!-~]+[+-]").unwrap();
-        }
-        if REX.is_match(input.as_ref()) {
-            let last = input.len() - 1;
-
-            let orient = match input[last] {
-                b'+' => 0 as usize,
-                b'-' => 1 as usize,
-                _ => panic!("reference segment did not include orientation"),
-            };
-            let segment_id = &input[..last];
-            let my_vec: Vec<char> = segment_id.to_str().unwrap().chars().collect();
-            let mut x = 0;
-            let mut res: String = "".to_string();
-            while x < last {
-                res = format!(
-                    "{}{}",
-                    res,
-                    &get_code_from_char(&my_vec[x].to_string()).to_string()
-                );
-                x += 1;
-            }
-            match res.len() {
-                1..=20 => format!("{}{}", res, orient).parse::<usize>().ok(),
-                _ => panic!(
-                    "Error! the conversion of the string: {} (length: {}) into usize: {} (lenght {}) exceeds {} ",
-                    segment_id.to_str().unwrap(), segment_id.len(), res, res.len(), "the maximum length (20 digits)"
-                    ),
-            }
-        } else {
-            panic!("Error! the reference tag it's not correct")
+        usize_ref(input).ok().flatten()
+    }
+
+    // the parsing functions of the lines report the ids too long to be
+    // encoded with their own error, so the caller can switch to BString ids
+    fn parse_next<I>(mut input: I) -> Result<Self, ParseFieldError>
+    where
+        I: Iterator,
+        I::Item: AsRef<[u8]>,
+    {
+        let next = input.next().ok_or(ParseFieldError::MissingFields)?;
+        usize_id(next.as_ref())?.ok_or(Self::ERROR)
+    }
+
+    fn parse_next_opt<I>(mut input: I) -> Result<Self, ParseFieldError>
+    where
+        I: Iterator,
+        I::Item: AsRef<[u8]>,
+    {
+        let next = input.next().ok_or(ParseFieldError::MissingFields)?;
+        usize_opt_id(next.as_ref())?.ok_or(Self::ERROR)
+    }
+
+    fn parse_next_ref<I>(mut input: I) -> Result<Self, ParseFieldError>
+    where
+        I: Iterator,
+        I::Item: AsRef<[u8]>,
+    {
+        let next = input.next().ok_or(ParseFieldError::MissingFields)?;
+        usize_ref(next.as_ref())?.ok_or(Self::ERROR)
+    }
+
+    fn split_ref(&self) -> Option<(Self, Orientation)> {
+        // the orientation is encoded as the last bit of the reference
+        match self & 1 {
+            0 => Some((self >> 1, Orientation::Forward)),
+            _ => Some((self >> 1, Orientation::Backward)),
         }
     }
+
+    fn anonymous() -> Self {
+        // the encoded ids and references are always lower than usize::MAX
+        // (and usize_from_name rejects it), so it can stand for `*`
+        usize::MAX
+    }
+
+    fn is_anonymous(&self) -> bool {
+        *self == Self::anonymous()
+    }
+
+    fn to_name(&self) -> String {
+        decode_usize_id(*self).map_or_else(|| self.to_string(), |name| name.to_string())
+    }
 }
 
 impl SegmentId for BString {
-    const ERROR: ParseFieldError = ParseFieldError::Utf8Error;
+    // the bytes are never decoded, so a failure means that the field
+    // is not a valid id (e.g. it's empty or contains whitespaces)
+    const ERROR: ParseFieldError = ParseFieldError::InvalidField("Id");
 
+    // the whole field has to match, otherwise an id with an embedded
+    // whitespace would be silently truncated to its first word
     fn parse_id(input: &[u8]) -> Option<Self> {
         lazy_static! {
-            static ref RE: Regex = Regex::new(r"(?-u)[!-~]+").unwrap();
+            static ref RE: Regex = Regex::new(r"(?-u)^[!-~]+$").unwrap();
         }
         RE.find(input).map(|s| BString::from(s.as_bytes()))
     }
 
     fn parse_opt_id(input: &[u8]) -> Option<Self> {
         lazy_static! {
-            static ref RE: Regex = Regex::new(r"(?-u)[!-~]+|\*").unwrap();
+            static ref RE: Regex = Regex::new(r"(?-u)^([!-~]+|\*)$").unwrap();
         }
         RE.find(input).map(|s| BString::from(s.as_bytes()))
     }
 
     fn parse_ref(input: &[u8]) -> Option<Self> {
         lazy_static! {
-            static ref RE: Regex = Regex::new(r"(?-u)[!-~]+[+-]").unwrap();
+            static ref RE: Regex = Regex::new(r"(?-u)^[!-~]+[+-]$").unwrap();
         }
         RE.find(input).map(|s| BString::from(s.as_bytes()))
     }
+
+    fn split_ref(&self) -> Option<(Self, Orientation)> {
+        let (last, id) = self.split_last()?;
+        let orient = match last {
+            b'+' => Orientation::Forward,
+            b'-' => Orientation::Backward,
+            _ => return None,
+        };
+        Some((BString::from(id), orient))
+    }
+
+    fn anonymous() -> Self {
+        BString::from("*")
+    }
+
+    fn is_anonymous(&self) -> bool {
+        self == "*"
+    }
+
+    fn to_name(&self) -> String {
+        self.to_string()
+    }
 }
 
-/// array to perform the conversion from symbols to usize and viceversa
-const CHARS: [&str; 128] = [
-    // unprintable characters, never used but they need to be here
-    "NUL", "SOH", "STX", "ETX", "EOT", "ENQ", "ACK", "BEL", "BS", "HT", "LF", "VT", "FF", "CR",
-    "SO", "SI", "DLE", "DC1", "DC2", "DC3", "DC4", "NAK", "SYN", "ETB", "CAN", "EM", "SUB", "ESC",
-    "FS", "GS", "RS", "US", // printable characters, the ones that will be used
-    " ", "!", "\"", "#", "$", "%", "&", "\'", "(", ")", "*", "+", ",", "-", ".", "/", "0", "1",
-    "2", "3", "4", "5", "6", "7", "8", "9", ":", ";", "<", "=", ">", "?", "@", "A", "B", "C", "D",
-    "E", "F", "G", "H", "I", "J", "K", "L", "M", "N", "O", "P", "Q", "R", "S", "T", "U", "V", "W",
-    "X", "Y", "Z", "[", "\\", "]", "^", "_", "`", "a", "b", "c", "d", "e", "f", "g", "h", "i", "j",
-    "k", "l", "m", "n", "o", "p", "q", "r", "s", "t", "u", "v", "w", "x", "y", "z", "{", "|", "}",
-    "~", // even if printable, this character it's not used
-    "DEL",
-];
-
-/// function that performs the conversion from the code to the associated symbol
+/// the characters that can be used in the names of the usize ids, each
+/// one encoded with its position in the array
+const ID_CHARS: &[u8; 64] = b"0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz_.";
+
+/// the number of bits used to encode every character of a name
+const BITS_PER_CHAR: u32 = 6;
+
+/// the maximum number of characters of a name encoded as a usize id,
+/// so that the leading 1 bit, the characters and the orientation bit of
+/// a reference fit in a usize (10 characters on 64 bit targets)
+const MAX_ID_CHARS: usize = ((usize::BITS - 2) / BITS_PER_CHAR) as usize;
+
+/// function that encodes a name as a usize id.\
+/// Every character of the name takes the same 6 bits, its position in
+/// ```[0-9A-Za-z_.]```, after a leading 1 bit that keeps the length of
+/// the name (e.g. ```0``` and ```00``` are different ids), so digits,
+/// letters and mixed names use the same encoding.\
+/// Returns None if the name is empty, longer than 10 characters (5 on
+/// 32 bit targets) or has a character that can't be encoded
 /// # Example
 /// ```ignore
-///  let a: &str = "a";
-/// let a_: i32 = 97;
-/// assert_eq!(a, get_char_from_code(a_));
+/// assert_eq!(Some(0b1_000001_000010), encode_usize_id(b"12"));
+/// assert_eq!(Some(0b1_100100_000001), encode_usize_id(b"a1"));
 /// ```
-fn get_char_from_code(c: i32) -> &'static str {
-    CHARS.get(c as usize).unwrap_or(&"")
+pub(crate) fn encode_usize_id(input: &[u8]) -> Option<usize> {
+    if input.is_empty() || input.len() > MAX_ID_CHARS {
+        return None;
+    }
+    input.iter().try_fold(1, |bits: usize, c| {
+        let code = ID_CHARS.iter().position(|x| x == c)?;
+        Some(bits << BITS_PER_CHAR | code)
+    })
 }
 
-/// function that performs the conversion from a symbol to the associated ascii code
+/// Function that decodes a usize id back to the name it was encoded
+/// from, so the graphs parsed with usize ids can still show the
+/// original names. The anonymous id is decoded as ```*```.\
+/// Returns None if the id is not a valid encoding.\
+/// A reference keeps its orientation as the last bit, so it has to
+/// be split with split_ref before decoding it
 /// # Example
 /// ```ignore
-///  let a: &str = "a";
-/// let a_: usize = 97;
-/// assert_eq!(a_, get_code_from_char(a));
+/// let id = usize::parse_id(b"s148227").unwrap();
+/// assert_eq!(Some(BString::from("s148227")), decode_usize_id(id));
 /// ```
-fn get_code_from_char(c: &str) -> usize {
-    if c.parse::<u64>().is_ok() {
-        c.parse::<usize>().unwrap()
-    } else {
-        CHARS.iter().position(|&x| x == c).unwrap()
+pub fn decode_usize_id(id: usize) -> Option<BString> {
+    if id.is_anonymous() {
+        return Some(BString::from("*"));
+    }
+    // the bits after the leading one are made of 6 bits chunks
+    let used = usize::BITS - id.leading_zeros();
+    if used <= 1 || !(used - 1).is_multiple_of(BITS_PER_CHAR) {
+        return None;
     }
+    let len = ((used - 1) / BITS_PER_CHAR) as usize;
+    if len > MAX_ID_CHARS {
+        return None;
+    }
+    let name: Vec<u8> = (0..len)
+        .rev()
+        .map(|i| ID_CHARS[(id >> (i as u32 * BITS_PER_CHAR)) & 0b11_1111])
+        .collect();
+    Some(name.into())
 }
 
-// TODO: add a way to display the usize file as a BString file
-// so the file it's easier to read and understand
-
 #[cfg(test)]
 mod tests {
     use super::*;
+    use bstr::ByteSlice;
 
     #[test]
-    fn can_print_char() {
-        let a: &str = "a";
-        let a_: i32 = 97;
+    fn usize_id_overflow_is_none() {
+        // 20 chars, more than an encoded id can hold
+        let big = b"99999999999999999999";
+        assert_eq!(None, usize::parse_id(big));
+        assert_eq!(None, usize::parse_opt_id(big));
+        assert_eq!(None, usize::parse_ref(b"99999999999999999999+"));
+        match usize::parse_next([&big[..]].iter()) {
+            Err(ParseFieldError::IdTooLong { name }) => assert_eq!(&big[..], name),
+            _ => panic!("the id should be too long to be encoded"),
+        }
+        match usize::parse_next_ref([&b"99999999999999999999+"[..]].iter()) {
+            Err(ParseFieldError::IdTooLong { name }) => assert_eq!(&big[..], name),
+            _ => panic!("the reference should be too long to be encoded"),
+        }
+    }
 
-        assert_eq!(a, get_char_from_code(a_));
-        assert_eq!(
-            a_,
-            get_code_from_char(a).to_string().parse::<i32>().unwrap()
-        );
-        println!("{} = {}", a, get_char_from_code(a_));
-        println!("{} = {}", a_, get_code_from_char(a));
+    #[test]
+    fn invalid_usize_id_is_none() {
+        for invalid in [&b""[..], b" ", b"foo bar", b"\t", "é".as_bytes()].iter() {
+            assert_eq!(None, usize::parse_id(invalid));
+            assert_eq!(None, usize::parse_opt_id(invalid));
+            assert_eq!(None, usize::parse_ref(invalid));
+            match usize::parse_next([invalid].iter()) {
+                Err(ParseFieldError::UintIdError) => (),
+                _ => panic!("{:?} should not be a valid id", invalid.as_bstr()),
+            }
+        }
+        assert_eq!(None, usize::parse_ref(b"+"));
     }
 
     #[test]
-    fn can_parse_string_to_usize() {
-        let s = "texthree";
-        let my_vec: Vec<char> = s.chars().collect();
-        let mut res: String = "".to_string();
-        let len = s.len();
-        let mut x = 0;
-        while x < len {
-            res = format!(
-                "{}{}",
-                res,
-                &get_code_from_char(&my_vec[x].to_string()).to_string()
-            );
-            x += 1;
+    fn ref_without_orientation_is_none() {
+        assert_eq!(None, usize::parse_ref(b"12"));
+        assert_eq!(None, usize::parse_ref(b"1+2"));
+        assert_eq!(None, BString::parse_ref(b"12"));
+        assert_eq!(Some(0b1_000001_000010_0), usize::parse_ref(b"12+"));
+        assert_eq!(Some(0b1_000001_000010_1), usize::parse_ref(b"12-"));
+    }
+
+    #[test]
+    fn usize_ids_use_one_encoding() {
+        // digits only, letters only and mixed names: 6 bits per char
+        assert_eq!(Some(0b1_000001_000010), usize::parse_id(b"12"));
+        assert_eq!(Some(0b1_000000), usize::parse_id(b"0"));
+        assert_eq!(Some(0b1_100100_100101), usize::parse_id(b"ab"));
+        assert_eq!(Some(0b1_100100_000001), usize::parse_id(b"a1"));
+        assert_eq!(Some(0b1_000001_100100), usize::parse_id(b"1a"));
+        assert_eq!(Some(0b1_111110_001010), usize::parse_id(b"_A"));
+        // the leading zeros are part of the name
+        assert_ne!(usize::parse_id(b"007"), usize::parse_id(b"7"));
+
+        let names = [
+            "12",
+            "0",
+            "007",
+            "ab",
+            "a1",
+            "1a",
+            "s148227",
+            "53_38",
+            "utg000001l",
+            "ctg.1",
+        ];
+        for name in names.iter() {
+            let id = usize::parse_id(name.as_bytes()).unwrap();
+            assert_eq!(*name, id.to_name());
+            let (seg, orient) = usize::parse_ref(format!("{}-", name).as_bytes())
+                .unwrap()
+                .split_ref()
+                .unwrap();
+            assert_eq!(id, seg);
+            assert_eq!(Orientation::Backward, orient);
         }
-        match res.len() {
-            1..=20 => println!("{}\n{}", res, res.len()),
-            _ => println!("Error! the conversion of the string into usize exceeds the maximum length (20 digits)"),
+        assert_eq!("*", usize::anonymous().to_name());
+    }
+
+    #[test]
+    fn names_that_cannot_be_encoded() {
+        // too long, or with a char out of [0-9A-Za-z_.]
+        let too_long = "a".repeat(MAX_ID_CHARS + 1);
+        for name in [too_long.as_bytes(), b"a-b", b"x:1", b"!"].iter() {
+            assert_eq!(None, usize::parse_id(name));
+            match usize::parse_next([name].iter()) {
+                Err(ParseFieldError::IdTooLong { name: found }) => assert_eq!(name, &found),
+                _ => panic!("{:?} should not fit in a usize", name.as_bstr()),
+            }
         }
+        let longest = "z".repeat(MAX_ID_CHARS);
+        let id = usize::parse_id(longest.as_bytes()).unwrap();
+        assert_eq!(longest, id.to_name());
+        assert!(usize::parse_ref(format!("{}-", longest).as_bytes()).is_some());
+    }
+
+    #[test]
+    fn can_decode_usize_id() {
+        let id = usize::parse_id(b"s148227").unwrap();
+        assert_eq!(Some(BString::from("s148227")), decode_usize_id(id));
+        assert_eq!(
+            Some(BString::from("a1")),
+            decode_usize_id(0b1_100100_000001)
+        );
+        assert_eq!(
+            Some(BString::from("*")),
+            decode_usize_id(usize::anonymous())
+        );
+        // no leading bit, or the bits are not a whole number of chars
+        assert_eq!(None, decode_usize_id(0));
+        assert_eq!(None, decode_usize_id(1));
+        assert_eq!(None, decode_usize_id(0b10));
+        assert_eq!(None, decode_usize_id(0b1_100100_1));
     }
 
     #[test]
-    fn can_parse_big_id() {
-        let s = "s148227";
-        let my_vec: Vec<char> = s.chars().collect();
-        let mut res: String = "".to_string();
-        let len = s.len();
-        let mut x = 0;
-        while x < len {
-            res = format!(
-                "{}{}",
-                res,
-                &get_code_from_char(&my_vec[x].to_string()).to_string()
-            );
-            x += 1;
+    fn no_name_is_anonymous() {
+        assert_eq!(Some(usize::anonymous()), usize::parse_opt_id(b"*"));
+        // encoded as usize::MAX by the previous encoding
+        assert_ne!(Some(usize::anonymous()), usize::parse_opt_id(b"2LcL'E)W0/"));
+        for name in [&b"18446744073709551615"[..], b"2LcL'E)W0/"].iter() {
+            match usize::parse_next_opt([name].iter()) {
+                Err(ParseFieldError::IdTooLong { .. }) => (),
+                Ok(id) => assert!(!id.is_anonymous()),
+                Err(err) => panic!("unexpected error {}", err),
+            }
         }
-        match res.len() {
-            1..=20 => println!("{}\n{}", res, res.len()),
-            _ => println!("Error! the conversion of the string into usize exceeds the maximum length (20 digits)"),
+    }
+
+    #[test]
+    fn id_with_whitespace_is_rejected() {
+        assert_eq!(None, BString::parse_id(b"foo bar"));
+        assert_eq!(None, BString::parse_opt_id(b"foo\tbar"));
+        assert_eq!(None, BString::parse_ref(b"foo bar+"));
+        assert_eq!(Some("foo".into()), BString::parse_id(b"foo"));
+        match BString::parse_next([&b"foo bar"[..]].iter()) {
+            Err(ParseFieldError::InvalidField(field)) => assert_eq!("Id", field),
+            _ => panic!("an id with a whitespace should be rejected"),
         }
     }
 
@@ -0,0 +1,470 @@
+/// file that collects the functions used to convert a GFA2 object
+/// into a GFA (GFA1) object
+use crate::{
+    gfa1, gfa2::*, gfa_to_gfa2_conversion::ConversionOptions, parser_gfa2::ConversionError, tag::*,
+};
+
+use bstr::BString;
+use std::collections::HashSet;
+use std::convert::TryFrom;
+use std::hash::Hash;
+
+impl<N: SegmentId + Eq + Hash, T: OptFields> GFA2<N, T> {
+    /// Returns the lines of the GFA2 object that have no equivalent in
+    /// the GFA1 format, and so would be lost converting it:
+    /// - fragments that don't cover their external sequence entirely
+    /// - gaps and u-groups
+    /// - edges that are neither dovetail overlaps nor containments
+    /// - o-groups that contain something other than segments
+    pub fn gfa1_blocking_lines(&self) -> Vec<LineRef<'_, N, T>> {
+        let segments: HashSet<&N> = self.segments.iter().map(|s| &s.id).collect();
+        let only_segments = |o: &GroupO<N, T>| only_segments(o, &segments);
+
+        let fragments = self
+            .fragments
+            .iter()
+            .filter(|f| !f.is_containment())
+            .map(LineRef::Fragment);
+        let edges = self
+            .edges
+            .iter()
+            .filter(|e| !e.is_dovetail() && !e.is_containment())
+            .map(LineRef::Edge);
+        let gaps = self.gaps.iter().map(LineRef::Gap);
+        let ogroups = self
+            .groups_o
+            .iter()
+            .filter(|o| !only_segments(o))
+            .map(LineRef::GroupO);
+        let ugroups = self.groups_u.iter().map(LineRef::GroupU);
+
+        fragments
+            .chain(edges)
+            .chain(gaps)
+            .chain(ogroups)
+            .chain(ugroups)
+            .collect()
+    }
+
+    /// Checks if the GFA2 object can be converted into a GFA1 object
+    /// without losing any line (see gfa1_blocking_lines)
+    pub fn is_gfa1_convertible(&self) -> bool {
+        self.gfa1_blocking_lines().is_empty()
+    }
+}
+
+/// checks if every member of an o-group is a segment, as a GFA1 path
+/// can't go through edges or other groups
+fn only_segments<N, T>(group: &GroupO<N, T>, segments: &HashSet<&N>) -> bool
+where
+    N: SegmentId + Eq + Hash,
+    T: OptFields,
+{
+    !group.is_empty()
+        && group.members().all(|member| {
+            match member.split_last().and_then(|(_, id)| N::parse_id(id)) {
+                Some(id) => segments.contains(&id),
+                None => false,
+            }
+        })
+}
+
+/// returns the overlap of a GFA1 line from the alignment of an edge,
+/// swapping insertions and deletions if the segments have been swapped.\
+/// A trace has no equivalent in GFA1, so it becomes ```*```
+fn gfa1_overlap(alignment: &[u8], swap: bool) -> BString {
+    match alignment.last() {
+        Some(c) if c.is_ascii_alphabetic() => alignment
+            .iter()
+            .map(|&c| match c {
+                b'I' if swap => b'D',
+                b'D' if swap => b'I',
+                c => c,
+            })
+            .collect::<Vec<u8>>()
+            .into(),
+        _ => "*".into(),
+    }
+}
+
+/// converts an edge into a GFA1 link (dovetail overlap) or
+/// containment
+fn edge_to_gfa1<N: SegmentId, T: OptFields>(
+    edge: Edge<N, T>,
+) -> Result<gfa1::Line<N, T>, ConversionError> {
+    let (id1, orient1) = edge.sid1.split_ref().ok_or(ConversionError::InvalidEdge)?;
+    let (id2, orient2) = edge.sid2.split_ref().ok_or(ConversionError::InvalidEdge)?;
+    let ends1 = Edge::<N, T>::reached_ends(&edge.beg1, &edge.end1);
+    let ends2 = Edge::<N, T>::reached_ends(&edge.beg2, &edge.end2);
+    let ((start1, end1), (start2, end2)) = match (ends1, ends2) {
+        (Some(ends1), Some(ends2)) => (ends1, ends2),
+        _ => return Err(ConversionError::InvalidEdge),
+    };
+
+    if edge.is_dovetail() {
+        // the positions are on the forward strand, so the end of a
+        // reversed segment is its start
+        let at_end1 = if orient1.is_reverse() { start1 } else { end1 };
+        let at_start2 = if orient2.is_reverse() { end2 } else { start2 };
+        let link = match (at_end1, at_start2) {
+            (true, true) => gfa1::Link {
+                from_segment: id1,
+                from_orient: orient1,
+                to_segment: id2,
+                to_orient: orient2,
+                overlap: gfa1_overlap(&edge.alignment, false),
+                optional: edge.tag,
+            },
+            (false, false) => gfa1::Link {
+                from_segment: id2,
+                from_orient: orient2,
+                to_segment: id1,
+                to_orient: orient1,
+                overlap: gfa1_overlap(&edge.alignment, true),
+                optional: edge.tag,
+            },
+            _ => return Err(ConversionError::InvalidEdge),
+        };
+        Ok(gfa1::Line::Link(link))
+    } else if edge.is_containment() {
+        let parse_beg = |beg: &BString| parse_position(beg).map(|(pos, _)| pos);
+        // the contained segment is the one covered entirely
+        let containment = if start2 && end2 {
+            gfa1::Containment {
+                container_name: id1,
+                container_orient: orient1,
+                contained_name: id2,
+                contained_orient: orient2,
+                pos: parse_beg(&edge.beg1).ok_or(ConversionError::InvalidEdge)?,
+                overlap: gfa1_overlap(&edge.alignment, false),
+                optional: edge.tag,
+            }
+        } else {
+            gfa1::Containment {
+                container_name: id2,
+                container_orient: orient2,
+                contained_name: id1,
+                contained_orient: orient1,
+                pos: parse_beg(&edge.beg2).ok_or(ConversionError::InvalidEdge)?,
+                overlap: gfa1_overlap(&edge.alignment, true),
+                optional: edge.tag,
+            }
+        };
+        Ok(gfa1::Line::Containment(containment))
+    } else {
+        Err(ConversionError::InvalidEdge)
+    }
+}
+
+impl<N: SegmentId, T: OptFields> Fragment<N, T> {
+    /// Checks if the fragment covers its external sequence entirely, so
+    /// that the external sequence is contained in the segment
+    pub fn is_containment(&self) -> bool {
+        Edge::<N, T>::reached_ends(&self.fbeg, &self.fend) == Some((true, true))
+            && self.ext_ref.split_ref().is_some()
+            && parse_position(&self.sbeg).is_some()
+    }
+}
+
+/// converts a fragment into a GFA1 containment of the external
+/// sequence in the segment, if the fragment covers it entirely
+fn fragment_to_gfa1<N: SegmentId, T: OptFields>(
+    fragment: Fragment<N, T>,
+) -> Result<gfa1::Line<N, T>, ConversionError> {
+    if !fragment.is_containment() {
+        return Err(ConversionError::InvalidFragment);
+    }
+    let (contained_name, contained_orient) = fragment
+        .ext_ref
+        .split_ref()
+        .ok_or(ConversionError::InvalidFragment)?;
+    let (pos, _) = parse_position(&fragment.sbeg).ok_or(ConversionError::InvalidFragment)?;
+
+    Ok(gfa1::Line::Containment(gfa1::Containment {
+        container_name: fragment.id,
+        container_orient: Orientation::Forward,
+        contained_name,
+        contained_orient,
+        pos,
+        overlap: gfa1_overlap(&fragment.alignment, false),
+        optional: fragment.tag,
+    }))
+}
+
+/// Converts a single GFA2 line into a GFA1 line:
+/// - the header takes the GFA1 version
+/// - segments keep their name and sequence (the length is dropped)
+/// - edges become links (dovetail overlaps) or containments
+/// - o-groups become paths
+/// - fragments become containments of the external sequence in the
+///   segment, if they cover it entirely
+/// - comments and custom records are kept as they are
+///
+/// gaps and u-groups have no counterpart in GFA1
+impl<N: SegmentId, T: OptFields> TryFrom<Line<N, T>> for gfa1::Line<N, T> {
+    type Error = ConversionError;
+
+    fn try_from(line: Line<N, T>) -> Result<Self, Self::Error> {
+        match line {
+            Line::Header(h) => Ok(gfa1::Line::Header(gfa1::Header {
+                version: h.version.map(|_| "VN:Z:1.0".into()),
+                optional: h.tag,
+            })),
+            Line::Segment(s) => Ok(gfa1::Line::Segment(gfa1::Segment {
+                sequence: s.sequence_bytes().into_owned().into(),
+                name: s.id,
+                optional: s.tag,
+            })),
+            Line::Edge(e) => edge_to_gfa1(e),
+            Line::GroupO(o) => {
+                let segment_names = bstr::join(",", o.members()).into();
+                Ok(gfa1::Line::Path(gfa1::Path::new(
+                    o.id,
+                    segment_names,
+                    "*".into(),
+                    o.tag,
+                )))
+            }
+            Line::Fragment(f) => fragment_to_gfa1(f),
+            Line::Gap(_) => Err(ConversionError::NoCounterpart("Gap")),
+            Line::GroupU(_) => Err(ConversionError::NoCounterpart("U-Group")),
+            Line::Comment(c) => Ok(gfa1::Line::Comment(c)),
+            Line::CustomRecord(r) => Ok(gfa1::Line::CustomRecord(r)),
+        }
+    }
+}
+
+/// Converts a whole GFA2 object into a GFA1 object, failing with the
+/// error of the first line that has no equivalent in GFA1 (see
+/// gfa1_blocking_lines), so that nothing is lost silently
+///
+/// # Examples
+///
+/// ```ignore
+/// let parser: GFA2Parser<BString, OptionalFields> = GFA2Parser::new();
+/// let gfa2 = parser.parse_file("./tests/gfa2_files/spec_q7.gfa").unwrap();
+///
+/// let gfa = GFA::try_from(gfa2).unwrap();
+/// ```
+impl<N: SegmentId + Eq + Hash, T: OptFields> TryFrom<GFA2<N, T>> for gfa1::GFA<N, T> {
+    type Error = ConversionError;
+
+    fn try_from(gfa2: GFA2<N, T>) -> Result<Self, Self::Error> {
+        {
+            let segments: HashSet<&N> = gfa2.segments.iter().map(|s| &s.id).collect();
+            if !gfa2.groups_o.iter().all(|o| only_segments(o, &segments)) {
+                return Err(ConversionError::InvalidGroup);
+            }
+        }
+
+        let mut gfa = gfa1::GFA::new();
+        for line in gfa2.lines_into_iter() {
+            gfa.insert_line(gfa1::Line::try_from(line)?);
+        }
+        Ok(gfa)
+    }
+}
+
+/// Function that converts a GFA2 object into a GFA1 object, without
+/// consuming it (see ```GFA::try_from```):
+/// - edges become links (dovetail overlaps) or containments, any
+///   other edge is an ```InvalidEdge``` error
+/// - o-groups become paths
+/// - segments lose their length
+///
+/// # Examples
+///
+/// ```ignore
+/// use gfa2::gfa2_to_gfa1_conversion::convert_gfa2_to_gfa1;
+///
+/// let parser: GFA2Parser<BString, OptionalFields> = GFA2Parser::new();
+/// let gfa2 = parser.parse_file("./tests/gfa2_files/spec_q7.gfa").unwrap();
+///
+/// match convert_gfa2_to_gfa1(&gfa2) {
+///     Ok(gfa) => println!("{}", gfa),
+///     Err(err) => eprintln!("{}", err),
+/// }
+/// ```
+pub fn convert_gfa2_to_gfa1<N, T>(gfa2: &GFA2<N, T>) -> Result<gfa1::GFA<N, T>, ConversionError>
+where
+    N: SegmentId + Clone + Eq + Hash,
+    T: OptFields,
+{
+    gfa1::GFA::try_from(gfa2.clone())
+}
+
+/// Like convert_gfa2_to_gfa1, but the conversion follows the given
+/// options
+///
+/// # Examples
+///
+/// ```ignore
+/// use gfa2::{
+///     gfa2_to_gfa1_conversion::convert_gfa2_to_gfa1_with,
+///     gfa_to_gfa2_conversion::ConversionOptions,
+/// };
+///
+/// let options = ConversionOptions { sort_canonical: true };
+/// let gfa = convert_gfa2_to_gfa1_with(&gfa2, &options).unwrap();
+/// ```
+pub fn convert_gfa2_to_gfa1_with<N, T>(
+    gfa2: &GFA2<N, T>,
+    options: &ConversionOptions,
+) -> Result<gfa1::GFA<N, T>, ConversionError>
+where
+    N: SegmentId + Clone + Eq + Hash + PartialOrd,
+    T: OptFields + PartialOrd,
+{
+    let mut gfa = convert_gfa2_to_gfa1(gfa2)?;
+    if options.sort_canonical {
+        gfa.sort_canonical();
+    }
+    Ok(gfa)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{gfa2::*, parser_gfa2::GFA2Parser};
+    use bstr::BString;
+
+    #[test]
+    fn can_check_gfa1_convertible() {
+        let parser: GFA2Parser<BString, ()> = GFA2Parser::new();
+        let gfa2: GFA2<BString, ()> = parser.parse_file("./tests/gfa2_files/spec_q7.gfa").unwrap();
+        assert!(gfa2.is_gfa1_convertible());
+
+        let gfa2: GFA2<BString, ()> = parser.parse_file("./tests/gfa2_files/sample2.gfa").unwrap();
+        assert!(!gfa2.is_gfa1_convertible());
+        let blocking = gfa2.gfa1_blocking_lines();
+        // 2 fragments, 2 gaps, 2 u-groups and 1 o-group with an edge
+        assert_eq!(7, blocking.len());
+        assert_eq!(
+            Some("15"),
+            blocking
+                .into_iter()
+                .find_map(|line| line.some_ogroup())
+                .map(|o| o.id.to_string())
+                .as_deref()
+        );
+    }
+
+    #[test]
+    fn can_convert_gfa2_object() {
+        use super::convert_gfa2_to_gfa1;
+        use crate::parser_gfa2::ConversionError;
+
+        let parser: GFA2Parser<BString, ()> = GFA2Parser::new();
+        let gfa2: GFA2<BString, ()> = parser.parse_file("./tests/gfa2_files/spec_q7.gfa").unwrap();
+        let gfa = convert_gfa2_to_gfa1(&gfa2).unwrap();
+        assert_eq!(3, gfa.segments.len());
+        assert_eq!(3, gfa.links.len());
+        assert_eq!("11+,12-,13+", gfa.paths[0].segment_names);
+
+        let lines = ["S\t1\t4\tACGT", "S\t2\t4\tACGT", "O\tp1\t1+ e1+ 2+"];
+        let gfa2: GFA2<BString, ()> = parser.parse_lines(lines.iter()).unwrap();
+        assert_eq!(
+            Err(ConversionError::InvalidGroup),
+            convert_gfa2_to_gfa1(&gfa2)
+        );
+        // the members can be separated by multiple spaces
+        let lines = ["S\t1\t4\tACGT", "S\t2\t4\tACGT"];
+        let mut gfa2: GFA2<BString, ()> = parser.parse_lines(lines.iter()).unwrap();
+        gfa2.groups_o
+            .push(GroupO::new("p1".into(), "1+  2+".into(), ()));
+        assert!(gfa2.is_gfa1_convertible());
+        assert_eq!(
+            "1+,2+",
+            convert_gfa2_to_gfa1(&gfa2).unwrap().paths[0].segment_names
+        );
+
+        let lines = ["S\t1\t4\tACGT", "E\t*\t1+\t2+\t1\t3\t1\t3\t2M"];
+        let gfa2: GFA2<BString, ()> = parser.parse_lines(lines.iter()).unwrap();
+        assert_eq!(
+            Err(ConversionError::InvalidEdge),
+            convert_gfa2_to_gfa1(&gfa2)
+        );
+    }
+
+    #[test]
+    fn can_sort_converted_gfa1() {
+        use super::{convert_gfa2_to_gfa1, convert_gfa2_to_gfa1_with};
+        use crate::gfa_to_gfa2_conversion::ConversionOptions;
+
+        let lines = [
+            "S\t2\t4\tACGT",
+            "S\t1\t4\tACGT",
+            "E\t*\t2+\t1+\t3\t4$\t0\t1\t1M",
+            "E\t*\t1+\t2+\t3\t4$\t0\t1\t1M",
+        ];
+        let parser: GFA2Parser<BString, ()> = GFA2Parser::new();
+        let gfa2: GFA2<BString, ()> = parser.parse_lines(lines.iter()).unwrap();
+        let shuffled: GFA2<BString, ()> = parser.parse_lines(lines.iter().rev()).unwrap();
+        assert_ne!(
+            convert_gfa2_to_gfa1(&gfa2).unwrap(),
+            convert_gfa2_to_gfa1(&shuffled).unwrap()
+        );
+
+        let options = ConversionOptions {
+            sort_canonical: true,
+        };
+        let gfa = convert_gfa2_to_gfa1_with(&gfa2, &options).unwrap();
+        assert_eq!(gfa, convert_gfa2_to_gfa1_with(&shuffled, &options).unwrap());
+        assert_eq!("1", gfa.segments[0].name);
+        assert_eq!("1", gfa.links[0].from_segment);
+    }
+
+    fn gfa1_line_to_string(line: crate::gfa1::Line<BString, ()>) -> String {
+        use crate::gfa1::Line;
+        match line {
+            Line::Header(h) => h.to_string(),
+            Line::Segment(s) => s.to_string(),
+            Line::Link(l) => l.to_string(),
+            Line::Containment(c) => c.to_string(),
+            Line::Path(p) => p.to_string(),
+            Line::Comment(c) => format!("#{}", c),
+            Line::CustomRecord(r) => r.to_string(),
+        }
+    }
+
+    #[test]
+    fn can_convert_gfa2_lines() {
+        use crate::{gfa1, parser_gfa2::ConversionError};
+        use std::convert::TryFrom;
+
+        let lines = [
+            "H\tVN:Z:2.0",
+            "S\t11\t5\tACCTT",
+            "E\t*\t11+\t12-\t1\t5$\t2\t6$\t4M",
+            "E\t*\t11-\t13+\t0\t3\t0\t3\t2M1I",
+            "E\t*\t13+\t11+\t0\t2\t3\t5$\t2M1I",
+            "E\t*\t11+\t13+\t1\t3\t0\t2$\t2M",
+            "O\t14\t11+ 12- 13+",
+            "G\tg1\t11+\t13+\t10\t*",
+            "F\t11\tr1-\t1\t4\t0\t3$\t2M1D",
+            "F\t11\tr2+\t0\t3\t2\t6\t*",
+        ];
+        let parser: GFA2Parser<BString, ()> = GFA2Parser::new();
+        let converted: Vec<_> = parser
+            .parse_lines(lines.iter())
+            .unwrap()
+            .lines_into_iter()
+            .map(|line| gfa1::Line::try_from(line).map(gfa1_line_to_string))
+            .collect();
+
+        assert_eq!(
+            vec![
+                Ok("H\tVN:Z:1.0".to_string()),
+                Ok("S\t11\tACCTT".to_string()),
+                Ok("C\t11\t+\tr1\t-\t1\t2M1D".to_string()),
+                Err(ConversionError::InvalidFragment),
+                Ok("L\t11\t+\t12\t-\t4M".to_string()),
+                Ok("L\t11\t-\t13\t+\t2M1I".to_string()),
+                Ok("L\t11\t+\t13\t+\t2M1D".to_string()),
+                Ok("C\t11\t+\t13\t+\t1\t2M".to_string()),
+                Err(ConversionError::NoCounterpart("Gap")),
+                Ok("P\t14\t11+,12-,13+\t*".to_string()),
+            ],
+            converted
+        );
+    }
+}
@@ -0,0 +1,612 @@
+/// file that collects the functions used to convert a GFA (GFA1) object
+/// into a GFA2 object
+use crate::{
+    gfa1,
+    gfa2::*,
+    parser_gfa1::GFAParser,
+    parser_gfa2::{ConversionError, ParseError},
+    tag::*,
+};
+
+use bstr::{BString, ByteSlice};
+use std::collections::{BTreeMap, HashMap};
+use std::{convert::TryFrom, hash::Hash};
+
+/// Converts a single GFA1 line into a GFA2 line:
+/// - the header takes the GFA2 version
+/// - segments get their length from the sequence, or from the `LN`
+///   tag if the sequence is ```*```
+/// - paths become o-groups (the overlaps are dropped)
+/// - comments and custom records are kept as they are
+///
+/// links and containments can't be converted one at a time, as the
+/// positions of an edge need the length of its segments
+impl<N: SegmentId, T: OptFields> TryFrom<gfa1::Line<N, T>> for Line<N, T> {
+    type Error = ConversionError;
+
+    fn try_from(line: gfa1::Line<N, T>) -> Result<Self, Self::Error> {
+        match line {
+            gfa1::Line::Header(h) => Ok(Line::Header(Header {
+                version: h.version.map(|_| "VN:Z:2.0".into()),
+                tag: h.optional,
+            })),
+            gfa1::Line::Segment(s) => {
+                let len: BString = if s.sequence == "*" {
+                    s.optional
+                        .get_field(b"LN")
+                        .map(|ln| ln.value_bytes().into())
+                        .ok_or(ConversionError::MissingSegmentLength("Segment"))?
+                } else {
+                    s.sequence.len().to_string().into()
+                };
+                Ok(Line::Segment(Segment {
+                    id: s.name,
+                    len,
+                    sequence: s.sequence.into(),
+                    tag: s.optional,
+                }))
+            }
+            gfa1::Line::Path(p) => Ok(Line::GroupO(GroupO::new(
+                p.path_name,
+                p.segment_names.replace(",", " ").into(),
+                p.optional,
+            ))),
+            gfa1::Line::Link(_) => Err(ConversionError::MissingSegmentLength("Link")),
+            gfa1::Line::Containment(_) => Err(ConversionError::MissingSegmentLength("Containment")),
+            gfa1::Line::Comment(c) => Ok(Line::Comment(c)),
+            gfa1::Line::CustomRecord(r) => Ok(Line::CustomRecord(r)),
+        }
+    }
+}
+
+/// returns a position field, marking with the final `$` char the
+/// end of a segment of length `len`
+fn position(pos: usize, len: usize) -> BString {
+    if pos == len {
+        format!("{}$", pos).into()
+    } else {
+        pos.to_string().into()
+    }
+}
+
+/// returns the reference (the id followed by the orientation) of a segment
+fn oriented_ref<N: SegmentId>(id: &N, orient: Orientation) -> Result<N, ConversionError> {
+    N::parse_ref(format!("{}{}", id.to_name(), orient).as_bytes())
+        .ok_or(ConversionError::InvalidEdge)
+}
+
+/// converts a GFA1 link into a dovetail edge, given the length of the
+/// segment it starts from (`len1`) and of the one it reaches (`len2`)
+fn link_to_edge<N: SegmentId, T: OptFields>(
+    link: gfa1::Link<N, T>,
+    len1: usize,
+    len2: usize,
+) -> Result<Edge<N, T>, ConversionError> {
+    // an unknown overlap (*) is taken as a blunt one
+    let overlap = Cigar::parse(&link.overlap).map_err(|_| ConversionError::InvalidEdge)?;
+    let (ov1, ov2) = (overlap.reference_len(), overlap.query_len());
+    if ov1 > len1 || ov2 > len2 {
+        return Err(ConversionError::InvalidEdge);
+    }
+
+    // the positions are on the forward strand, so the end of a
+    // reversed segment is its start
+    let (beg1, end1) = if link.from_orient.is_reverse() {
+        (0, ov1)
+    } else {
+        (len1 - ov1, len1)
+    };
+    let (beg2, end2) = if link.to_orient.is_reverse() {
+        (len2 - ov2, len2)
+    } else {
+        (0, ov2)
+    };
+    Ok(Edge {
+        id: N::anonymous(),
+        sid1: oriented_ref(&link.from_segment, link.from_orient)?,
+        sid2: oriented_ref(&link.to_segment, link.to_orient)?,
+        beg1: position(beg1, len1),
+        end1: position(end1, len1),
+        beg2: position(beg2, len2),
+        end2: position(end2, len2),
+        alignment: link.overlap,
+        tag: link.optional,
+    })
+}
+
+/// converts a GFA1 containment into an edge, given the length of the
+/// container (`len1`) and of the contained segment (`len2`)
+fn containment_to_edge<N: SegmentId, T: OptFields>(
+    containment: gfa1::Containment<N, T>,
+    len1: usize,
+    len2: usize,
+) -> Result<Edge<N, T>, ConversionError> {
+    let overlap = Cigar::parse(&containment.overlap).map_err(|_| ConversionError::InvalidEdge)?;
+    // an unknown overlap (*) covers the whole contained segment
+    let covered = if overlap.0.is_empty() {
+        len2
+    } else {
+        overlap.reference_len()
+    };
+    let end1 = containment.pos + covered;
+    if end1 > len1 {
+        return Err(ConversionError::InvalidEdge);
+    }
+
+    Ok(Edge {
+        id: N::anonymous(),
+        sid1: oriented_ref(&containment.container_name, containment.container_orient)?,
+        sid2: oriented_ref(&containment.contained_name, containment.contained_orient)?,
+        beg1: position(containment.pos, len1),
+        end1: position(end1, len1),
+        beg2: position(0, len2),
+        end2: position(len2, len2),
+        alignment: containment.overlap,
+        tag: containment.optional,
+    })
+}
+
+/// How many lines of each type (```"Header"```, ```"Segment"```,
+/// ```"Link"```, ```"Containment"``` or ```"Path"```) a conversion
+/// has written and dropped
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ConversionReport {
+    pub converted: BTreeMap<&'static str, usize>,
+    pub dropped: BTreeMap<&'static str, usize>,
+}
+
+impl ConversionReport {
+    /// Returns the total number of dropped lines
+    pub fn total_dropped(&self) -> usize {
+        self.dropped.values().sum()
+    }
+}
+
+/// Options of the converters between the GFA1 and the GFA2 format
+/// (see ```convert_gfa_to_gfa2_with```, ```convert_gfa1_to_gfa2_file_with```
+/// and ```convert_gfa2_to_gfa1_with```)
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ConversionOptions {
+    /// Sorts the converted lines and their optional fields (see
+    /// sort_canonical), so that the same graph is always converted
+    /// into the same output, whatever the order of its lines.
+    pub sort_canonical: bool,
+}
+
+/// returns the segment length used by the GFA2 format: the length of
+/// the sequence, or the `LN` tag if the sequence is ```*```
+fn gfa1_segment_len<N, T: OptFields>(segment: &gfa1::Segment<N, T>) -> Option<usize> {
+    if segment.sequence == "*" {
+        let ln = segment.optional.get_field(b"LN")?;
+        ln.value_bytes().to_str().ok()?.parse().ok()
+    } else {
+        Some(segment.sequence.len())
+    }
+}
+
+/// Converts a whole GFA1 object into a GFA2 object, stopping at the
+/// first line that can't be converted (e.g. a segment without sequence
+/// nor `LN` tag, or a link to it).\
+/// Links become dovetail edges and containments become edges covering
+/// the contained segment. If the GFA1 object has no header with a
+/// version, a ```VN:Z:2.0``` header is added as the first one.
+///
+/// # Examples
+///
+/// ```ignore
+/// let parser: GFAParser<BString, OptionalFields> = GFAParser::new();
+/// let gfa = parser.parse_file("./tests/gfa1_files/lil.gfa").unwrap();
+///
+/// let gfa2 = GFA2::try_from(gfa).unwrap();
+/// ```
+impl<N, T> TryFrom<gfa1::GFA<N, T>> for GFA2<N, T>
+where
+    N: SegmentId + Clone + Eq + Hash,
+    T: OptFields,
+{
+    type Error = ConversionError;
+
+    fn try_from(gfa: gfa1::GFA<N, T>) -> Result<Self, Self::Error> {
+        let lengths: HashMap<N, usize> = gfa
+            .segments
+            .iter()
+            .filter_map(|s| Some((s.name.clone(), gfa1_segment_len(s)?)))
+            .collect();
+        let len = |id: &N| lengths.get(id).copied();
+        let missing = ConversionError::MissingSegmentLength;
+
+        let mut gfa2 = GFA2::new();
+        if gfa.headers.iter().all(|h| h.version.is_none()) {
+            gfa2.headers.push(Header::new(Some("VN:Z:2.0".into())));
+        }
+        for line in gfa.lines_into_iter() {
+            let converted = match line {
+                gfa1::Line::Link(l) => {
+                    let (len1, len2) = len(&l.from_segment)
+                        .zip(len(&l.to_segment))
+                        .ok_or_else(|| missing("Link"))?;
+                    Line::Edge(link_to_edge(l, len1, len2)?)
+                }
+                gfa1::Line::Containment(c) => {
+                    let (len1, len2) = len(&c.container_name)
+                        .zip(len(&c.contained_name))
+                        .ok_or_else(|| missing("Containment"))?;
+                    Line::Edge(containment_to_edge(c, len1, len2)?)
+                }
+                line => Line::try_from(line)?,
+            };
+            gfa2.insert_line(converted);
+        }
+        Ok(gfa2)
+    }
+}
+
+/// Function that parses a GFA1 file and converts it into a GFA2 object,
+/// returning the parsing error or the conversion error (as
+/// ```ParseError::Conversion```) of the first line that can't be handled
+/// instead of dropping it.\
+/// To convert an already parsed GFA1 object use ```GFA2::try_from```
+///
+/// # Examples
+///
+/// ```ignore
+/// use gfa2::gfa_to_gfa2_conversion::convert_gfa_to_gfa2;
+///
+/// match convert_gfa_to_gfa2("./tests/gfa1_files/lil.gfa") {
+///     Ok(gfa2) => println!("{}", gfa2),
+///     Err(err) => eprintln!("{}", err),
+/// }
+/// ```
+pub fn convert_gfa_to_gfa2<P>(path: P) -> Result<GFA2<BString, OptionalFields>, ParseError>
+where
+    P: AsRef<std::path::Path>,
+{
+    convert_gfa_to_gfa2_with(path, &ConversionOptions::default())
+}
+
+/// Like convert_gfa_to_gfa2, but the conversion follows the given
+/// options
+///
+/// # Examples
+///
+/// ```ignore
+/// use gfa2::gfa_to_gfa2_conversion::{convert_gfa_to_gfa2_with, ConversionOptions};
+///
+/// let options = ConversionOptions { sort_canonical: true };
+/// let gfa2 = convert_gfa_to_gfa2_with("./tests/gfa1_files/lil.gfa", &options).unwrap();
+/// ```
+pub fn convert_gfa_to_gfa2_with<P>(
+    path: P,
+    options: &ConversionOptions,
+) -> Result<GFA2<BString, OptionalFields>, ParseError>
+where
+    P: AsRef<std::path::Path>,
+{
+    let parser: GFAParser<BString, OptionalFields> = GFAParser::new();
+    let gfa = parser.parse_file(path)?;
+    let mut gfa2 = GFA2::try_from(gfa)?;
+    if options.sort_canonical {
+        gfa2.sort_canonical();
+    }
+    Ok(gfa2)
+}
+
+/// Function that converts a GFA1 file into a GFA2 file, line by line,
+/// without storing the graph: the input is read twice, the first time
+/// to collect the length of the segments needed by the edges, the
+/// second one to write every converted line into `output`.\
+/// Links become dovetail edges and containments become edges covering
+/// the contained segment; the lines that can't be converted (e.g. a
+/// segment without sequence nor `LN` tag, or the links to it) are
+/// dropped and counted in the returned report.\
+/// If the input has no header with a version, a ```VN:Z:2.0``` header
+/// is written as the first line.
+///
+/// # Examples
+///
+/// ```ignore
+/// use gfa2::gfa_to_gfa2_conversion::convert_gfa1_to_gfa2_file;
+///
+/// let report = convert_gfa1_to_gfa2_file(
+///     "./tests/gfa1_files/lil.gfa",
+///     "./tests/output_files/lil.gfa2",
+/// ).unwrap();
+/// println!("{:?}", report);
+/// ```
+pub fn convert_gfa1_to_gfa2_file<P, Q>(input: P, output: Q) -> Result<ConversionReport, ParseError>
+where
+    P: AsRef<std::path::Path>,
+    Q: AsRef<std::path::Path>,
+{
+    convert_gfa1_to_gfa2_file_with(input, output, &ConversionOptions::default())
+}
+
+/// Like convert_gfa1_to_gfa2_file, but the conversion follows the given
+/// options.\
+/// The lines can only be sorted once they have all been converted, so
+/// with ```sort_canonical``` the converted graph is stored and written
+/// at the end (with the comments after the other lines)
+///
+/// # Examples
+///
+/// ```ignore
+/// use gfa2::gfa_to_gfa2_conversion::{convert_gfa1_to_gfa2_file_with, ConversionOptions};
+///
+/// let options = ConversionOptions { sort_canonical: true };
+/// let report = convert_gfa1_to_gfa2_file_with(
+///     "./tests/gfa1_files/lil.gfa",
+///     "./tests/output_files/lil.gfa2",
+///     &options,
+/// ).unwrap();
+/// ```
+pub fn convert_gfa1_to_gfa2_file_with<P, Q>(
+    input: P,
+    output: Q,
+    options: &ConversionOptions,
+) -> Result<ConversionReport, ParseError>
+where
+    P: AsRef<std::path::Path>,
+    Q: AsRef<std::path::Path>,
+{
+    use {
+        bstr::io::BufReadExt,
+        std::{
+            fs::File,
+            io::{BufReader, BufWriter, Write},
+        },
+    };
+
+    let parser: GFAParser<BString, OptionalFields> = GFAParser::new();
+    let read_lines = || -> std::io::Result<_> {
+        let file = File::open(input.as_ref())?;
+        Ok(BufReader::new(file).byte_lines())
+    };
+
+    // first pass: collect the length of the segments
+    let mut lengths: HashMap<BString, usize> = HashMap::new();
+    let mut has_version = false;
+    for line in read_lines()? {
+        match parser.parse_gfa_line(line?.as_ref()) {
+            Ok(gfa1::Line::Segment(s)) => {
+                if let Some(len) = gfa1_segment_len(&s) {
+                    lengths.insert(s.name, len);
+                }
+            }
+            Ok(gfa1::Line::Header(h)) => has_version |= h.version.is_some(),
+            _ => (),
+        }
+    }
+
+    // second pass: convert and write every line
+    let mut out = BufWriter::new(File::create(output.as_ref())?);
+    let mut report = ConversionReport::default();
+    // the converted lines to sort, if they have to be sorted
+    let mut sorted: Option<GFA2<BString, OptionalFields>> = if options.sort_canonical {
+        Some(GFA2::new())
+    } else {
+        None
+    };
+    if !has_version {
+        let header: Header<OptionalFields> = Header::new(Some("VN:Z:2.0".into()));
+        match sorted.as_mut() {
+            Some(gfa2) => gfa2.headers.push(header),
+            None => writeln!(out, "{}", header)?,
+        }
+    }
+    for (line_no, line) in read_lines()?.enumerate() {
+        let line = match parser.parse_gfa_line(line?.as_ref()) {
+            Ok(line) => line,
+            Err(ParseError::EmptyLine) | Err(ParseError::UnknownLineType) => continue,
+            Err(err) => return Err(err.at_line(line_no + 1)),
+        };
+        let len = |id: &BString| lengths.get(id).copied();
+        let missing = |line_type| ConversionError::MissingSegmentLength(line_type);
+
+        let (line_type, converted) = match line {
+            gfa1::Line::Header(h) => ("Header", Line::try_from(gfa1::Line::Header(h))),
+            gfa1::Line::Segment(s) => ("Segment", Line::try_from(gfa1::Line::Segment(s))),
+            gfa1::Line::Path(p) => ("Path", Line::try_from(gfa1::Line::Path(p))),
+            gfa1::Line::Comment(c) => ("Comment", Ok(Line::Comment(c))),
+            gfa1::Line::CustomRecord(r) => ("Custom record", Ok(Line::CustomRecord(r))),
+            gfa1::Line::Link(l) => {
+                let lens = len(&l.from_segment).zip(len(&l.to_segment));
+                let edge = lens
+                    .ok_or_else(|| missing("Link"))
+                    .and_then(|(len1, len2)| link_to_edge(l, len1, len2));
+                ("Link", edge.map(Line::Edge))
+            }
+            gfa1::Line::Containment(c) => {
+                let lens = len(&c.container_name).zip(len(&c.contained_name));
+                let edge = lens
+                    .ok_or_else(|| missing("Containment"))
+                    .and_then(|(len1, len2)| containment_to_edge(c, len1, len2));
+                ("Containment", edge.map(Line::Edge))
+            }
+        };
+
+        match converted {
+            Ok(line) => {
+                match (sorted.as_mut(), line) {
+                    (Some(gfa2), line) => gfa2.insert_line(line),
+                    (None, Line::Header(h)) => writeln!(out, "{}", h)?,
+                    (None, Line::Segment(s)) => writeln!(out, "{}", s)?,
+                    (None, Line::Edge(e)) => writeln!(out, "{}", e)?,
+                    (None, Line::GroupO(o)) => writeln!(out, "{}", o)?,
+                    (None, Line::Comment(c)) => writeln!(out, "#{}", c)?,
+                    (None, Line::CustomRecord(r)) => writeln!(out, "{}", r)?,
+                    // a GFA1 line never becomes a fragment, a gap or an u-group
+                    _ => unreachable!(),
+                }
+                *report.converted.entry(line_type).or_insert(0) += 1;
+            }
+            Err(_) => *report.dropped.entry(line_type).or_insert(0) += 1,
+        }
+    }
+    if let Some(mut gfa2) = sorted {
+        gfa2.sort_canonical();
+        gfa2.write_to(&mut out)?;
+    }
+    out.flush()?;
+
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        gfa1, gfa2::*, parser_gfa1::GFAParser, parser_gfa2::ConversionError, tag::OptionalFields,
+    };
+    use bstr::{BString, ByteSlice};
+    use std::convert::TryFrom;
+
+    fn line_to_string(line: Line<BString, OptionalFields>) -> String {
+        match line {
+            Line::Header(h) => h.to_string(),
+            Line::Segment(s) => s.to_string(),
+            Line::Fragment(f) => f.to_string(),
+            Line::Edge(e) => e.to_string(),
+            Line::Gap(g) => g.to_string(),
+            Line::GroupO(o) => o.to_string(),
+            Line::GroupU(u) => u.to_string(),
+            Line::Comment(c) => format!("#{}", c),
+            Line::CustomRecord(r) => r.to_string(),
+        }
+    }
+
+    #[test]
+    fn can_convert_gfa1_lines() {
+        let lines = [
+            "H\tVN:Z:1.0",
+            "S\t11\tACCTT",
+            "S\t12\t*\tLN:i:6",
+            "S\t13\t*",
+            "L\t11\t+\t12\t-\t4M",
+            "P\t14\t11+,12-\t4M",
+        ];
+        let parser: GFAParser<BString, OptionalFields> = GFAParser::new();
+        let converted: Vec<_> = parser
+            .parse_lines(lines.iter())
+            .unwrap()
+            .lines_into_iter()
+            .map(|line| Line::try_from(line).map(line_to_string))
+            .collect();
+
+        assert_eq!(
+            vec![
+                Ok("H\tVN:Z:2.0".to_string()),
+                Ok("S\t11\t5\tACCTT".to_string()),
+                Ok("S\t12\t6\t*\tLN:i:6".to_string()),
+                Err(ConversionError::MissingSegmentLength("Segment")),
+                Err(ConversionError::MissingSegmentLength("Link")),
+                Ok("O\t14\t11+ 12-".to_string()),
+            ],
+            converted
+        );
+    }
+
+    #[test]
+    fn can_convert_gfa1_file() {
+        use super::convert_gfa1_to_gfa2_file;
+        use crate::parser_gfa2::GFA2Parser;
+
+        let output = std::env::temp_dir().join("gfa2_convert_lil.gfa2");
+        let report = convert_gfa1_to_gfa2_file("./tests/gfa1_files/lil.gfa", &output).unwrap();
+        assert_eq!(Some(&15), report.converted.get("Segment"));
+        assert_eq!(Some(&3), report.converted.get("Path"));
+        assert_eq!(0, report.total_dropped());
+
+        let parser: GFA2Parser<BString, ()> = GFA2Parser::new();
+        let gfa2 = parser.parse_file(&output).unwrap();
+        assert_eq!(Some(&gfa2.edges.len()), report.converted.get("Link"));
+        assert!(gfa2.edges.iter().all(Edge::is_blunt));
+        assert!(parser.validate_file(&output).is_empty());
+        std::fs::remove_file(output).unwrap();
+    }
+
+    #[test]
+    fn sorted_conversion_matches_golden_file() {
+        use super::{convert_gfa1_to_gfa2_file_with, convert_gfa_to_gfa2_with, ConversionOptions};
+
+        let options = ConversionOptions {
+            sort_canonical: true,
+        };
+        let expected = std::fs::read("./tests/gfa2_files/lil_sorted.gfa2").unwrap();
+
+        let output = std::env::temp_dir().join("gfa2_convert_lil_sorted.gfa2");
+        let report =
+            convert_gfa1_to_gfa2_file_with("./tests/gfa1_files/lil.gfa", &output, &options)
+                .unwrap();
+        assert_eq!(0, report.total_dropped());
+        let converted = std::fs::read(&output).unwrap();
+        std::fs::remove_file(output).unwrap();
+        assert_eq!(expected.as_bstr(), converted.as_bstr());
+
+        // the object converter gives the same output
+        let gfa2 = convert_gfa_to_gfa2_with("./tests/gfa1_files/lil.gfa", &options).unwrap();
+        let mut converted = vec![];
+        gfa2.write_to(&mut converted).unwrap();
+        assert_eq!(expected.as_bstr(), converted.as_bstr());
+    }
+
+    #[test]
+    fn can_convert_gfa1_object() {
+        use super::convert_gfa_to_gfa2;
+        use crate::parser_gfa2::ParseError;
+
+        let gfa2 = convert_gfa_to_gfa2("./tests/gfa1_files/lil.gfa").unwrap();
+        let parser: GFAParser<BString, OptionalFields> = GFAParser::new();
+        let gfa = parser.parse_file("./tests/gfa1_files/lil.gfa").unwrap();
+        assert_eq!(gfa.segments.len(), gfa2.segments.len());
+        assert_eq!(gfa.links.len(), gfa2.edges.len());
+        assert_eq!(gfa.paths.len(), gfa2.groups_o.len());
+        assert!(gfa2.edges.iter().all(Edge::is_blunt));
+
+        let lines = ["S\t11\tACCTT", "S\t12\t*", "L\t11\t+\t12\t-\t4M"];
+        let gfa = parser.parse_lines(lines.iter()).unwrap();
+        assert_eq!(
+            Err(ConversionError::MissingSegmentLength("Segment")),
+            GFA2::try_from(gfa)
+        );
+        match convert_gfa_to_gfa2("./tests/gfa1_files/not_a_file.gfa") {
+            Err(ParseError::IOError(_)) => (),
+            _ => panic!("a missing file can't be converted"),
+        }
+    }
+
+    #[test]
+    fn links_and_containments_become_edges() {
+        use super::{containment_to_edge, link_to_edge};
+        use crate::gfa2::Orientation::*;
+
+        let link: gfa1::Link<BString, ()> = gfa1::Link::new(b"11", Forward, b"12", Backward, b"4M");
+        let edge = link_to_edge(link, 5, 6).unwrap();
+        assert_eq!("E\t*\t11+\t12-\t1\t5$\t2\t6$\t4M", edge.to_string());
+        assert_eq!(
+            Ok(gfa1::Line::Link(gfa1::Link::new(
+                b"11", Forward, b"12", Backward, b"4M"
+            ))),
+            gfa1::Line::try_from(Line::Edge(edge))
+        );
+
+        let containment: gfa1::Containment<BString, ()> = gfa1::Containment {
+            container_name: "11".into(),
+            container_orient: Forward,
+            contained_name: "13".into(),
+            contained_orient: Forward,
+            pos: 1,
+            overlap: "2M".into(),
+            optional: (),
+        };
+        let edge = containment_to_edge(containment.clone(), 5, 2).unwrap();
+        assert_eq!("E\t*\t11+\t13+\t1\t3\t0\t2$\t2M", edge.to_string());
+        assert!(containment_to_edge(containment, 2, 2).is_err());
+    }
+
+    #[test]
+    fn gfa1_line_round_trip() {
+        let segment: gfa1::Line<BString, ()> =
+            gfa1::Line::Segment(gfa1::Segment::new(b"A", b"ACGT"));
+        let converted = Line::try_from(segment.clone()).unwrap();
+        assert_eq!(Ok(segment), gfa1::Line::try_from(converted));
+    }
+}
@@ -1,5 +1,7 @@
 pub mod gfa1;
 pub mod gfa2;
+pub mod gfa2_to_gfa1_conversion;
+pub mod gfa_to_gfa2_conversion;
 pub mod parser_gfa1;
 pub mod parser_gfa2;
 pub mod tag;
@@ -6,7 +6,11 @@ use bstr::{BStr, BString, ByteSlice};
 use lazy_static::lazy_static;
 use regex::bytes::Regex;
 
-use crate::{gfa1::*, tag::*};
+use crate::{
+    gfa1::*,
+    parser_gfa2::{file::open_file, normalize_whitespace},
+    tag::*,
+};
 
 /// Builder struct for GFAParsers
 pub struct GFAParserBuilder {
@@ -16,6 +20,10 @@ pub struct GFAParserBuilder {
     pub containments: bool,
     pub paths: bool,
     pub tolerance: ParserTolerance,
+    pub trim_fields: bool,
+    pub keep_comments: bool,
+    pub keep_unknown: bool,
+    pub whitespace_tolerant: bool,
 }
 
 impl GFAParserBuilder {
@@ -28,6 +36,10 @@ impl GFAParserBuilder {
             containments: false,
             paths: false,
             tolerance: Default::default(),
+            trim_fields: false,
+            keep_comments: false,
+            keep_unknown: false,
+            whitespace_tolerant: false,
         }
     }
 
@@ -40,6 +52,10 @@ impl GFAParserBuilder {
             containments: true,
             paths: true,
             tolerance: Default::default(),
+            trim_fields: false,
+            keep_comments: false,
+            keep_unknown: false,
+            whitespace_tolerant: false,
         }
     }
 
@@ -53,6 +69,36 @@ impl GFAParserBuilder {
         self
     }
 
+    /// Trim the leading and trailing ASCII whitespaces of every field
+    /// before parsing it, useful for hand-edited files
+    pub fn trim_fields(&mut self, trim: bool) -> &mut Self {
+        self.trim_fields = trim;
+        self
+    }
+
+    /// Store the ```#``` comment lines (without the ```#```) in the
+    /// comments of the GFA object, instead of skipping them.\
+    /// The position of the comments is not kept: they are written all
+    /// together after the paths (see GFA::lines_into_iter)
+    pub fn keep_comments(&mut self, keep: bool) -> &mut Self {
+        self.keep_comments = keep;
+        self
+    }
+
+    /// Store the lines of unknown types (a single char other than
+    /// the GFA1 ones) as custom records, instead of skipping them
+    pub fn keep_unknown(&mut self, keep: bool) -> &mut Self {
+        self.keep_unknown = keep;
+        self
+    }
+
+    /// Split the fields on any run of spaces and tabs instead of a
+    /// single tab, keeping the spaces inside the tags
+    pub fn whitespace_tolerant(&mut self, tolerant: bool) -> &mut Self {
+        self.whitespace_tolerant = tolerant;
+        self
+    }
+
     pub fn error_tolerance(&mut self, tol: ParserTolerance) -> &mut Self {
         self.tolerance = tol;
         self
@@ -81,6 +127,10 @@ impl GFAParserBuilder {
             containments: self.containments,
             paths: self.paths,
             tolerance: self.tolerance,
+            trim_fields: self.trim_fields,
+            keep_comments: self.keep_comments,
+            keep_unknown: self.keep_unknown,
+            whitespace_tolerant: self.whitespace_tolerant,
             _optional_fields: std::marker::PhantomData,
             _segment_names: std::marker::PhantomData,
         }
@@ -111,6 +161,10 @@ pub struct GFAParser<N: SegmentId, T: OptFields> {
     containments: bool,
     paths: bool,
     tolerance: ParserTolerance,
+    trim_fields: bool,
+    keep_comments: bool,
+    keep_unknown: bool,
+    whitespace_tolerant: bool,
     _optional_fields: std::marker::PhantomData<T>,
     _segment_names: std::marker::PhantomData<N>,
 }
@@ -130,13 +184,49 @@ impl<N: SegmentId, T: OptFields> GFAParser<N, T> {
     }
 
     pub fn parse_gfa_line(&self, bytes: &[u8]) -> GFAResult<Line<N, T>> {
+        if self.keep_comments {
+            if let Some(comment) = bytes.strip_prefix(b"#") {
+                let comment = comment.trim_end_with(|c| c == '\n' || c == '\r');
+                return Ok(Line::Comment(comment.into()));
+            }
+        }
         let line: &BStr = bytes.trim().as_ref();
 
-        let mut fields = line.split_str(b"\t");
+        let normalized;
+        let line: &[u8] = if self.whitespace_tolerant {
+            normalized = normalize_whitespace(line);
+            &normalized
+        } else {
+            line
+        };
+
+        let fields: Vec<&[u8]> = line
+            .split_str(b"\t")
+            .map(|field| {
+                if self.trim_fields {
+                    field.trim()
+                } else {
+                    field
+                }
+            })
+            .collect();
+        let mut fields = fields.into_iter();
         let hdr = fields.next().ok_or(ParseError::EmptyLine)?;
 
         let invalid_line = |e: ParseFieldError| ParseError::invalid_line(e, bytes);
 
+        // the known line types skipped by the builder are not custom records
+        match hdr {
+            [t] if self.keep_unknown && !b"HSLCP#".contains(t) => {
+                let record = CustomRecord {
+                    type_field: *t,
+                    fields: fields.map(BString::from).collect(),
+                };
+                return Ok(Line::CustomRecord(record));
+            }
+            _ => (),
+        }
+
         let line = match hdr {
             b"H" => Header::parse_line(fields).map(Header::wrap),
             b"S" if self.segments => Segment::parse_line(fields).map(Segment::wrap),
@@ -149,6 +239,21 @@ impl<N: SegmentId, T: OptFields> GFAParser<N, T> {
         Ok(line)
     }
 
+    /// Parses a whole GFA file already in memory (e.g. a memory mapped
+    /// file), splitting its lines without copying them
+    pub fn parse_bytes(&self, bytes: &[u8]) -> GFAResult<GFA<N, T>> {
+        self.parse_lines(bytes.lines())
+    }
+
+    /// Like parse_file, but the file is mapped in memory and parsed
+    /// with parse_bytes, without copying its lines.\
+    /// The compressed files are rejected, and the file must not be
+    /// modified while it's parsed
+    #[cfg(feature = "memmap2")]
+    pub fn parse_file_mmap<P: AsRef<std::path::Path>>(&self, path: P) -> GFAResult<GFA<N, T>> {
+        self.parse_bytes(&crate::parser_gfa2::file::map_file(path)?)
+    }
+
     pub fn parse_lines<I>(&self, lines: I) -> GFAResult<GFA<N, T>>
     where
         I: Iterator,
@@ -156,11 +261,11 @@ impl<N: SegmentId, T: OptFields> GFAParser<N, T> {
     {
         let mut gfa = GFA::new();
 
-        for line in lines {
+        for (line_no, line) in lines.enumerate() {
             match self.parse_gfa_line(line.as_ref()) {
                 Ok(parsed) => gfa.insert_line(parsed),
                 Err(err) if err.can_safely_continue(&self.tolerance) => (),
-                Err(err) => return Err(err),
+                Err(err) => return Err(err.at_line(line_no + 1)),
             };
         }
 
@@ -194,19 +299,25 @@ impl<N: SegmentId, T: OptFields> GFAParser<N, T> {
     ///
     /// ```
     pub fn parse_file<P: AsRef<std::path::Path>>(&self, path: P) -> Result<GFA<N, T>, ParseError> {
-        use {
-            bstr::io::BufReadExt,
-            std::{fs::File, io::BufReader},
-        };
+        self.parse_reader(open_file(path)?)
+    }
+
+    /// Like parse_file, but the lines are read from ```reader```, without
+    /// checking any extension, so a GFA file can be parsed from the
+    /// standard input or a pipe
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// let parser: GFAParser<BString, OptionalFields> = GFAParser::new();
+    /// let stdin = std::io::stdin();
+    /// let gfa = parser.parse_reader(stdin.lock()).unwrap();
+    /// ```
+    pub fn parse_reader<R: std::io::BufRead>(&self, reader: R) -> Result<GFA<N, T>, ParseError> {
+        use bstr::io::BufReadExt;
         // use indicatif::{ProgressBar, ProgressIterator, ProgressStyle};
-        use std::ffi::OsStr;
 
-        let file = File::open(path.as_ref())?;
-        match path.as_ref().extension().and_then(OsStr::to_str).unwrap() {
-            "gfa2" | "gfa" => (),
-            _ => return Err(ParseError::ExtensionError()),
-        }
-        let lines = BufReader::new(file).byte_lines();
+        let lines = reader.byte_lines();
         let mut gfa = GFA::new();
 
         /*
@@ -217,14 +328,14 @@ impl<N: SegmentId, T: OptFields> GFAParser<N, T> {
         ));
         */
 
-        for line in lines
+        for (line_no, line) in lines.enumerate()
         /*.progress_with(pb)*/
         {
-            let line = line?;
+            let line = line.map_err(|err| ParseError::from(err).at_line(line_no + 1))?;
             match self.parse_gfa_line(line.as_ref()) {
                 Ok(parsed) => gfa.insert_line(parsed),
                 Err(err) if err.can_safely_continue(&self.tolerance) => (),
-                Err(err) => return Err(err),
+                Err(err) => return Err(err.at_line(line_no + 1)),
             };
         }
 
@@ -232,6 +343,48 @@ impl<N: SegmentId, T: OptFields> GFAParser<N, T> {
     }
 }
 
+#[cfg(feature = "rayon")]
+impl<N, T> GFAParser<N, T>
+where
+    N: SegmentId + Send + Sync,
+    T: OptFields + Send + Sync,
+{
+    /// Like parse_file, but the lines are parsed on ```threads``` threads
+    /// (at least one), each one building the part of the GFA object of
+    /// a contiguous chunk of lines, that are then merged keeping the
+    /// order of the file.\
+    /// The whole file is read in memory before parsing it
+    pub fn parse_file_parallel<P: AsRef<std::path::Path>>(
+        &self,
+        path: P,
+        threads: usize,
+    ) -> Result<GFA<N, T>, ParseError> {
+        use crate::parser_gfa2::parallel::{parse_chunks, read_file_lines};
+
+        let lines = read_file_lines(path)?;
+        let chunks = parse_chunks(&lines, threads, |first_line, chunk| {
+            let mut gfa = GFA::new();
+            for (line_no, line) in chunk.iter().enumerate() {
+                match self.parse_gfa_line(line.as_ref()) {
+                    Ok(parsed) => gfa.insert_line(parsed),
+                    Err(err) if err.can_safely_continue(&self.tolerance) => (),
+                    Err(err) => return Err(err.at_line(first_line + line_no)),
+                }
+            }
+            Ok(gfa)
+        })?;
+
+        let mut gfa = GFA::new();
+        for mut chunk in chunks {
+            // merge keeps only the first headers, but every chunk can
+            // have some headers of the file
+            gfa.headers.append(&mut chunk.headers);
+            gfa.merge(chunk);
+        }
+        Ok(gfa)
+    }
+}
+
 pub struct GFAParserLineIter<I, N, T>
 where
     N: SegmentId,
@@ -305,18 +458,25 @@ impl<T: OptFields> Header<T> {
     }
 
     #[inline]
-    fn parse_line<I>(mut input: I) -> GFAFieldResult<Self>
+    fn parse_line<I>(input: I) -> GFAFieldResult<Self>
     where
         I: Iterator,
         I::Item: AsRef<[u8]>,
     {
-        let next = next_field(&mut input)?;
-        let version = OptField::parse(next.as_ref());
-        let version = if let Some(OptFieldVal::Z(version)) = version.map(|v| v.value) {
-            Some(version)
-        } else {
-            None
-        };
+        let mut input = input.peekable();
+        // the first field is the version only if it's a VN tag,
+        // otherwise it's an optional tag of an header without version
+        let version = input
+            .peek()
+            .and_then(|next| OptField::parse(next.as_ref()))
+            .filter(|opt| &opt.tag == b"VN")
+            .and_then(|opt| match opt.value {
+                OptFieldVal::Z(version) => Some(version),
+                _ => None,
+            });
+        if version.is_some() {
+            input.next();
+        }
 
         let optional = T::parse(input);
 
@@ -505,6 +665,92 @@ impl<N: SegmentId, T: OptFields> Path<N, T> {
 mod tests {
     use super::*;
 
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn can_parse_file_parallel() {
+        let parser: GFAParser<BString, OptionalFields> = GFAParser::new();
+        let gfa = parser.parse_file("./tests/gfa1_files/lil.gfa").unwrap();
+        let parallel = parser
+            .parse_file_parallel("./tests/gfa1_files/lil.gfa", 4)
+            .unwrap();
+        assert_eq!(gfa, parallel);
+    }
+
+    #[test]
+    fn can_parse_bytes() {
+        let parser: GFAParser<BString, OptionalFields> = GFAParser::new();
+        let gfa = parser.parse_file("./tests/gfa1_files/lil.gfa").unwrap();
+        let bytes = std::fs::read("./tests/gfa1_files/lil.gfa").unwrap();
+        assert_eq!(gfa, parser.parse_bytes(&bytes).unwrap());
+    }
+
+    #[cfg(feature = "memmap2")]
+    #[test]
+    fn can_parse_file_mmap() {
+        let parser: GFAParser<BString, OptionalFields> = GFAParser::new();
+        let gfa = parser.parse_file("./tests/gfa1_files/lil.gfa").unwrap();
+        assert_eq!(
+            gfa,
+            parser
+                .parse_file_mmap("./tests/gfa1_files/lil.gfa")
+                .unwrap()
+        );
+        assert!(parser
+            .parse_file_mmap("./tests/gfa2_files/extension_error.txt")
+            .is_err());
+    }
+
+    #[test]
+    fn can_parse_reader() {
+        let parser: GFAParser<BString, OptionalFields> = GFAParser::new();
+        let gfa = parser.parse_file("./tests/gfa1_files/lil.gfa").unwrap();
+        let bytes = std::fs::read("./tests/gfa1_files/lil.gfa").unwrap();
+        assert_eq!(gfa, parser.parse_reader(bytes.as_slice()).unwrap());
+    }
+
+    #[test]
+    fn can_parse_whitespace_tolerant() {
+        let lines = [
+            "H VN:Z:1.0",
+            "S  11 ACCTT\txx:Z:a b",
+            "S 12 TCAAGG",
+            "L 11 + 12  - 4M",
+        ];
+        let mut builder = GFAParserBuilder::all();
+        builder.whitespace_tolerant(true);
+        let parser: GFAParser<BString, ()> = builder.build();
+        let gfa = parser.parse_lines(lines.iter()).unwrap();
+        assert_eq!(2, gfa.segments.len());
+        assert_eq!("ACCTT", gfa.segments[0].sequence);
+        assert_eq!(1, gfa.links.len());
+    }
+
+    #[test]
+    fn can_keep_comments() {
+        let lines = ["H\tVN:Z:1.0", "# assembled with tool", "S\t1\tACGT"];
+        let mut builder = GFAParserBuilder::all();
+        builder.keep_comments(true);
+        let parser: GFAParser<BString, ()> = builder.build();
+        let gfa = parser.parse_lines(lines.iter()).unwrap();
+        assert_eq!(vec![BString::from(" assembled with tool")], gfa.comments);
+        // the comments are written after the other lines, wherever they were
+        assert_eq!(
+            "H\tVN:Z:1.0\nS\t1\tACGT\n# assembled with tool\n",
+            gfa.to_string()
+        );
+    }
+
+    #[test]
+    fn can_keep_unknown_lines() {
+        let lines = ["S\t1\tACGT", "W\tsample\t0\tchr1\t0\t4\t>1"];
+        let mut builder = GFAParserBuilder::all();
+        builder.keep_unknown(true);
+        let parser: GFAParser<BString, ()> = builder.build();
+        let gfa = parser.parse_lines(lines.iter()).unwrap();
+        assert_eq!(1, gfa.custom_records.len());
+        assert_eq!(lines.join("\n") + "\n", gfa.to_string());
+    }
+
     #[test]
     fn can_parse_header() {
         let hdr = b"VN:Z:1.0";
@@ -523,6 +769,20 @@ mod tests {
         }
     }
 
+    #[test]
+    fn header_without_version_round_trip() {
+        let hdr_: Header<OptionalFields> = Header {
+            version: None,
+            optional: vec![OptField::new(b"PG", OptFieldVal::Z("PG:Z:bandage".into()))],
+        };
+        let hdr = hdr_.to_string();
+        assert_eq!("H\tPG:Z:bandage", hdr);
+
+        let result: Header<OptionalFields> =
+            Header::parse_line(hdr.split_terminator('\t').skip(1)).unwrap();
+        assert_eq!(hdr_, result);
+    }
+
     #[test]
     fn can_parse_link() {
         let link = "11	+	12	-	4M";
@@ -1,5 +1,16 @@
+pub mod any;
 pub mod error;
-pub use self::error::{GFA2FieldResult, GFA2Result, ParseError, ParseFieldError};
+pub(crate) mod file;
+#[cfg(feature = "rayon")]
+pub mod parallel;
+pub mod profile;
+pub mod validation;
+pub use self::any::{parse_any, GFAGraph};
+pub use self::error::{
+    ConversionError, GFA2FieldResult, GFA2Result, ParseError, ParseFieldError, ParseWarning,
+};
+pub use self::profile::{analyze_file, count_file_lines, detect_version, FileProfile, GFAVersion};
+pub use self::validation::{OrderError, Utf8Error, ValidationError, ValidationIssue};
 
 use crate::{gfa2::*, tag::*};
 use bstr::{BStr, BString, ByteSlice};
@@ -18,6 +29,12 @@ pub struct GFA2ParserBuilder {
     pub groups_o: bool,
     pub groups_u: bool,
     pub tolerance: ParserTolerance,
+    pub drop_sequences: bool,
+    pub trim_fields: bool,
+    pub pack_sequences: bool,
+    pub keep_comments: bool,
+    pub keep_unknown: bool,
+    pub whitespace_tolerant: bool,
 }
 
 impl GFA2ParserBuilder {
@@ -32,6 +49,12 @@ impl GFA2ParserBuilder {
             groups_o: false,
             groups_u: false,
             tolerance: Default::default(),
+            drop_sequences: false,
+            trim_fields: false,
+            pack_sequences: false,
+            keep_comments: false,
+            keep_unknown: false,
+            whitespace_tolerant: false,
         }
     }
 
@@ -46,9 +69,51 @@ impl GFA2ParserBuilder {
             groups_o: true,
             groups_u: true,
             tolerance: Default::default(),
+            drop_sequences: false,
+            trim_fields: false,
+            pack_sequences: false,
+            keep_comments: false,
+            keep_unknown: false,
+            whitespace_tolerant: false,
         }
     }
 
+    /// Parse the segment (S) lines or skip them
+    pub fn segments(mut self, include: bool) -> Self {
+        self.segments = include;
+        self
+    }
+
+    /// Parse the fragment (F) lines or skip them
+    pub fn fragments(mut self, include: bool) -> Self {
+        self.fragments = include;
+        self
+    }
+
+    /// Parse the edge (E) lines or skip them
+    pub fn edges(mut self, include: bool) -> Self {
+        self.edges = include;
+        self
+    }
+
+    /// Parse the gap (G) lines or skip them
+    pub fn gaps(mut self, include: bool) -> Self {
+        self.gaps = include;
+        self
+    }
+
+    /// Parse the O-group (O) lines or skip them
+    pub fn groups_o(mut self, include: bool) -> Self {
+        self.groups_o = include;
+        self
+    }
+
+    /// Parse the U-group (U) lines or skip them
+    pub fn groups_u(mut self, include: bool) -> Self {
+        self.groups_u = include;
+        self
+    }
+
     pub fn ignore_errors(mut self) -> Self {
         self.tolerance = ParserTolerance::IgnoreAll;
         self
@@ -64,16 +129,80 @@ impl GFA2ParserBuilder {
         self
     }
 
+    /// Replace the sequence of every segment with ```*```, keeping
+    /// only its length, useful if only the graph structure is needed
+    pub fn drop_sequences(mut self, drop: bool) -> Self {
+        self.drop_sequences = drop;
+        self
+    }
+
+    /// Trim the leading and trailing ASCII whitespaces of every field
+    /// before parsing it, useful for hand-edited files
+    pub fn trim_fields(mut self, trim: bool) -> Self {
+        self.trim_fields = trim;
+        self
+    }
+
+    /// Store the sequences made only of ```ACGT``` bases on 2 bits per
+    /// base (see Segment::pack_sequence), keeping the other ones as
+    /// they are, to reduce the memory used by large files
+    pub fn pack_sequences(mut self, pack: bool) -> Self {
+        self.pack_sequences = pack;
+        self
+    }
+
+    /// Store the ```#``` comment lines (without the ```#```) in the
+    /// comments of the GFA2 object, instead of skipping them, so they
+    /// are written back with the graph.\
+    /// The position of the comments is not kept: they are written all
+    /// together after the groups (see GFA2::lines_into_iter)
+    pub fn keep_comments(mut self, keep: bool) -> Self {
+        self.keep_comments = keep;
+        self
+    }
+
+    /// Store the lines of unknown types (a single char other than
+    /// the GFA2 ones) as custom records, instead of skipping them or
+    /// failing, so they are written back with the graph
+    pub fn keep_unknown(mut self, keep: bool) -> Self {
+        self.keep_unknown = keep;
+        self
+    }
+
+    /// Split the fields on any run of spaces and tabs instead of a
+    /// single tab, useful for hand-edited files.\
+    /// The spaces inside the tags (e.g. in a ```Z``` or ```J``` value)
+    /// are kept, and the members of a group are separated by a single
+    /// space
+    pub fn whitespace_tolerant(mut self, tolerant: bool) -> Self {
+        self.whitespace_tolerant = tolerant;
+        self
+    }
+
     pub fn build<N: SegmentId, T: OptFields>(self) -> GFA2Parser<N, T> {
+        let types = [
+            (self.headers, b'H'),
+            (self.segments, b'S'),
+            (self.fragments, b'F'),
+            (self.edges, b'E'),
+            (self.gaps, b'G'),
+            (self.groups_o, b'O'),
+            (self.groups_u, b'U'),
+        ];
+        let types: Vec<u8> = types
+            .iter()
+            .filter(|(on, _)| *on)
+            .map(|(_, t)| *t)
+            .collect();
         GFA2Parser {
-            headers: self.headers,
-            segments: self.segments,
-            fragments: self.fragments,
-            edges: self.edges,
-            gaps: self.gaps,
-            groups_o: self.groups_o,
-            groups_u: self.groups_u,
+            line_types: line_types_set(&types),
             tolerance: self.tolerance,
+            drop_sequences: self.drop_sequences,
+            trim_fields: self.trim_fields,
+            pack_sequences: self.pack_sequences,
+            keep_comments: self.keep_comments,
+            keep_unknown: self.keep_unknown,
+            whitespace_tolerant: self.whitespace_tolerant,
             _optional_fields: std::marker::PhantomData,
             _segment_names: std::marker::PhantomData,
         }
@@ -88,6 +217,66 @@ impl GFA2ParserBuilder {
     }
 }
 
+/// returns the set of the line types given by their first byte, one
+/// bit for each ASCII char
+fn line_types_set(types: &[u8]) -> u128 {
+    types
+        .iter()
+        .filter(|t| t.is_ascii())
+        .fold(0, |set, &t| set | 1 << t)
+}
+
+/// checks if a field starts like an optional tag, ```<tag>:<type>:```
+fn looks_like_tag(field: &[u8]) -> bool {
+    matches!(
+        field,
+        [a, b, b':', _, b':', ..] if a.is_ascii_alphabetic() && b.is_ascii_alphanumeric()
+    )
+}
+
+/// rewrites a line separating its fields with a single tab, where
+/// every run of spaces and tabs is a separator except the ones inside
+/// the tags (kept, with the tabs replaced by spaces) and between the
+/// members of an O-Group or U-Group (replaced by a single space)
+pub(crate) fn normalize_whitespace(line: &[u8]) -> Vec<u8> {
+    let is_whitespace = |c: &u8| *c == b' ' || *c == b'\t';
+    let is_group = matches!(line, [b'O', c, ..] | [b'U', c, ..] if is_whitespace(c));
+
+    let mut normalized = Vec::with_capacity(line.len());
+    let mut fields = 0;
+    let mut in_tag = false;
+    let mut last_end = 0;
+    let mut beg = 0;
+    while beg < line.len() {
+        if is_whitespace(&line[beg]) {
+            beg += 1;
+            continue;
+        }
+        let end = line[beg..]
+            .iter()
+            .position(is_whitespace)
+            .map_or(line.len(), |len| beg + len);
+        let token = &line[beg..end];
+        let is_tag = looks_like_tag(token);
+        if in_tag && !is_tag {
+            let spaces = line[last_end..beg].iter().map(|_| b' ');
+            normalized.extend(spaces);
+        } else if is_group && fields >= 3 && !is_tag {
+            normalized.push(b' ');
+        } else {
+            if fields > 0 {
+                normalized.push(b'\t');
+            }
+            fields += 1;
+            in_tag = is_tag;
+        }
+        normalized.extend_from_slice(token);
+        last_end = end;
+        beg = end;
+    }
+    normalized
+}
+
 /// return a GFA2Parser object
 ///
 /// # Examples
@@ -104,14 +293,15 @@ impl GFA2ParserBuilder {
 /// ```
 #[derive(Clone)]
 pub struct GFA2Parser<N: SegmentId, T: OptFields> {
-    headers: bool,
-    segments: bool,
-    fragments: bool,
-    edges: bool,
-    gaps: bool,
-    groups_o: bool,
-    groups_u: bool,
+    // the line types to parse, as a set of their first byte
+    line_types: u128,
     tolerance: ParserTolerance,
+    drop_sequences: bool,
+    trim_fields: bool,
+    pack_sequences: bool,
+    keep_comments: bool,
+    keep_unknown: bool,
+    whitespace_tolerant: bool,
     _optional_fields: std::marker::PhantomData<T>,
     _segment_names: std::marker::PhantomData<N>,
 }
@@ -130,26 +320,144 @@ impl<N: SegmentId, T: OptFields> GFA2Parser<N, T> {
         Default::default()
     }
 
+    /// Parse only the line types given by their first char, replacing
+    /// the ones chosen with the builder
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// // parse only the segments and the edges
+    /// let parser: GFA2Parser<BString, ()> = GFA2Parser::new().only(b"SE");
+    /// ```
+    pub fn only(mut self, types: &[u8]) -> Self {
+        self.line_types = line_types_set(types);
+        self
+    }
+
+    /// checks if the lines starting with ```hdr``` have to be parsed
+    fn allows(&self, hdr: &[u8]) -> bool {
+        match hdr {
+            [t] if t.is_ascii() => self.line_types & (1 << t) != 0,
+            _ => false,
+        }
+    }
+
+    /// checks if a line is skipped on purpose, as a comment or a known
+    /// line type excluded by the builder, rather than being of an
+    /// unknown type
+    fn filters_out(&self, bytes: &[u8]) -> bool {
+        let line = bytes.trim_start();
+        match line.split_str(b"\t").next().map(|hdr| hdr.trim()) {
+            _ if line.starts_with(b"#") => true,
+            Some(hdr @ [t]) => b"HSFEGOU".contains(t) && !self.allows(hdr),
+            _ => false,
+        }
+    }
+
     fn parse_gfa_line(&self, bytes: &[u8]) -> GFA2Result<Line<N, T>> {
+        self.parse_gfa_line_with_warnings(bytes, 0)
+            .map(|(line, _)| line)
+    }
+
+    /// parses a line, returning also the warnings about what has been
+    /// skipped or fixed (```line_no``` is the number of the line)
+    fn parse_gfa_line_with_warnings(
+        &self,
+        bytes: &[u8],
+        line_no: usize,
+    ) -> GFA2Result<(Line<N, T>, Vec<ParseWarning>)> {
+        if self.keep_comments {
+            if let Some(comment) = bytes.strip_prefix(b"#") {
+                let comment = comment.trim_end_with(|c| c == '\n' || c == '\r');
+                return Ok((Line::Comment(comment.into()), vec![]));
+            }
+        }
         let line: &BStr = bytes.trim().as_ref();
 
-        let mut fields = line.split_str(b"\t");
+        let normalized;
+        let line: &[u8] = if self.whitespace_tolerant {
+            normalized = normalize_whitespace(line);
+            &normalized
+        } else {
+            line
+        };
+
+        let fields: Vec<&[u8]> = line
+            .split_str(b"\t")
+            .map(|field| {
+                if self.trim_fields {
+                    field.trim()
+                } else {
+                    field
+                }
+            })
+            .collect();
+        let mut fields = fields.into_iter();
         let hdr = fields.next().ok_or(ParseError::EmptyLine)?;
 
         let invalid_line = |e: ParseFieldError| ParseError::invalid_line(e, bytes);
+        let mut warnings = vec![];
+
+        // the known line types skipped by the builder are not custom records
+        match hdr {
+            [t] if self.keep_unknown && !b"HSFEGOU#".contains(t) => {
+                let record = CustomRecord {
+                    type_field: *t,
+                    fields: fields.map(BString::from).collect(),
+                };
+                return Ok((Line::CustomRecord(record), warnings));
+            }
+            _ => (),
+        }
 
-        let line = match hdr {
-            b"H" if self.headers => Header::parse_line(fields).map(Header::wrap),
-            b"S" if self.segments => Segment::parse_line(fields).map(Segment::wrap),
-            b"F" if self.fragments => Fragment::parse_line(fields).map(Fragment::wrap),
-            b"E" if self.edges => Edge::parse_line(fields).map(Edge::wrap),
-            b"G" if self.gaps => Gap::parse_line(fields).map(Gap::wrap),
-            b"O" if self.groups_o => GroupO::parse_line(fields).map(GroupO::wrap),
-            b"U" if self.groups_u => GroupU::parse_line(fields).map(GroupU::wrap),
+        // every line parser also returns the optional fields it has skipped
+        let (parsed, invalid) = match hdr {
+            _ if !self.allows(hdr) => return Err(ParseError::UnknownLineType),
+            b"H" => Header::parse_line(fields).map(|(h, invalid)| (h.wrap(), invalid)),
+            b"S" => Segment::parse_line(fields).map(|(mut s, invalid)| {
+                if self.drop_sequences {
+                    s.sequence = "*".into();
+                } else if self.pack_sequences {
+                    s.pack_sequence();
+                }
+                (s.wrap(), invalid)
+            }),
+            b"F" => Fragment::parse_line(fields).map(|(f, invalid)| (f.wrap(), invalid)),
+            b"E" => {
+                if let ParserTolerance::IgnoreAll = self.tolerance {
+                    // some tools omit the orientation of forward segments
+                    let mut fields: Vec<BString> = fields.map(BString::from).collect();
+                    for sid in fields.iter_mut().skip(1).take(2) {
+                        if !sid.ends_with(b"+") && !sid.ends_with(b"-") {
+                            warnings
+                                .push(ParseWarning::MissingOrientation(line_no, sid.to_string()));
+                            sid.push(b'+');
+                        }
+                    }
+                    Edge::parse_line(fields.iter())
+                } else {
+                    Edge::parse_line(fields)
+                }
+                .map(|(e, invalid)| (e.wrap(), invalid))
+            }
+            b"G" => Gap::parse_line(fields).map(|(g, invalid)| (g.wrap(), invalid)),
+            b"O" => GroupO::parse_line(fields).map(|(o, invalid)| (o.wrap(), invalid)),
+            b"U" => GroupU::parse_line(fields).map(|(u, invalid)| (u.wrap(), invalid)),
             _ => return Err(ParseError::UnknownLineType),
         }
         .map_err(invalid_line)?;
-        Ok(line)
+
+        if !invalid.is_empty() {
+            if let ParserTolerance::Pedantic = self.tolerance {
+                return Err(invalid_line(ParseFieldError::InvalidField("tag")));
+            }
+        }
+        warnings.extend(
+            invalid
+                .iter()
+                .map(|field| ParseWarning::InvalidOptField(line_no, field.to_string())),
+        );
+        Ok((parsed, warnings))
     }
 
     pub fn parse_lines<I>(&self, lines: I) -> GFA2Result<GFA2<N, T>>
@@ -157,17 +465,69 @@ impl<N: SegmentId, T: OptFields> GFA2Parser<N, T> {
         I: Iterator,
         I::Item: AsRef<[u8]>,
     {
-        let mut gfa2 = GFA2::new();
+        self.parse_lines_with_warnings(lines).map(|(gfa2, _)| gfa2)
+    }
+
+    /// Parses a whole GFA2 file already in memory, splitting its lines
+    /// (ended by ```\n``` or ```\r\n```) without copying them.\
+    /// The bytes can come from a memory mapped file, to parse the same
+    /// file many times without reading it again
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// let file = File::open("./tests/gfa2_files/big.gfa")?;
+    /// let mmap = unsafe { memmap2::Mmap::map(&file)? };
+    ///
+    /// let parser: GFA2Parser<BString, OptionalFields> = GFA2Parser::new();
+    /// let gfa2 = parser.parse_bytes(&mmap).unwrap();
+    /// ```
+    pub fn parse_bytes(&self, bytes: &[u8]) -> GFA2Result<GFA2<N, T>> {
+        self.parse_lines(bytes.lines())
+    }
+
+    /// Like parse_file, but the file is mapped in memory and parsed
+    /// with parse_bytes, without copying its lines.\
+    /// The compressed files are rejected, and the file must not be
+    /// modified while it's parsed
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// let parser: GFA2Parser<BString, OptionalFields> = GFA2Parser::new();
+    /// let gfa2 = parser.parse_file_mmap("./tests/gfa2_files/big.gfa").unwrap();
+    /// ```
+    #[cfg(feature = "memmap2")]
+    pub fn parse_file_mmap<P: AsRef<std::path::Path>>(&self, path: P) -> GFA2Result<GFA2<N, T>> {
+        self.parse_bytes(&file::map_file(path)?)
+    }
 
-        for line in lines {
-            match self.parse_gfa_line(line.as_ref()) {
-                Ok(parsed) => gfa2.insert_line(parsed),
+    /// Like parse_lines, but also returns the warnings about the
+    /// malformed optional fields that have been skipped and the
+    /// missing orientations that have been implied
+    pub fn parse_lines_with_warnings<I>(
+        &self,
+        lines: I,
+    ) -> GFA2Result<(GFA2<N, T>, Vec<ParseWarning>)>
+    where
+        I: Iterator,
+        I::Item: AsRef<[u8]>,
+    {
+        let mut gfa2 = GFA2::new();
+        let mut warnings = vec![];
+
+        for (line_no, line) in lines.enumerate() {
+            match self.parse_gfa_line_with_warnings(line.as_ref(), line_no + 1) {
+                Ok((parsed, line_warnings)) => {
+                    gfa2.insert_line(parsed);
+                    warnings.extend(line_warnings);
+                }
                 Err(err) if err.can_safely_continue(&self.tolerance) => (),
-                Err(err) => return Err(err),
+                Err(err) => return Err(err.at_line(line_no + 1)),
             };
         }
 
-        Ok(gfa2)
+        Ok((gfa2, warnings))
     }
 
     /// Function that return a ```Result<GFA2<N, T>, ParseError>``` object\
@@ -193,20 +553,80 @@ impl<N: SegmentId, T: OptFields> GFA2Parser<N, T> {
     ///
     /// ```
     pub fn parse_file<P: AsRef<std::path::Path>>(&self, path: P) -> Result<GFA2<N, T>, ParseError> {
-        use {
-            bstr::io::BufReadExt,
-            std::{fs::File, io::BufReader},
-        };
+        self.parse_file_with_warnings(path).map(|(gfa2, _)| gfa2)
+    }
+
+    /// Like parse_file, but also returns the warnings about the
+    /// malformed optional fields that have been skipped and the
+    /// missing orientations that have been implied
+    pub fn parse_file_with_warnings<P: AsRef<std::path::Path>>(
+        &self,
+        path: P,
+    ) -> Result<(GFA2<N, T>, Vec<ParseWarning>), ParseError> {
+        self.parse_reader_mapped(file::open_file(path)?, Some)
+    }
+
+    /// Like parse_file, but the lines are read from ```reader```, without
+    /// checking any extension, so a GFA2 file can be parsed from the
+    /// standard input or a pipe
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// let parser: GFA2Parser<BString, OptionalFields> = GFA2Parser::new();
+    /// let stdin = std::io::stdin();
+    /// let gfa2 = parser.parse_reader(stdin.lock()).unwrap();
+    /// ```
+    pub fn parse_reader<R: std::io::BufRead>(&self, reader: R) -> Result<GFA2<N, T>, ParseError> {
+        self.parse_reader_mapped(reader, Some).map(|(gfa2, _)| gfa2)
+    }
+
+    /// Like parse_file, but every line is passed to ```f``` as soon as
+    /// it's parsed: the line it returns is inserted in the GFA2 object in
+    /// place of the parsed one, or the line is dropped if it returns None.\
+    /// This filters and transforms the lines without keeping a second
+    /// copy of the whole graph in memory
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// let parser: GFA2Parser<BString, OptionalFields> = GFA2Parser::new();
+    /// // keep only the segments, without their optional fields
+    /// let gfa2 = parser
+    ///     .parse_file_with("./tests/gfa2_files/big.gfa", |line| match line {
+    ///         Line::Segment(mut segment) => {
+    ///             segment.tag = vec![];
+    ///             Some(Line::Segment(segment))
+    ///         }
+    ///         _ => None,
+    ///     })
+    ///     .unwrap();
+    /// ```
+    pub fn parse_file_with<P, F>(&self, path: P, f: F) -> Result<GFA2<N, T>, ParseError>
+    where
+        P: AsRef<std::path::Path>,
+        F: FnMut(Line<N, T>) -> Option<Line<N, T>>,
+    {
+        self.parse_reader_mapped(file::open_file(path)?, f)
+            .map(|(gfa2, _)| gfa2)
+    }
+
+    /// parses the lines of a reader inserting the lines returned by
+    /// ```f``` for each parsed line
+    fn parse_reader_mapped<R, F>(
+        &self,
+        reader: R,
+        mut f: F,
+    ) -> Result<(GFA2<N, T>, Vec<ParseWarning>), ParseError>
+    where
+        R: std::io::BufRead,
+        F: FnMut(Line<N, T>) -> Option<Line<N, T>>,
+    {
+        use bstr::io::BufReadExt;
         // use indicatif::{ProgressBar, ProgressIterator, ProgressStyle};
-        use std::ffi::OsStr;
 
-        let file = File::open(path.as_ref())?;
         //let len = std::fs::metadata(path)?.len();
-        match path.as_ref().extension().and_then(OsStr::to_str).unwrap() {
-            "gfa2" | "gfa" => (),
-            _ => return Err(ParseError::ExtensionError()),
-        }
-        let lines = BufReader::new(file).byte_lines();
+        let lines = reader.byte_lines();
         let mut gfa2 = GFA2::new();
 
         /*
@@ -217,21 +637,106 @@ impl<N: SegmentId, T: OptFields> GFA2Parser<N, T> {
         ));
         */
 
-        for line in lines
+        let mut warnings = vec![];
+
+        for (line_no, line) in lines.enumerate()
         /*.progress_with(pb)*/
         {
-            let line = line?;
-            match self.parse_gfa_line(line.as_ref()) {
-                Ok(parsed) => gfa2.insert_line(parsed),
+            let line = line.map_err(|err| ParseError::from(err).at_line(line_no + 1))?;
+            match self.parse_gfa_line_with_warnings(line.as_ref(), line_no + 1) {
+                Ok((parsed, line_warnings)) => {
+                    if let Some(line) = f(parsed) {
+                        gfa2.insert_line(line);
+                    }
+                    warnings.extend(line_warnings);
+                }
                 Err(err) if err.can_safely_continue(&self.tolerance) => (),
-                Err(err) => return Err(err),
+                Err(err) => return Err(err.at_line(line_no + 1)),
+            };
+        }
+
+        Ok((gfa2, warnings))
+    }
+
+    /// Parses the whole file without ever stopping, returning the
+    /// lines that have been parsed and every error encountered
+    /// (whatever the tolerance of the parser), together with the
+    /// number of its line.\
+    /// The empty lines, the comments and the line types excluded by the
+    /// builder are skipped without errors, while the unknown line types
+    /// are reported.\
+    /// An error that prevents reading the file (e.g. a wrong extension
+    /// or an IO error) is reported with the line 0 or the line that
+    /// could not be read, and ends the parsing.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// let parser: GFA2Parser<BString, ()> = GFA2Parser::new();
+    /// let (gfa2, errors) = parser.parse_file_collect_errors("./tests/gfa2_files/invalid.gfa");
+    ///
+    /// for (line, err) in errors {
+    ///     println!("line {}: {}", line, err);
+    /// }
+    /// ```
+    pub fn parse_file_collect_errors<P: AsRef<std::path::Path>>(
+        &self,
+        path: P,
+    ) -> (GFA2<N, T>, Vec<(usize, ParseError)>) {
+        use bstr::io::BufReadExt;
+
+        let mut gfa2 = GFA2::new();
+        let mut errors = vec![];
+
+        let reader = match file::open_file(path) {
+            Ok(reader) => reader,
+            Err(err) => {
+                errors.push((0, err));
+                return (gfa2, errors);
+            }
+        };
+
+        for (line_no, line) in reader.byte_lines().enumerate() {
+            let line = match line {
+                Ok(line) => line,
+                Err(err) => {
+                    errors.push((line_no + 1, err.into()));
+                    break;
+                }
             };
+            match self.parse_gfa_line_with_warnings(line.as_ref(), line_no + 1) {
+                Ok((parsed, _)) => gfa2.insert_line(parsed),
+                Err(ParseError::EmptyLine) => (),
+                Err(ParseError::UnknownLineType) if self.filters_out(&line) => (),
+                Err(err) => errors.push((line_no + 1, err)),
+            }
         }
 
-        Ok(gfa2)
+        (gfa2, errors)
     }
 }
 
+/// Iterator that lazily parses the GFA2 lines of `iter`, yielding
+/// every line as soon as it's parsed together with its error (if any),
+/// whatever the tolerance of the parser
+///
+/// # Examples
+///
+/// ```ignore
+/// use bstr::io::BufReadExt;
+///
+/// // count the edges of a file without building the whole graph
+/// let parser: GFA2Parser<BString, ()> = GFA2Parser::new();
+/// let file = BufReader::new(File::open("./tests/big_files/ape-4-0.10b.gfa2").unwrap());
+/// let lines = file.byte_lines().map(|line| line.unwrap());
+/// let (mut dovetails, mut containments) = (0, 0);
+/// for line in GFA2ParserLineIter::from_parser(parser, lines) {
+///     if let Ok(Line::Edge(edge)) = line {
+///         dovetails += edge.is_dovetail() as usize;
+///         containments += edge.is_containment() as usize;
+///     }
+/// }
+/// ```
 pub struct GFA2ParserLineIter<I, N, T>
 where
     N: SegmentId,
@@ -297,25 +802,29 @@ impl<T: OptFields> Header<T> {
     }
 
     #[inline]
-    fn parse_line<I>(mut input: I) -> GFA2FieldResult<Self>
+    fn parse_line<I>(input: I) -> GFA2FieldResult<(Self, Vec<BString>)>
     where
         I: Iterator,
         I::Item: AsRef<[u8]>,
     {
-        let next = next_field(&mut input)?;
-        let version = OptField::parse(next.as_ref());
-        let version2 = version.clone();
-        let version = if let Some(OptFieldVal::Z(version)) = version.map(|v| v.value) {
-            Some(version)
-        } else if let Some(OptFieldVal::I(version2)) = version2.map(|v| v.value) {
-            Some(version2)
-        } else {
-            None
-        };
+        let mut input = input.peekable();
+        // the first field is the version only if it's a VN tag,
+        // otherwise it's an optional tag of an header without version
+        let version = input
+            .peek()
+            .and_then(|next| OptField::parse(next.as_ref()))
+            .filter(|opt| &opt.tag == b"VN")
+            .and_then(|opt| match opt.value {
+                OptFieldVal::Z(version) | OptFieldVal::I(version) => Some(version),
+                _ => None,
+            });
+        if version.is_some() {
+            input.next();
+        }
 
-        let tag = T::parse(input);
+        let (tag, invalid) = T::parse_with_invalid(input);
 
-        Ok(Header { version, tag })
+        Ok((Header { version, tag }, invalid))
     }
 }
 
@@ -344,13 +853,15 @@ where
     I::Item: AsRef<[u8]>,
 {
     lazy_static! {
-        static ref RE: Regex = Regex::new(r"(?-u)\-?[0-9]+").unwrap();
+        // the whole field has to be a non negative integer, or * if the length
+        // is unknown
+        static ref RE: Regex = Regex::new(r"(?-u)^(\*|[0-9]+)$").unwrap();
     }
 
     let next = next_field(input)?;
     RE.find(next.as_ref())
         .map(|s| BString::from(s.as_bytes()))
-        .ok_or(ParseFieldError::InvalidField("Lenght"))
+        .ok_or(ParseFieldError::InvalidField("Length"))
 }
 
 /// function that parses the SEGMENT element
@@ -362,7 +873,7 @@ impl<N: SegmentId, T: OptFields> Segment<N, T> {
     }
 
     #[inline]
-    fn parse_line<I>(mut input: I) -> GFA2FieldResult<Self>
+    fn parse_line<I>(mut input: I) -> GFA2FieldResult<(Self, Vec<BString>)>
     where
         I: Iterator,
         I::Item: AsRef<[u8]>,
@@ -370,13 +881,16 @@ impl<N: SegmentId, T: OptFields> Segment<N, T> {
         let id = N::parse_next(&mut input)?;
         let len = parse_slen(&mut input)?;
         let sequence = parse_sequence(&mut input)?;
-        let tag = T::parse(input);
-        Ok(Segment {
-            id,
-            len,
-            sequence,
-            tag,
-        })
+        let (tag, invalid) = T::parse_with_invalid(input);
+        Ok((
+            Segment {
+                id,
+                len,
+                sequence: sequence.into(),
+                tag,
+            },
+            invalid,
+        ))
     }
 }
 
@@ -424,7 +938,7 @@ impl<N: SegmentId, T: OptFields> Fragment<N, T> {
     }
 
     #[inline]
-    fn parse_line<I>(mut input: I) -> GFA2FieldResult<Self>
+    fn parse_line<I>(mut input: I) -> GFA2FieldResult<(Self, Vec<BString>)>
     where
         I: Iterator,
         I::Item: AsRef<[u8]>,
@@ -436,17 +950,20 @@ impl<N: SegmentId, T: OptFields> Fragment<N, T> {
         let fbeg = parse_pos(&mut input)?;
         let fend = parse_pos(&mut input)?;
         let alignment = parse_alignment(&mut input)?;
-        let tag = T::parse(input);
-        Ok(Fragment {
-            id,
-            ext_ref,
-            sbeg,
-            send,
-            fbeg,
-            fend,
-            alignment,
-            tag,
-        })
+        let (tag, invalid) = T::parse_with_invalid(input);
+        Ok((
+            Fragment {
+                id,
+                ext_ref,
+                sbeg,
+                send,
+                fbeg,
+                fend,
+                alignment,
+                tag,
+            },
+            invalid,
+        ))
     }
 }
 
@@ -459,7 +976,7 @@ impl<N: SegmentId, T: OptFields> Edge<N, T> {
     }
 
     #[inline]
-    fn parse_line<I>(mut input: I) -> GFA2FieldResult<Self>
+    fn parse_line<I>(mut input: I) -> GFA2FieldResult<(Self, Vec<BString>)>
     where
         I: Iterator,
         I::Item: AsRef<[u8]>,
@@ -472,18 +989,21 @@ impl<N: SegmentId, T: OptFields> Edge<N, T> {
         let beg2 = parse_pos(&mut input)?;
         let end2 = parse_pos(&mut input)?;
         let alignment = parse_alignment(&mut input)?;
-        let tag = T::parse(input);
-        Ok(Edge {
-            id,
-            sid1,
-            sid2,
-            beg1,
-            end1,
-            beg2,
-            end2,
-            alignment,
-            tag,
-        })
+        let (tag, invalid) = T::parse_with_invalid(input);
+        Ok((
+            Edge {
+                id,
+                sid1,
+                sid2,
+                beg1,
+                end1,
+                beg2,
+                end2,
+                alignment,
+                tag,
+            },
+            invalid,
+        ))
     }
 }
 
@@ -530,7 +1050,7 @@ impl<N: SegmentId, T: OptFields> Gap<N, T> {
     }
 
     #[inline]
-    fn parse_line<I>(mut input: I) -> GFA2FieldResult<Self>
+    fn parse_line<I>(mut input: I) -> GFA2FieldResult<(Self, Vec<BString>)>
     where
         I: Iterator,
         I::Item: AsRef<[u8]>,
@@ -540,15 +1060,18 @@ impl<N: SegmentId, T: OptFields> Gap<N, T> {
         let sid2 = N::parse_next_ref(&mut input)?;
         let dist = parse_dist(&mut input)?;
         let var = parse_var(&mut input)?;
-        let tag = T::parse(input);
-        Ok(Gap {
-            id,
-            sid1,
-            sid2,
-            dist,
-            var,
-            tag,
-        })
+        let (tag, invalid) = T::parse_with_invalid(input);
+        Ok((
+            Gap {
+                id,
+                sid1,
+                sid2,
+                dist,
+                var,
+                tag,
+            },
+            invalid,
+        ))
     }
 }
 
@@ -612,15 +1135,15 @@ impl<N: SegmentId, T: OptFields> GroupO<N, T> {
     }
 
     #[inline]
-    fn parse_line<I>(mut input: I) -> GFA2FieldResult<Self>
+    fn parse_line<I>(mut input: I) -> GFA2FieldResult<(Self, Vec<BString>)>
     where
         I: Iterator,
         I::Item: AsRef<[u8]>,
     {
         let id = parse_optional_id(&mut input)?;
         let var_field = parse_group_ref(&mut input)?;
-        let tag = T::parse(input);
-        Ok(GroupO::new(id, var_field, tag))
+        let (tag, invalid) = T::parse_with_invalid(input);
+        Ok((GroupO::new(id, var_field, tag), invalid))
     }
 }
 
@@ -633,15 +1156,15 @@ impl<N: SegmentId, T: OptFields> GroupU<N, T> {
     }
 
     #[inline]
-    fn parse_line<I>(mut input: I) -> GFA2FieldResult<Self>
+    fn parse_line<I>(mut input: I) -> GFA2FieldResult<(Self, Vec<BString>)>
     where
         I: Iterator,
         I::Item: AsRef<[u8]>,
     {
         let id = parse_optional_id(&mut input)?;
         let var_field = parse_group_id(&mut input)?;
-        let tag = T::parse(input);
-        Ok(GroupU::new(id, var_field, tag))
+        let (tag, invalid) = T::parse_with_invalid(input);
+        Ok((GroupU::new(id, var_field, tag), invalid))
     }
 }
 
@@ -659,7 +1182,8 @@ mod tests {
             tag: (),
         };
 
-        let result: GFA2FieldResult<Header<()>> = Header::parse_line([header].iter());
+        let result: GFA2FieldResult<Header<()>> =
+            Header::parse_line([header].iter()).map(|(line, _)| line);
 
         match result {
             Err(why) => println!("Error: {}", why),
@@ -675,7 +1199,8 @@ mod tests {
             tag: (),
         };
 
-        let result: GFA2FieldResult<Header<()>> = Header::parse_line([header].iter());
+        let result: GFA2FieldResult<Header<()>> =
+            Header::parse_line([header].iter()).map(|(line, _)| line);
 
         match result {
             Err(why) => println!("Error: {}", why),
@@ -683,6 +1208,523 @@ mod tests {
         }
     }
 
+    #[test]
+    fn header_without_version_round_trip() {
+        let header_: Header<OptionalFields> = Header {
+            version: None,
+            tag: vec![OptField::new(b"PG", OptFieldVal::Z("PG:Z:bandage".into()))],
+        };
+        let header = header_.to_string();
+        assert_eq!("H\tPG:Z:bandage", header);
+
+        let result: Header<OptionalFields> =
+            Header::parse_line(header.split_terminator('\t').skip(1))
+                .unwrap()
+                .0;
+        assert_eq!(header_, result);
+    }
+
+    #[test]
+    fn invalid_optional_field_is_a_warning() {
+        let lines = [
+            "S\t1\t4\tACGT\tRC:i:4\tk!:i:3\tLN:i:4",
+            "S\t2\t4\tACGT\tKC:i:10",
+        ];
+
+        let parser: GFA2Parser<BString, OptionalFields> = GFA2Parser::new();
+        let (gfa2, warnings) = parser.parse_lines_with_warnings(lines.iter()).unwrap();
+        let tags: Vec<String> = gfa2.segments[0].tag.iter().map(|t| t.to_string()).collect();
+        assert_eq!(vec!["RC:i:4", "LN:i:4"], tags);
+        assert_eq!(
+            vec![ParseWarning::InvalidOptField(1, "k!:i:3".to_string())],
+            warnings
+        );
+
+        let parser: GFA2Parser<BString, OptionalFields> =
+            GFA2ParserBuilder::all().pedantic_errors().build();
+        assert!(parser.parse_lines(lines.iter()).is_err());
+
+        // the invalid fields are the ones left by the parser of each line
+        let lines = [
+            "H\tVN:Z:2.0\th!:i:1",
+            "H\tPG:Z:bandage",
+            "F\t1\tr1+\t0\t4$\t0\t4\t*\tf!:i:1",
+            "G\t*\t1+\t2+\t10\t*\tg!:i:1",
+            "O\t*\t1+ 2+\to!:i:1",
+            "U\tu1\t1 2",
+        ];
+        let parser: GFA2Parser<BString, OptionalFields> = GFA2Parser::new();
+        let (_, warnings) = parser.parse_lines_with_warnings(lines.iter()).unwrap();
+        assert_eq!(
+            vec![
+                ParseWarning::InvalidOptField(1, "h!:i:1".to_string()),
+                ParseWarning::InvalidOptField(3, "f!:i:1".to_string()),
+                ParseWarning::InvalidOptField(4, "g!:i:1".to_string()),
+                ParseWarning::InvalidOptField(5, "o!:i:1".to_string()),
+            ],
+            warnings
+        );
+    }
+
+    #[test]
+    fn segment_length_must_be_numeric() {
+        for len in ["abc", "12abc", "1.5", "", "-5"].iter() {
+            let segment = format!("A\t{}\tACGT", len);
+            let result: GFA2FieldResult<Segment<BString, ()>> =
+                Segment::parse_line(segment.split_terminator('\t')).map(|(line, _)| line);
+            match result {
+                Err(ParseFieldError::InvalidField(field)) => assert_eq!("Length", field),
+                _ => panic!("Length {:?} should not be valid", len),
+            }
+        }
+
+        let parser: GFA2Parser<BString, ()> = GFA2Parser::new();
+        assert!(parser.parse_lines(["S\tA\tabc\tACGT"].iter()).is_err());
+        assert!(parser.parse_lines(["S\ts1\t-5\t*"].iter()).is_err());
+
+        let unknown: Segment<BString, ()> =
+            Segment::parse_line("A\t*\tACGT".split_terminator('\t'))
+                .unwrap()
+                .0;
+        assert_eq!("*", unknown.len);
+        assert_eq!(None, unknown.length());
+    }
+
+    #[test]
+    fn can_drop_sequences() {
+        let parser: GFA2Parser<BString, ()> = GFA2ParserBuilder::all().drop_sequences(true).build();
+        let gfa2 = parser.parse_file("./tests/gfa2_files/spec_q7.gfa").unwrap();
+
+        assert_eq!(3, gfa2.segments.len());
+        assert!(gfa2.segments.iter().all(|s| s.sequence == "*"));
+        let lens: Vec<_> = gfa2.segments.iter().map(|s| s.len.to_string()).collect();
+        assert_eq!(vec!["5", "6", "7"], lens);
+    }
+
+    #[test]
+    fn can_pack_sequences() {
+        let lines = ["S\t11\t5\tACCTT", "S\t12\t6\tTCANGG", "S\t13\t4\t*"];
+        let parser: GFA2Parser<BString, ()> = GFA2ParserBuilder::all().pack_sequences(true).build();
+        let gfa2 = parser.parse_lines(lines.iter()).unwrap();
+
+        let packed: Vec<_> = gfa2
+            .segments
+            .iter()
+            .map(|s| s.sequence.is_packed())
+            .collect();
+        assert_eq!(vec![true, false, false], packed);
+        // a packed segment is equal to the same segment stored as it is
+        let plain: GFA2Parser<BString, ()> = GFA2Parser::new();
+        let plain = plain.parse_lines(lines.iter()).unwrap();
+        assert_eq!(plain.segments, gfa2.segments);
+        let sequences: Vec<_> = gfa2
+            .segments
+            .iter()
+            .map(|s| s.sequence_bytes().to_str().unwrap().to_string())
+            .collect();
+        assert_eq!(vec!["ACCTT", "TCANGG", "*"], sequences);
+        assert_eq!("S\t11\t5\tACCTT", gfa2.segments[0].to_string());
+        assert_eq!(Some("CCT".into()), gfa2.segments[0].subsequence(1, 4));
+    }
+
+    #[test]
+    fn can_parse_only_some_line_types() {
+        let parser: GFA2Parser<BString, ()> = GFA2Parser::new().only(b"SE");
+        let gfa2 = parser.parse_file("./tests/gfa2_files/sample2.gfa").unwrap();
+        assert!(!gfa2.segments.is_empty());
+        assert!(!gfa2.edges.is_empty());
+        assert!(gfa2.headers.is_empty());
+        assert!(gfa2.fragments.is_empty());
+        assert!(gfa2.gaps.is_empty());
+        assert!(gfa2.groups_o.is_empty() && gfa2.groups_u.is_empty());
+
+        // the builder still chooses the line types
+        let mut builder = GFA2ParserBuilder::none();
+        builder.gaps = true;
+        let parser: GFA2Parser<BString, ()> = builder.build();
+        let gfa2 = parser.parse_file("./tests/gfa2_files/sample2.gfa").unwrap();
+        assert_eq!(
+            GFA2 {
+                gaps: gfa2.gaps.clone(),
+                ..GFA2::new()
+            },
+            gfa2
+        );
+        assert!(!gfa2.gaps.is_empty());
+    }
+
+    #[test]
+    fn can_trim_fields() {
+        let lines = [
+            "S\t 11 \t4\tACGT \tRC:i:4",
+            "E\t*\t11+ \t11-\t0\t4$\t0\t4$\t4M",
+        ];
+
+        let parser: GFA2Parser<BString, OptionalFields> = GFA2Parser::new();
+        assert!(parser.parse_lines(lines.iter()).is_err());
+
+        let parser: GFA2Parser<BString, OptionalFields> =
+            GFA2ParserBuilder::all().trim_fields(true).build();
+        let gfa2 = parser.parse_lines(lines.iter()).unwrap();
+        assert_eq!("S\t11\t4\tACGT\tRC:i:4", gfa2.segments[0].to_string());
+        assert_eq!("11+", gfa2.edges[0].sid1);
+    }
+
+    #[test]
+    fn edge_without_orientation() {
+        let lines = [
+            "S\t1\t4\tACGT",
+            "S\t2\t4\tACGT",
+            "E\t*\t1\t2\t2\t4$\t0\t2\t2M",
+        ];
+
+        let parser: GFA2Parser<BString, ()> = GFA2Parser::new();
+        assert!(parser.parse_lines(lines.iter()).is_err());
+        let parser: GFA2Parser<usize, ()> = GFA2ParserBuilder::all().pedantic_errors().build();
+        assert!(parser.parse_lines(lines.iter()).is_err());
+
+        let parser: GFA2Parser<BString, ()> = GFA2ParserBuilder::all().ignore_errors().build();
+        let (gfa2, warnings) = parser.parse_lines_with_warnings(lines.iter()).unwrap();
+        assert_eq!("E\t*\t1+\t2+\t2\t4$\t0\t2\t2M", gfa2.edges[0].to_string());
+        assert_eq!(
+            vec![
+                ParseWarning::MissingOrientation(3, "1".to_string()),
+                ParseWarning::MissingOrientation(3, "2".to_string()),
+            ],
+            warnings
+        );
+
+        let parser: GFA2Parser<usize, ()> = GFA2ParserBuilder::all().ignore_errors().build();
+        let gfa2 = parser.parse_lines(lines.iter()).unwrap();
+        assert_eq!(
+            Some((usize::parse_id(b"1").unwrap(), Orientation::Forward)),
+            gfa2.edges[0].sid1.split_ref()
+        );
+    }
+
+    #[test]
+    fn malformed_usize_id_is_an_error() {
+        let lines = ["S\t1\t4\tACGT", "S\t\t4\tACGT", "S\t3\t4\tACGT"];
+
+        let parser: GFA2Parser<usize, ()> = GFA2Parser::new();
+        match parser.parse_lines(lines.iter()) {
+            Err(ParseError::AtLine { line: 2, source }) => match *source {
+                ParseError::InvalidLine(ParseFieldError::UintIdError, _) => (),
+                _ => panic!("an empty id should not be parsed"),
+            },
+            _ => panic!("the error should be at the second line"),
+        }
+
+        let parser: GFA2Parser<usize, ()> = GFA2ParserBuilder::all().ignore_errors().build();
+        let gfa2 = parser.parse_lines(lines.iter()).unwrap();
+        assert_eq!(2, gfa2.segments.len());
+    }
+
+    #[test]
+    fn can_skip_line_types() {
+        let parser: GFA2Parser<BString, ()> = GFA2ParserBuilder::all()
+            .fragments(false)
+            .gaps(false)
+            .groups_o(false)
+            .groups_u(false)
+            .build();
+        let gfa2 = parser.parse_file("./tests/gfa2_files/spec_q7.gfa").unwrap();
+        let all = GFA2Parser::new()
+            .parse_file("./tests/gfa2_files/spec_q7.gfa")
+            .unwrap();
+        assert_eq!(all.segments, gfa2.segments);
+        assert_eq!(all.edges, gfa2.edges);
+        assert!(gfa2.gaps.is_empty());
+        assert!(gfa2.groups_o.is_empty() && gfa2.groups_u.is_empty());
+
+        let parser: GFA2Parser<BString, ()> = GFA2ParserBuilder::all()
+            .segments(false)
+            .edges(false)
+            .build();
+        let gfa2 = parser.parse_file("./tests/gfa2_files/spec_q7.gfa").unwrap();
+        assert!(gfa2.segments.is_empty() && gfa2.edges.is_empty());
+        assert_eq!(all.groups_o, gfa2.groups_o);
+    }
+
+    #[test]
+    fn errors_have_line_number() {
+        let parser: GFA2Parser<BString, ()> = GFA2Parser::new();
+        let err = parser
+            .parse_file("./tests/gfa2_files/parse_errors.gfa")
+            .unwrap_err();
+        assert_eq!(Some(2), err.line());
+        assert!(err.to_string().starts_with("line 2: "));
+        match err.kind() {
+            ParseError::InvalidLine(ParseFieldError::InvalidField(field), _) => {
+                assert_eq!("Length", *field)
+            }
+            _ => panic!("the length of the segment should be invalid"),
+        }
+
+        // the comment is skipped by the default parser, while the
+        // pedantic one stops at it
+        let lines = [
+            "S\t2\t4\tACGT",
+            "# comment",
+            "E\t*\t2+\t3+\t0\t4$\t0\t4\t4M\tk!:i:1",
+        ];
+        let parser: GFA2Parser<BString, OptionalFields> =
+            GFA2ParserBuilder::all().pedantic_errors().build();
+        assert_eq!(
+            Some(2),
+            parser.parse_lines(lines.iter()).unwrap_err().line()
+        );
+        let parser: GFA2Parser<BString, OptionalFields> =
+            GFA2ParserBuilder::all().ignore_safe_errors().build();
+        assert!(parser.parse_lines(lines.iter()).is_ok());
+    }
+
+    #[test]
+    fn can_keep_comments() {
+        let lines = [
+            "# generated by tool v1.2",
+            "H\tVN:Z:2.0",
+            "#no space\r",
+            "S\t1\t4\tACGT",
+        ];
+        let parser: GFA2Parser<BString, ()> = GFA2Parser::new();
+        let gfa2 = parser.parse_lines(lines.iter()).unwrap();
+        assert!(gfa2.comments.is_empty());
+
+        let parser: GFA2Parser<BString, ()> = GFA2ParserBuilder::all().keep_comments(true).build();
+        let gfa2 = parser.parse_lines(lines.iter()).unwrap();
+        assert_eq!(
+            vec![
+                BString::from(" generated by tool v1.2"),
+                BString::from("no space")
+            ],
+            gfa2.comments
+        );
+        // the comments are written after the other lines, wherever they were
+        assert_eq!(
+            "H\tVN:Z:2.0\nS\t1\t4\tACGT\n# generated by tool v1.2\n#no space\n",
+            gfa2.to_string()
+        );
+    }
+
+    #[test]
+    fn can_normalize_whitespaces() {
+        let normalize = |line: &str| {
+            normalize_whitespace(line.as_bytes())
+                .to_str_lossy()
+                .into_owned()
+        };
+        assert_eq!(
+            "S\t1\t4\tACGT\txx:Z:some  text\tRC:i:4",
+            normalize("S  1\t\t4 ACGT\txx:Z:some \ttext RC:i:4")
+        );
+        assert_eq!(
+            "O\tP1\t36+ 50- 7+\tvo:J:{\"a\": 1}",
+            normalize("O P1  36+ 50-\t7+ vo:J:{\"a\": 1}")
+        );
+        assert_eq!("U\t*\t16 24", normalize("U * 16  24"));
+        assert_eq!("H\tVN:Z:2.0", normalize("H\tVN:Z:2.0"));
+    }
+
+    #[test]
+    fn can_parse_whitespace_tolerant() {
+        let lines = [
+            "H  VN:Z:2.0",
+            "S 1  4\t\tACGT   xx:Z:some text",
+            "S 2 3 AAC",
+            "E * 1+  2- 0 2 1 3$ 2M",
+            "O  P1 1+  2-",
+            "U SG1 1 2",
+        ];
+        let parser: GFA2Parser<BString, OptionalFields> =
+            GFA2ParserBuilder::all().whitespace_tolerant(true).build();
+        let gfa2 = parser.parse_lines(lines.iter()).unwrap();
+        assert_eq!(2, gfa2.segments.len());
+        assert_eq!(
+            Some(OptFieldVal::Z("xx:Z:some text".into())),
+            gfa2.segments[0]
+                .tag
+                .get_field(b"xx")
+                .map(|t| t.value.clone())
+        );
+        assert_eq!("2-", gfa2.edges[0].sid2);
+        assert_eq!("1+ 2-", gfa2.groups_o[0].var_field);
+        assert_eq!("1 2", gfa2.groups_u[0].var_field);
+
+        // without the option the spaces are not separators
+        let parser: GFA2Parser<BString, OptionalFields> = GFA2Parser::new();
+        assert_ne!(Some(gfa2), parser.parse_lines(lines.iter()).ok());
+    }
+
+    #[test]
+    fn can_parse_reader() {
+        use std::{fs::File, io::BufReader};
+
+        let parser: GFA2Parser<BString, OptionalFields> = GFA2Parser::new();
+        let gfa2 = parser.parse_file("./tests/gfa2_files/spec_q7.gfa").unwrap();
+        let file = File::open("./tests/gfa2_files/spec_q7.gfa").unwrap();
+        assert_eq!(gfa2, parser.parse_reader(BufReader::new(file)).unwrap());
+
+        // the extension is not checked
+        let file = File::open("./tests/gfa2_files/extension_error.txt").unwrap();
+        let gfa2 = parser.parse_reader(BufReader::new(file)).unwrap();
+        assert_eq!(GFA2::new(), gfa2);
+    }
+
+    #[test]
+    fn can_keep_unknown_lines() {
+        let lines = [
+            "S\t1\t4\tACGT",
+            "X\tfoo\tbar",
+            "E\t*\t1+\t1-\t0\t4$\t0\t4$\t4M",
+            "Ab\tnot a record",
+            "Y",
+        ];
+        let parser: GFA2Parser<BString, ()> = GFA2Parser::new();
+        assert!(parser
+            .parse_lines(lines.iter())
+            .unwrap()
+            .custom_records
+            .is_empty());
+
+        let parser: GFA2Parser<BString, ()> = GFA2ParserBuilder::all()
+            .edges(false)
+            .keep_unknown(true)
+            .build();
+        let gfa2 = parser.parse_lines(lines.iter()).unwrap();
+        // the disabled edges are still skipped
+        assert!(gfa2.edges.is_empty());
+        assert_eq!(
+            vec![
+                CustomRecord {
+                    type_field: b'X',
+                    fields: vec!["foo".into(), "bar".into()],
+                },
+                CustomRecord {
+                    type_field: b'Y',
+                    fields: vec![],
+                },
+            ],
+            gfa2.custom_records
+        );
+        assert_eq!("S\t1\t4\tACGT\nX\tfoo\tbar\nY\n", gfa2.to_string());
+    }
+
+    #[test]
+    fn can_collect_errors() {
+        let parser: GFA2Parser<BString, OptionalFields> =
+            GFA2ParserBuilder::all().pedantic_errors().build();
+        let (gfa2, errors) =
+            parser.parse_file_collect_errors("./tests/gfa2_files/parse_errors.gfa");
+
+        let ids: Vec<_> = gfa2.segments.iter().map(|s| s.id.to_string()).collect();
+        assert_eq!(vec!["2", "3"], ids);
+        assert!(gfa2.edges.is_empty());
+        // the comment at line 4 is not an error
+        let lines: Vec<_> = errors.iter().map(|(line, _)| *line).collect();
+        assert_eq!(vec![2, 5], lines);
+
+        // nor are the line types excluded by the builder
+        let only_segments: GFA2Parser<BString, OptionalFields> = GFA2Parser::new().only(b"S");
+        let (gfa2, errors) =
+            only_segments.parse_file_collect_errors("./tests/gfa2_files/parse_errors.gfa");
+        assert_eq!(2, gfa2.segments.len());
+        let lines: Vec<_> = errors.iter().map(|(line, _)| *line).collect();
+        assert_eq!(vec![2], lines);
+
+        let (gfa2, errors) =
+            parser.parse_file_collect_errors("./tests/gfa2_files/extension_error.txt");
+        assert_eq!(GFA2::new(), gfa2);
+        match &errors[..] {
+            [(0, ParseError::ExtensionError(ext))] => assert_eq!("txt", ext),
+            _ => panic!("the extension .txt should be rejected"),
+        }
+    }
+
+    #[test]
+    fn can_parse_bytes() {
+        let parser: GFA2Parser<BString, OptionalFields> = GFA2Parser::new();
+        let bytes = std::fs::read("./tests/gfa2_files/sample2.gfa").unwrap();
+        assert_eq!(
+            parser.parse_file("./tests/gfa2_files/sample2.gfa").unwrap(),
+            parser.parse_bytes(&bytes).unwrap()
+        );
+        let crlf = bytes.replace("\n", "\r\n");
+        assert_eq!(
+            parser.parse_bytes(&bytes).unwrap(),
+            parser.parse_bytes(&crlf).unwrap()
+        );
+    }
+
+    #[cfg(feature = "memmap2")]
+    #[test]
+    fn can_parse_file_mmap() {
+        let parser: GFA2Parser<BString, OptionalFields> = GFA2Parser::new();
+        assert_eq!(
+            parser.parse_file("./tests/gfa2_files/sample2.gfa").unwrap(),
+            parser
+                .parse_file_mmap("./tests/gfa2_files/sample2.gfa")
+                .unwrap()
+        );
+
+        let err = parser
+            .parse_file_mmap("./tests/gfa2_files/parse_errors.gfa")
+            .unwrap_err();
+        assert_eq!(Some(2), err.line());
+        match parser.parse_file_mmap("./tests/gfa2_files/extension_error.txt") {
+            Err(ParseError::ExtensionError(ext)) => assert_eq!("txt", ext),
+            _ => panic!("the extension .txt should be rejected"),
+        }
+        match parser.parse_file_mmap("./tests/gfa2_files/sample2.gfa.gz") {
+            Err(ParseError::ExtensionError(ext)) => assert_eq!("gz", ext),
+            _ => panic!("a compressed file can't be mapped"),
+        }
+        assert!(parser
+            .parse_file_mmap("./tests/gfa2_files/not_a_file.gfa")
+            .is_err());
+    }
+
+    #[test]
+    fn can_transform_lines_while_parsing() {
+        let parser: GFA2Parser<BString, OptionalFields> = GFA2Parser::new();
+        let gfa2 = parser
+            .parse_file_with("./tests/gfa2_files/spec_q7.gfa", |line| match line {
+                Line::Segment(mut segment) => {
+                    segment.tag = vec![];
+                    Some(Line::Segment(segment))
+                }
+                Line::Edge(edge) if edge.sid1 != "11+" => Some(Line::Edge(edge)),
+                Line::Header(_) | Line::Edge(_) => None,
+                line => Some(line),
+            })
+            .unwrap();
+
+        assert!(gfa2.headers.is_empty());
+        assert_eq!(3, gfa2.segments.len());
+        assert!(gfa2.segments.iter().all(|s| s.tag.is_empty()));
+        assert_eq!(1, gfa2.edges.len());
+        assert_eq!(1, gfa2.groups_o.len());
+    }
+
+    #[test]
+    fn anonymous_edge_round_trip() {
+        let edge = "*\t2+\t45+\t2531\t2591$\t0\t60\t60M";
+
+        let result: Edge<BString, ()> = Edge::parse_line(edge.split_terminator('\t')).unwrap().0;
+        assert!(result.is_anonymous());
+        assert_eq!(format!("E\t{}", edge), result.to_string());
+
+        let result: Edge<usize, ()> = Edge::parse_line(edge.split_terminator('\t')).unwrap().0;
+        assert!(result.is_anonymous());
+        assert_eq!(format!("E\t{}", edge), result.to_string());
+
+        let named: Edge<usize, ()> =
+            Edge::parse_line("1\t2+\t45+\t2531\t2591$\t0\t60\t60M".split_terminator('\t'))
+                .unwrap()
+                .0;
+        assert!(!named.is_anonymous());
+    }
+
     #[test]
     fn can_parse_segment() {
         let segment = "A\t10\tAAAAAAACGT";
@@ -694,7 +1736,7 @@ mod tests {
         };
 
         let fields = segment.split_terminator('\t');
-        let result = Segment::parse_line(fields);
+        let result = Segment::parse_line(fields).map(|(line, _)| line);
 
         match result {
             Err(why) => println!("Error: {}", why),
@@ -717,7 +1759,7 @@ mod tests {
         };
 
         let fields = fragment.split_terminator('\t');
-        let result = Fragment::parse_line(fields);
+        let result = Fragment::parse_line(fields).map(|(line, _)| line);
 
         match result {
             Err(why) => println!("Error: {}", why),
@@ -741,7 +1783,7 @@ mod tests {
         };
 
         let fields = edge.split_terminator('\t');
-        let result = Edge::parse_line(fields);
+        let result = Edge::parse_line(fields).map(|(line, _)| line);
 
         match result {
             Err(why) => println!("Error: {}", why),
@@ -761,7 +1803,7 @@ mod tests {
         };
 
         let fields = gap.split_terminator('\t');
-        let result = Gap::parse_line(fields);
+        let result = Gap::parse_line(fields).map(|(line, _)| line);
 
         match result {
             Err(why) => println!("Error: {}", why),
@@ -776,7 +1818,7 @@ mod tests {
             GroupO::new("P1".into(), "36+ 53+ 53_38+ 38_13+ 13+ 14+ 50-".into(), ());
 
         let fields = ogroup.split_terminator('\t');
-        let result = GroupO::parse_line(fields);
+        let result = GroupO::parse_line(fields).map(|(line, _)| line);
 
         match result {
             Err(why) => println!("Error {}", why),
@@ -794,6 +1836,12 @@ mod tests {
         for (name, orientation) in ogroup_.iter() {
             println!("{}{}", name, orientation);
         }
+        assert_eq!(7, ogroup_.len());
+
+        let ogroup_: GroupO<BString, ()> = GroupO::new("P2".into(), " 36+  53-\t".into(), ());
+        assert_eq!(2, ogroup_.len());
+        let ogroup_: GroupO<BString, ()> = GroupO::new("P3".into(), "".into(), ());
+        assert!(ogroup_.is_empty());
     }
 
     #[test]
@@ -803,7 +1851,7 @@ mod tests {
             GroupU::new("SG1".into(), "16 24 SG2 51_24 16_24".into(), ());
 
         let fields = ugroup.split_terminator('\t');
-        let result = GroupU::parse_line(fields);
+        let result = GroupU::parse_line(fields).map(|(line, _)| line);
 
         match result {
             Err(why) => println!("Error: {}", why),
@@ -821,6 +1869,10 @@ mod tests {
         for name in ugroup_.iter() {
             println!("{}", name);
         }
+        assert_eq!(5, ugroup_.len());
+
+        let ugroup_: GroupU<BString, ()> = GroupU::new("SG2".into(), "  ".into(), ());
+        assert!(ugroup_.is_empty());
     }
 
     #[test]
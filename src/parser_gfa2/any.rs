@@ -0,0 +1,106 @@
+/// define a parser that accepts both GFA1 and GFA2 files, choosing
+/// the parser from the version detected in the file
+use crate::{
+    gfa1::GFA,
+    gfa2::*,
+    parser_gfa1::GFAParser,
+    parser_gfa2::{detect_version, ConversionError, GFA2Parser, GFAVersion, ParseError},
+    tag::*,
+};
+
+use std::{convert::TryFrom, hash::Hash};
+
+/// A graph parsed from a GFA1 or a GFA2 file
+#[derive(Debug, Clone, PartialEq)]
+pub enum GFAGraph<N, T: OptFields> {
+    V1(GFA<N, T>),
+    V2(GFA2<N, T>),
+}
+
+impl<N, T: OptFields> GFAGraph<N, T> {
+    /// Returns the version of the file the graph has been parsed from
+    pub fn version(&self) -> GFAVersion {
+        match self {
+            GFAGraph::V1(_) => GFAVersion::GFA1,
+            GFAGraph::V2(_) => GFAVersion::GFA2,
+        }
+    }
+}
+
+impl<N, T> GFAGraph<N, T>
+where
+    N: SegmentId + Clone + Eq + Hash,
+    T: OptFields,
+{
+    /// Returns the graph as a GFA2 object, converting it if it has been
+    /// parsed from a GFA1 file (see ```GFA2::try_from```)
+    pub fn into_gfa2(self) -> Result<GFA2<N, T>, ConversionError> {
+        match self {
+            GFAGraph::V1(gfa) => GFA2::try_from(gfa),
+            GFAGraph::V2(gfa2) => Ok(gfa2),
+        }
+    }
+}
+
+/// Function that parses a GFA1 or GFA2 file with the default parser of
+/// the version found by detect_version.\
+/// A file whose version can't be detected (e.g. it has only comments)
+/// is parsed as a GFA2 file
+///
+/// # Examples
+///
+/// ```ignore
+/// use gfa2::parser_gfa2::{parse_any, GFAGraph};
+///
+/// let graph: GFAGraph<BString, OptionalFields> =
+///     parse_any("./tests/gfa1_files/lil.gfa").unwrap();
+/// let gfa2 = graph.into_gfa2().unwrap();
+/// ```
+pub fn parse_any<N, T, P>(path: P) -> Result<GFAGraph<N, T>, ParseError>
+where
+    N: SegmentId,
+    T: OptFields,
+    P: AsRef<std::path::Path>,
+{
+    match detect_version(path.as_ref())? {
+        GFAVersion::GFA1 => {
+            let parser: GFAParser<N, T> = GFAParser::new();
+            parser.parse_file(path).map(GFAGraph::V1)
+        }
+        GFAVersion::GFA2 | GFAVersion::Unknown => {
+            let parser: GFA2Parser<N, T> = GFA2Parser::new();
+            parser.parse_file(path).map(GFAGraph::V2)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bstr::BString;
+
+    #[test]
+    fn can_parse_any_version() {
+        let graph: GFAGraph<BString, OptionalFields> =
+            parse_any("./tests/gfa1_files/lil.gfa").unwrap();
+        assert_eq!(GFAVersion::GFA1, graph.version());
+        let gfa = GFAParser::new()
+            .parse_file("./tests/gfa1_files/lil.gfa")
+            .unwrap();
+        assert_eq!(GFA2::try_from(gfa).unwrap(), graph.into_gfa2().unwrap());
+
+        let graph: GFAGraph<BString, OptionalFields> =
+            parse_any("./tests/gfa2_files/spec_q7.gfa").unwrap();
+        let gfa2 = GFA2Parser::new()
+            .parse_file("./tests/gfa2_files/spec_q7.gfa")
+            .unwrap();
+        assert_eq!(GFAGraph::V2(gfa2), graph);
+    }
+
+    #[test]
+    fn parse_any_errors() {
+        let graph: Result<GFAGraph<BString, ()>, _> =
+            parse_any("./tests/gfa2_files/extension_error.txt");
+        assert!(matches!(graph, Err(ParseError::ExtensionError(_))));
+    }
+}
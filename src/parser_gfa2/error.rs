@@ -1,4 +1,4 @@
-use bstr::ByteSlice;
+use bstr::{BString, ByteSlice};
 /// define a custom error for the GFA2 format
 use std::{error, fmt};
 
@@ -26,6 +26,13 @@ pub enum ParseFieldError {
     /// A segment ID couldn't be parsed as a u64. Can only happen
     /// when parsing into a GFA<u64, T>.
     UintIdError,
+    /// A segment name can't be encoded as a usize id: every char
+    /// takes 6 bits and must be in `[0-9A-Za-z_.]`, up to 10 chars on
+    /// 64-bit targets and 5 on 32-bit ones. Includes the name, the
+    /// file can still be parsed with BString ids.
+    IdTooLong {
+        name: BString,
+    },
     /// A bytestring couldn't be parsed as a bytestring, can happen
     /// when the contents aren't UTF8.
     Utf8Error,
@@ -71,6 +78,11 @@ impl fmt::Display for ParseFieldError {
         use ParseFieldError as PFE;
         match self {
             PFE::UintIdError => write!(f, "Failed to parse a segment ID as an unsigned integer"),
+            PFE::IdTooLong { name } => write!(
+                f,
+                "The segment ID `{}` is too long to be encoded as an unsigned integer",
+                name
+            ),
             PFE::Utf8Error => write!(f, "Failed to parse a bytestring as a UTF-8 string"),
             PFE::ParseFromStringError => write!(f, "Failed to parse a field from a string"),
             PFE::OrientationError => write!(f, "Failed to parse an orientation character"),
@@ -99,7 +111,19 @@ pub enum ParseError {
     InvalidField(ParseFieldError),
     /// Wrapper for an IO error.
     IOError(std::io::Error),
-    ExtensionError(),
+    /// The file extension was not .gfa or .gfa2, optionally followed
+    /// by .gz or .bgz if the `gzip` feature is enabled. Includes the
+    /// extension (empty if the file has none).
+    ExtensionError(String),
+    /// A line has been parsed but it can't be converted to the other
+    /// format. Includes the reason.
+    Conversion(ConversionError),
+    /// The error stopped the parsing of a file at the given line
+    /// (starting from 1).
+    AtLine {
+        line: usize,
+        source: Box<ParseError>,
+    },
     Unknown,
 }
 
@@ -117,7 +141,16 @@ impl fmt::Display for ParseError {
             }
             PE::InvalidField(field_err) => write!(f, "Failed to parse field: {}", field_err),
             PE::IOError(err) => write!(f, "IO error: {}", err),
-            PE::ExtensionError() => write!(f, "Extension not correct!"),
+            PE::ExtensionError(ext) if ext.is_empty() => {
+                write!(f, "missing extension, expected .gfa or .gfa2")
+            }
+            PE::ExtensionError(ext) => write!(
+                f,
+                "unsupported extension '.{}', expected .gfa or .gfa2",
+                ext
+            ),
+            PE::Conversion(err) => write!(f, "Failed to convert a line: {}", err),
+            PE::AtLine { line, source } => write!(f, "line {}: {}", line, source),
             PE::Unknown => write!(f, "Unknown error when parsing a line"),
         }
     }
@@ -135,7 +168,52 @@ impl From<ParseFieldError> for ParseError {
     }
 }
 
-impl error::Error for ParseError {}
+impl From<ConversionError> for ParseError {
+    fn from(err: ConversionError) -> Self {
+        Self::Conversion(err)
+    }
+}
+
+impl error::Error for ParseError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            ParseError::AtLine { source, .. } => Some(source.as_ref()),
+            _ => None,
+        }
+    }
+}
+
+/// A non fatal problem found while parsing a line, that has been
+/// skipped instead of rejecting the whole line
+#[derive(Debug, Clone, PartialEq)]
+pub enum ParseWarning {
+    /// An optional field was malformed. Includes the line number
+    /// (starting from 1) and the field.
+    InvalidOptField(usize, String),
+    /// A segment reference of an edge had no orientation, so it has
+    /// been taken as forward (only with ParserTolerance::IgnoreAll).
+    /// Includes the line number and the reference.
+    MissingOrientation(usize, String),
+}
+
+impl fmt::Display for ParseWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseWarning::InvalidOptField(line, field) => {
+                write!(
+                    f,
+                    "line {}: skipped invalid optional field `{}`",
+                    line, field
+                )
+            }
+            ParseWarning::MissingOrientation(line, sid) => write!(
+                f,
+                "line {}: reference `{}` has no orientation, using +",
+                line, sid
+            ),
+        }
+    }
+}
 
 impl ParseError {
     pub(crate) fn invalid_line(error: ParseFieldError, line: &[u8]) -> Self {
@@ -144,8 +222,36 @@ impl ParseError {
         Self::InvalidLine(error, dest)
     }
 
+    /// wraps the error with the number of the line that caused it
+    pub(crate) fn at_line(self, line: usize) -> Self {
+        Self::AtLine {
+            line,
+            source: Box::new(self),
+        }
+    }
+
+    /// Returns the number of the line where the parsing stopped, if
+    /// known
+    pub fn line(&self) -> Option<usize> {
+        match self {
+            ParseError::AtLine { line, .. } => Some(*line),
+            _ => None,
+        }
+    }
+
+    /// Returns the error without the number of its line
+    pub fn kind(&self) -> &ParseError {
+        match self {
+            ParseError::AtLine { source, .. } => source.kind(),
+            err => err,
+        }
+    }
+
     pub(crate) fn can_safely_continue(&self, tol: &ParserTolerance) -> bool {
         use ParserTolerance as Tol;
+        if let ParseError::AtLine { source, .. } = self {
+            return source.can_safely_continue(tol);
+        }
         match tol {
             Tol::IgnoreAll => true,
             Tol::Safe => match self {
@@ -157,3 +263,45 @@ impl ParseError {
         }
     }
 }
+
+/// Type encapsulating the reasons why a line can't be converted
+/// between the GFA1 and the GFA2 format
+#[derive(Debug, Clone, PartialEq)]
+pub enum ConversionError {
+    /// The line type has no equivalent in the other format. Includes
+    /// the line type.
+    NoCounterpart(&'static str),
+    /// The conversion needs the length of the segments, that is not
+    /// known from the line alone. Includes the line type.
+    MissingSegmentLength(&'static str),
+    /// An edge that is neither a dovetail overlap nor a containment,
+    /// or whose positions don't match its orientations.
+    InvalidEdge,
+    /// A fragment that doesn't cover its external sequence entirely,
+    /// so it can't be a containment.
+    InvalidFragment,
+    /// An o-group that goes through something other than segments
+    /// (e.g. an edge), so it can't be a path.
+    InvalidGroup,
+}
+
+impl fmt::Display for ConversionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        use ConversionError as CE;
+        match self {
+            CE::NoCounterpart(line) => {
+                write!(f, "{} lines have no counterpart in the other format", line)
+            }
+            CE::MissingSegmentLength(line) => write!(
+                f,
+                "Converting a {} line needs the length of its segments",
+                line
+            ),
+            CE::InvalidEdge => write!(f, "Edge is neither a dovetail overlap nor a containment"),
+            CE::InvalidFragment => write!(f, "Fragment is not contained entirely in its segment"),
+            CE::InvalidGroup => write!(f, "O-Group goes through lines other than segments"),
+        }
+    }
+}
+
+impl error::Error for ConversionError {}
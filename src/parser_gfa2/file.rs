@@ -0,0 +1,97 @@
+/// define how the GFA1 and GFA2 files are opened, checking their
+/// extension and decompressing them if they are gzip (or BGZF) files
+use crate::parser_gfa2::ParseError;
+
+use std::{
+    ffi::OsStr,
+    fs::File,
+    io::{BufRead, BufReader},
+    path::Path,
+};
+
+/// checks the extension of a file, that has to be .gfa or .gfa2,
+/// optionally followed by .gz or .bgz, returning if it's compressed
+fn is_compressed(path: &Path) -> Result<bool, ParseError> {
+    let extension = |path: &Path| path.extension().and_then(OsStr::to_str).map(str::to_string);
+    match extension(path).as_deref() {
+        Some("gfa2") | Some("gfa") => Ok(false),
+        Some(ext @ "gz") | Some(ext @ "bgz") => {
+            let inner = path.file_stem().map(Path::new).and_then(extension);
+            match inner.as_deref() {
+                Some("gfa2") | Some("gfa") => Ok(true),
+                Some(inner) => Err(ParseError::ExtensionError(format!("{}.{}", inner, ext))),
+                None => Err(ParseError::ExtensionError(ext.to_string())),
+            }
+        }
+        ext => Err(ParseError::ExtensionError(ext.unwrap_or("").to_string())),
+    }
+}
+
+/// opens a GFA1 or GFA2 file after checking its extension, reading
+/// it through a gzip decoder if it ends with .gz or .bgz
+pub(crate) fn open_file<P: AsRef<Path>>(path: P) -> Result<Box<dyn BufRead>, ParseError> {
+    let compressed = is_compressed(path.as_ref())?;
+    let file = File::open(path.as_ref())?;
+    if compressed {
+        decompress(file, path.as_ref())
+    } else {
+        Ok(Box::new(BufReader::new(file)))
+    }
+}
+
+/// maps a GFA1 or GFA2 file in memory after checking its extension,
+/// rejecting the compressed files (that can't be parsed in place)
+#[cfg(feature = "memmap2")]
+pub(crate) fn map_file<P: AsRef<Path>>(path: P) -> Result<memmap2::Mmap, ParseError> {
+    if is_compressed(path.as_ref())? {
+        let ext = path.as_ref().extension().and_then(OsStr::to_str);
+        return Err(ParseError::ExtensionError(ext.unwrap_or("").to_string()));
+    }
+    let file = File::open(path.as_ref())?;
+    // SAFETY: the map is only read, but the file must not be modified
+    // by another process while it's parsed
+    let mmap = unsafe { memmap2::Mmap::map(&file)? };
+    Ok(mmap)
+}
+
+#[cfg(feature = "gzip")]
+fn decompress(file: File, _path: &Path) -> Result<Box<dyn BufRead>, ParseError> {
+    // a BGZF file is a series of gzip members, so the decoder has
+    // to read all of them and not only the first one
+    let decoder = flate2::read::MultiGzDecoder::new(BufReader::new(file));
+    Ok(Box::new(BufReader::new(decoder)))
+}
+
+#[cfg(not(feature = "gzip"))]
+fn decompress(_file: File, path: &Path) -> Result<Box<dyn BufRead>, ParseError> {
+    // without the gzip feature the compressed files are not supported
+    let ext = path.extension().and_then(OsStr::to_str).unwrap_or("");
+    Err(ParseError::ExtensionError(ext.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn can_check_extension() {
+        assert!(!is_compressed(Path::new("graph.gfa")).unwrap());
+        assert!(!is_compressed(Path::new("./dir.gz/graph.gfa2")).unwrap());
+        assert!(is_compressed(Path::new("graph.gfa2.gz")).unwrap());
+        assert!(is_compressed(Path::new("graph.gfa.bgz")).unwrap());
+
+        for (path, ext) in [
+            ("graph.txt", "txt"),
+            ("graph", ""),
+            ("graph.txt.gz", "txt.gz"),
+            ("graph.gz", "gz"),
+        ]
+        .iter()
+        {
+            match is_compressed(Path::new(path)) {
+                Err(ParseError::ExtensionError(found)) => assert_eq!(ext, &found),
+                _ => panic!("the extension of {} should be rejected", path),
+            }
+        }
+    }
+}
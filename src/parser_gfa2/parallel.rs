@@ -0,0 +1,130 @@
+/// define the parsing of a file split across multiple threads, as the
+/// lines can be parsed independently of each other
+use crate::{
+    gfa2::*,
+    parser_gfa2::{file::open_file, GFA2Parser, ParseError},
+    tag::*,
+};
+
+/// reads every line of a GFA1 or GFA2 file, checking its extension
+pub(crate) fn read_file_lines<P: AsRef<std::path::Path>>(
+    path: P,
+) -> Result<Vec<Vec<u8>>, ParseError> {
+    use bstr::io::BufReadExt;
+
+    let lines = open_file(path)?.byte_lines().collect::<Result<_, _>>()?;
+    Ok(lines)
+}
+
+/// splits the lines in (at most) ```threads``` chunks and parses them
+/// on a rayon pool of ```threads``` threads with ```parse_chunk``` (that
+/// gets the number of the first line of the chunk, starting from 1),
+/// returning the parsed chunks in the order of the lines, or the first
+/// error found
+pub(crate) fn parse_chunks<G, F>(
+    lines: &[Vec<u8>],
+    threads: usize,
+    parse_chunk: F,
+) -> Result<Vec<G>, ParseError>
+where
+    G: Send,
+    F: Fn(usize, &[Vec<u8>]) -> Result<G, ParseError> + Sync,
+{
+    use rayon::prelude::*;
+
+    let threads = threads.max(1);
+    let chunk_size = lines.len().div_ceil(threads).max(1);
+    let pool = rayon::ThreadPoolBuilder::new()
+        .num_threads(threads)
+        .build()
+        .map_err(std::io::Error::other)?;
+    pool.install(|| {
+        lines
+            .par_chunks(chunk_size)
+            .enumerate()
+            .map(|(i, chunk)| parse_chunk(i * chunk_size + 1, chunk))
+            .collect()
+    })
+}
+
+impl<N, T> GFA2Parser<N, T>
+where
+    N: SegmentId + Send + Sync,
+    T: OptFields + Send + Sync,
+{
+    /// Like parse_file, but the lines are parsed on ```threads``` threads
+    /// (at least one), each one building the part of the GFA2 object of
+    /// a contiguous chunk of lines, that are then merged keeping the
+    /// order of the file.\
+    /// The whole file is read in memory before parsing it
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// let parser: GFA2Parser<BString, OptionalFields> = GFA2Parser::new();
+    /// let threads = std::thread::available_parallelism().map_or(1, |n| n.get());
+    /// let gfa2 = parser
+    ///     .parse_file_parallel("./tests/big_files/ape-4-0.10b.gfa2", threads)
+    ///     .unwrap();
+    /// ```
+    pub fn parse_file_parallel<P: AsRef<std::path::Path>>(
+        &self,
+        path: P,
+        threads: usize,
+    ) -> Result<GFA2<N, T>, ParseError> {
+        let lines = read_file_lines(path)?;
+        let chunks = parse_chunks(&lines, threads, |first_line, chunk| {
+            let mut gfa2 = GFA2::new();
+            for (line_no, line) in chunk.iter().enumerate() {
+                match self.parse_gfa_line(line.as_ref()) {
+                    Ok(parsed) => gfa2.insert_line(parsed),
+                    Err(err) if err.can_safely_continue(&self.tolerance) => (),
+                    Err(err) => return Err(err.at_line(first_line + line_no)),
+                }
+            }
+            Ok(gfa2)
+        })?;
+
+        let mut gfa2 = GFA2::new();
+        for mut chunk in chunks {
+            // merge keeps only the first headers, but every chunk can
+            // have some headers of the file
+            gfa2.headers.append(&mut chunk.headers);
+            gfa2.merge(chunk);
+        }
+        Ok(gfa2)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bstr::BString;
+
+    #[test]
+    fn parallel_parsing_keeps_the_order() {
+        let parser: GFA2Parser<BString, OptionalFields> = GFA2Parser::new();
+        let gfa2 = parser.parse_file("./tests/gfa2_files/sample2.gfa").unwrap();
+        for threads in [0, 1, 3, 100].iter() {
+            let parallel = parser
+                .parse_file_parallel("./tests/gfa2_files/sample2.gfa", *threads)
+                .unwrap();
+            assert_eq!(gfa2, parallel);
+        }
+    }
+
+    #[test]
+    fn parallel_parsing_errors() {
+        let parser: GFA2Parser<BString, OptionalFields> = GFA2Parser::new();
+        for threads in [1, 2, 6].iter() {
+            let err = parser
+                .parse_file_parallel("./tests/gfa2_files/parse_errors.gfa", *threads)
+                .unwrap_err();
+            assert_eq!(Some(2), err.line());
+        }
+        match parser.parse_file_parallel("./tests/gfa2_files/extension_error.txt", 2) {
+            Err(ParseError::ExtensionError(ext)) => assert_eq!("txt", ext),
+            _ => panic!("the extension .txt should be rejected"),
+        }
+    }
+}
@@ -0,0 +1,340 @@
+/// define a quick scan of a GFA file that reports which features the
+/// parser needs to handle it, before committing to a full parse
+use crate::{
+    gfa2::traits::encode_usize_id,
+    parser_gfa2::{error::ParseError, file::open_file, looks_like_tag},
+};
+
+use bstr::ByteSlice;
+use std::fmt;
+
+/// The version of the GFA format a file is written in
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GFAVersion {
+    GFA1,
+    GFA2,
+    /// Neither the header nor the line types tell the version.
+    Unknown,
+}
+
+impl GFAVersion {
+    /// Parses the value of a version tag, either the whole tag (e.g.
+    /// ```VN:Z:2.0```) or only its value (e.g. ```2.0```), looking only
+    /// at the major version (so ```1.2``` is still GFA1)
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// assert_eq!(GFAVersion::GFA2, GFAVersion::from_version(b"VN:Z:2.0"));
+    /// assert_eq!(GFAVersion::GFA1, GFAVersion::from_version(b"1.2"));
+    /// assert_eq!(GFAVersion::Unknown, GFAVersion::from_version(b"3.0"));
+    /// ```
+    pub fn from_version(version: &[u8]) -> Self {
+        // other tags can follow the version, separated by spaces
+        let version = version.fields().next().unwrap_or(b"");
+        let value = version.rsplit_str(b":").next().unwrap_or(b"");
+        match value.split_str(b".").next() {
+            Some(b"1") => GFAVersion::GFA1,
+            Some(b"2") => GFAVersion::GFA2,
+            _ => GFAVersion::Unknown,
+        }
+    }
+}
+
+impl fmt::Display for GFAVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GFAVersion::GFA1 => write!(f, "GFA1"),
+            GFAVersion::GFA2 => write!(f, "GFA2"),
+            GFAVersion::Unknown => write!(f, "unknown"),
+        }
+    }
+}
+
+/// What a quick scan of a file has found, to choose the parser to use
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileProfile {
+    /// The version declared in the header (the `VN` tag), or inferred
+    /// from the line types if there is no version.
+    pub version: GFAVersion,
+    /// At least one line has optional tags, so parsing with `()`
+    /// as the optional fields would drop them.
+    pub has_tags: bool,
+    /// At least one segment name can't be encoded as a `usize` id,
+    /// so parsing with `usize` ids would fail or panic.
+    pub usize_incompatible_ids: bool,
+    /// At least one byte of the file is not ASCII.
+    pub non_ascii: bool,
+}
+
+impl fmt::Display for FileProfile {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "version: {}", self.version)?;
+        writeln!(f, "optional tags: {}", self.has_tags)?;
+        writeln!(f, "usize incompatible ids: {}", self.usize_incompatible_ids)?;
+        write!(f, "non ascii bytes: {}", self.non_ascii)
+    }
+}
+
+/// checks if a segment name can be encoded as a `usize` id (the encoded
+/// ids always keep room for the orientation bit of their references)
+fn fits_usize(name: &[u8]) -> bool {
+    encode_usize_id(name).is_some()
+}
+
+/// Function that scans a GFA1 or GFA2 file line by line, without
+/// parsing it, and reports its version, whether it uses optional
+/// tags, segment names that can't be used as `usize` ids or non
+/// ASCII bytes.
+///
+/// # Examples
+///
+/// ```ignore
+/// use gfa2::parser_gfa2::analyze_file;
+///
+/// let profile = analyze_file("./tests/gfa2_files/spec_q7.gfa").unwrap();
+/// if profile.usize_incompatible_ids {
+///     // parse with BString ids
+/// }
+/// ```
+pub fn analyze_file<P: AsRef<std::path::Path>>(path: P) -> Result<FileProfile, ParseError> {
+    use bstr::io::BufReadExt;
+
+    let file = open_file(path)?;
+    let mut declared = None;
+    let mut inferred = GFAVersion::Unknown;
+    let mut has_tags = false;
+    // the segment lines have a different number of mandatory fields in
+    // the two versions, so they are checked once the version is known
+    let mut segment_fields = 0;
+    let mut usize_incompatible_ids = false;
+    let mut non_ascii = false;
+
+    for line in file.byte_lines() {
+        let line = line?;
+        non_ascii |= !line.is_ascii();
+
+        let mut fields = line.trim().split_str(b"\t");
+        let hdr = fields.next().unwrap_or(b"");
+        let fields: Vec<&[u8]> = fields.collect();
+        let mandatory = match hdr {
+            b"H" => {
+                for field in fields.iter() {
+                    match field.strip_prefix(b"VN:") {
+                        Some(version) if declared.is_none() => {
+                            declared = match GFAVersion::from_version(version) {
+                                GFAVersion::Unknown => None,
+                                version => Some(version),
+                            };
+                            // the version can be followed by other tags
+                            // separated by spaces instead of tabs
+                            has_tags |= version.contains(&b' ');
+                        }
+                        _ => has_tags = true,
+                    }
+                }
+                continue;
+            }
+            b"S" => {
+                segment_fields = segment_fields.max(fields.len());
+                if let Some(name) = fields.first() {
+                    usize_incompatible_ids |= !fits_usize(name);
+                }
+                continue;
+            }
+            b"L" | b"C" | b"P" | b"W" => {
+                inferred = GFAVersion::GFA1;
+                match hdr {
+                    b"L" => 5,
+                    b"C" => 6,
+                    b"P" => 3,
+                    _ => 6,
+                }
+            }
+            b"F" | b"E" | b"G" | b"O" | b"U" => {
+                inferred = GFAVersion::GFA2;
+                match hdr {
+                    b"F" => 7,
+                    b"E" => 8,
+                    b"G" => 5,
+                    _ => 2,
+                }
+            }
+            _ => continue,
+        };
+        has_tags |= fields.len() > mandatory;
+    }
+
+    let version = declared.unwrap_or(inferred);
+    has_tags |= match version {
+        GFAVersion::GFA1 => segment_fields > 2,
+        _ => segment_fields > 3,
+    };
+
+    Ok(FileProfile {
+        version,
+        has_tags,
+        usize_incompatible_ids,
+        non_ascii,
+    })
+}
+
+/// Function that reads a GFA1 or GFA2 file only up to the first line
+/// that tells its version: the version declared by an header, a line
+/// type that exists only in one of the versions or a segment (that has
+/// 2 mandatory fields in GFA1 and 3 in GFA2).\
+/// Returns Unknown if no line tells the version
+///
+/// # Examples
+///
+/// ```ignore
+/// use gfa2::parser_gfa2::{detect_version, GFAVersion};
+///
+/// match detect_version("./tests/gfa1_files/lil.gfa").unwrap() {
+///     GFAVersion::GFA1 => { /* parse with GFAParser */ }
+///     _ => { /* parse with GFA2Parser */ }
+/// }
+/// ```
+pub fn detect_version<P: AsRef<std::path::Path>>(path: P) -> Result<GFAVersion, ParseError> {
+    use bstr::io::BufReadExt;
+
+    let mut version = GFAVersion::Unknown;
+    open_file(path)?.for_byte_line(|line| {
+        let mut fields = line.trim().split_str(b"\t");
+        version = match fields.next().unwrap_or(b"") {
+            b"H" => match fields.find_map(|field| field.strip_prefix(b"VN:")) {
+                Some(declared) => GFAVersion::from_version(declared),
+                None => GFAVersion::Unknown,
+            },
+            b"S" => match fields.nth(2) {
+                Some(field) if !looks_like_tag(field) => GFAVersion::GFA2,
+                _ => GFAVersion::GFA1,
+            },
+            b"L" | b"C" | b"P" | b"W" => GFAVersion::GFA1,
+            b"F" | b"E" | b"G" | b"O" | b"U" => GFAVersion::GFA2,
+            _ => GFAVersion::Unknown,
+        };
+        // keep reading until a line tells the version
+        Ok(version == GFAVersion::Unknown)
+    })?;
+    Ok(version)
+}
+
+/// Function that counts the lines of a file without parsing them, for
+/// example to know the total of a progress bar before the parsing.\
+/// Only the ```\n``` bytes are counted, plus one for a last line that
+/// doesn't end with a newline (of the decompressed file, if it's a
+/// gzip file)
+///
+/// # Examples
+///
+/// ```ignore
+/// use gfa2::parser_gfa2::count_file_lines;
+///
+/// let total = count_file_lines("./tests/gfa2_files/big.gfa").unwrap();
+/// ```
+pub fn count_file_lines<P: AsRef<std::path::Path>>(path: P) -> Result<u64, ParseError> {
+    use std::io::BufRead;
+
+    let mut file = open_file(path)?;
+    let mut lines = 0;
+    let mut last = b'\n';
+
+    loop {
+        let buffer = match file.fill_buf() {
+            Ok([]) => break,
+            Ok(buffer) => buffer,
+            Err(err) if err.kind() == std::io::ErrorKind::Interrupted => continue,
+            Err(err) => return Err(err.into()),
+        };
+        lines += buffer.iter().filter(|&&b| b == b'\n').count() as u64;
+        last = buffer[buffer.len() - 1];
+        let read = buffer.len();
+        file.consume(read);
+    }
+
+    if last != b'\n' {
+        lines += 1;
+    }
+    Ok(lines)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn can_analyze_file() {
+        let profile = analyze_file("./tests/gfa2_files/spec_q7.gfa").unwrap();
+        assert_eq!(
+            FileProfile {
+                version: GFAVersion::GFA2,
+                has_tags: true,
+                usize_incompatible_ids: false,
+                non_ascii: false,
+            },
+            profile
+        );
+
+        let profile = analyze_file("./tests/gfa1_files/lil.gfa").unwrap();
+        assert_eq!(GFAVersion::GFA1, profile.version);
+        assert!(!profile.usize_incompatible_ids);
+    }
+
+    #[test]
+    fn can_parse_version() {
+        assert_eq!(GFAVersion::GFA2, GFAVersion::from_version(b"VN:Z:2.0"));
+        assert_eq!(GFAVersion::GFA2, GFAVersion::from_version(b"2.0"));
+        assert_eq!(GFAVersion::GFA2, GFAVersion::from_version(b"Z:2.0 TS:i:15"));
+        assert_eq!(GFAVersion::GFA1, GFAVersion::from_version(b"VN:Z:1.2"));
+        assert_eq!(GFAVersion::GFA1, GFAVersion::from_version(b"1"));
+        assert_eq!(GFAVersion::Unknown, GFAVersion::from_version(b"VN:Z:10.0"));
+        assert_eq!(GFAVersion::Unknown, GFAVersion::from_version(b""));
+    }
+
+    #[test]
+    fn long_names_are_usize_incompatible() {
+        assert!(fits_usize(b"s148227"));
+        assert!(fits_usize(b"utg000001l"));
+        assert!(!fits_usize(b"utg000001l_ctg"));
+        assert!(!fits_usize(b"seg-1"));
+        assert!(!fits_usize("sé".as_bytes()));
+    }
+
+    #[test]
+    fn can_count_lines() {
+        assert_eq!(
+            9,
+            count_file_lines("./tests/gfa2_files/invalid.gfa").unwrap()
+        );
+        // the last line doesn't end with a newline
+        assert_eq!(
+            9,
+            count_file_lines("./tests/gfa2_files/spec_q7.gfa").unwrap()
+        );
+        assert!(matches!(
+            count_file_lines("./tests/gfa2_files/extension_error.txt"),
+            Err(ParseError::ExtensionError(_))
+        ));
+    }
+
+    #[test]
+    fn can_detect_version() {
+        let version = |path| detect_version(path).unwrap();
+        assert_eq!(GFAVersion::GFA2, version("./tests/gfa2_files/spec_q7.gfa"));
+        assert_eq!(GFAVersion::GFA2, version("./tests/gfa2_files/sample2.gfa"));
+        assert_eq!(GFAVersion::GFA1, version("./tests/gfa1_files/lil.gfa"));
+        assert_eq!(GFAVersion::GFA1, version("./tests/gfa1_files/diatom.gfa"));
+        assert!(detect_version("./tests/gfa2_files/extension_error.txt").is_err());
+    }
+
+    #[test]
+    fn missing_file() {
+        assert!(analyze_file("./tests/gfa2_files/not_a_file.gfa").is_err());
+        assert!(matches!(
+            analyze_file("./tests/gfa2_files/extension_error.txt"),
+            Err(ParseError::ExtensionError(_))
+        ));
+        assert!(count_file_lines("./tests/gfa2_files/not_a_file.gfa").is_err());
+    }
+}
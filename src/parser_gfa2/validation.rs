@@ -0,0 +1,1023 @@
+/// define the problems that can be found when validating a GFA2 file
+/// or object (or the references of a GFA1 object), beyond the ones
+/// that stop the parsing
+use crate::{
+    gfa1::GFA,
+    gfa2::*,
+    parser_gfa2::{error::ParseError, file::open_file, GFA2Parser},
+    tag::*,
+};
+
+use bstr::{BString, ByteSlice};
+use std::collections::{HashMap, HashSet};
+use std::{fmt, hash::Hash};
+
+/// Enum containing the different kinds of problems that a validator can find
+#[derive(Debug, Clone, PartialEq)]
+pub enum ValidationIssue {
+    /// The file couldn't be read. Includes the IO error message.
+    Unreadable(String),
+    /// A line couldn't be parsed. Includes the parser error message.
+    InvalidLine(String),
+    /// A segment id has already been used by another segment.
+    DuplicateSegment(BString),
+    /// A line references an id that isn't declared anywhere.
+    DanglingReference(BString),
+    /// A position couldn't be parsed or it's outside of its segment.
+    InvalidPosition { segment: BString, position: BString },
+    /// A position has the final `$` char but it's not the end of its
+    /// segment, or viceversa.
+    MisplacedEndMarker { segment: BString, position: BString },
+    /// The begin position of an interval is greater than the end one.
+    InvalidInterval {
+        segment: BString,
+        beg: BString,
+        end: BString,
+    },
+    /// The length of a segment differs from the length of its
+    /// sequence (not checked if the length is `*`).
+    LengthMismatch {
+        segment: BString,
+        len: BString,
+        sequence_len: usize,
+    },
+    /// An O-group or U-group id is already used by a segment (if
+    /// `with_segment` is true) or by another group.
+    GroupIdCollision { id: BString, with_segment: bool },
+    /// A line of a GFA1 or GFA2 object references an id that isn't
+    /// declared in the graph. Includes the kind of line (e.g. `Edge`),
+    /// its position among the lines of the same kind and the id.
+    UndeclaredReference {
+        line_type: &'static str,
+        index: usize,
+        id: BString,
+    },
+}
+
+impl fmt::Display for ValidationIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        use ValidationIssue as VI;
+        match self {
+            VI::Unreadable(err) => write!(f, "Failed to read the file: {}", err),
+            VI::InvalidLine(err) => write!(f, "{}", err),
+            VI::DuplicateSegment(id) => write!(f, "Segment `{}` is declared more than once", id),
+            VI::DanglingReference(id) => write!(f, "Reference to an undeclared id `{}`", id),
+            VI::InvalidPosition { segment, position } => write!(
+                f,
+                "Position `{}` is not valid for segment `{}`",
+                position, segment
+            ),
+            VI::MisplacedEndMarker { segment, position } => write!(
+                f,
+                "Position `{}` has a misplaced `$` char for segment `{}`",
+                position, segment
+            ),
+            VI::InvalidInterval { segment, beg, end } => write!(
+                f,
+                "Interval `{}..{}` of segment `{}` ends before it begins",
+                beg, end, segment
+            ),
+            VI::LengthMismatch {
+                segment,
+                len,
+                sequence_len,
+            } => write!(
+                f,
+                "Segment `{}` has length {} but its sequence is {} long",
+                segment, len, sequence_len
+            ),
+            VI::GroupIdCollision { id, with_segment } => {
+                let other = if *with_segment {
+                    "a segment"
+                } else {
+                    "another group"
+                };
+                write!(f, "Group `{}` has the same id of {}", id, other)
+            }
+            VI::UndeclaredReference {
+                line_type,
+                index,
+                id,
+            } => write!(
+                f,
+                "{} {}: Reference to an undeclared id `{}`",
+                line_type, index, id
+            ),
+        }
+    }
+}
+
+/// A problem found by a validator, with the 1-based number of the
+/// line where it has been found (0 if it's not related to a line)
+#[derive(Debug, Clone, PartialEq)]
+pub struct ValidationError {
+    pub line: usize,
+    pub issue: ValidationIssue,
+}
+
+impl ValidationError {
+    fn new(line: usize, issue: ValidationIssue) -> Self {
+        ValidationError { line, issue }
+    }
+}
+
+impl fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.issue)
+    }
+}
+
+/// A reference to a segment that is declared only later in the file
+#[derive(Debug, Clone, PartialEq)]
+pub struct OrderError {
+    /// The 1-based number of the line with the reference.
+    pub line: usize,
+    /// The referenced segment.
+    pub segment: BString,
+    /// The 1-based number of the line where the segment is declared.
+    pub declared_at: usize,
+}
+
+impl fmt::Display for OrderError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "line {}: Segment `{}` is referenced before its declaration at line {}",
+            self.line, self.segment, self.declared_at
+        )
+    }
+}
+
+/// A field of a GFA2 object that is not valid UTF-8, so it can't be
+/// exported as a JSON string
+#[derive(Debug, Clone, PartialEq)]
+pub struct Utf8Error {
+    /// The kind of line with the field (e.g. `Segment`).
+    pub line_type: &'static str,
+    /// The position of the line among the ones of the same kind.
+    pub index: usize,
+    /// The name of the field, or the tag of an optional field.
+    pub field: String,
+    /// The length of the valid UTF-8 prefix of the field.
+    pub valid_up_to: usize,
+}
+
+impl fmt::Display for Utf8Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} {}: field `{}` is not valid UTF-8 after byte {}",
+            self.line_type, self.index, self.field, self.valid_up_to
+        )
+    }
+}
+
+/// checks that every field of a line is valid UTF-8, pushing an error
+/// for each one that is not
+fn check_utf8<'a, I>(
+    errors: &mut Vec<Utf8Error>,
+    line_type: &'static str,
+    index: usize,
+    fields: I,
+    tags: &[OptField],
+) where
+    I: IntoIterator<Item = (&'static str, &'a [u8])>,
+{
+    let fields = fields
+        .into_iter()
+        .map(|(name, bytes)| (name.to_string(), bytes));
+    let tags = tags
+        .iter()
+        .map(|tag| (tag.tag.as_bstr().to_string(), tag.value_bytes()));
+    for (field, bytes) in fields.chain(tags) {
+        if let Err(err) = std::str::from_utf8(bytes) {
+            errors.push(Utf8Error {
+                line_type,
+                index,
+                field,
+                valid_up_to: err.valid_up_to(),
+            });
+        }
+    }
+}
+
+impl<T: OptFields> GFA2<BString, T> {
+    /// Returns every field (optional fields included) that is not valid
+    /// UTF-8, with the line where it has been found, to clean them before
+    /// exporting the graph as JSON.\
+    /// The usize ids are always valid, so only the graphs with BString
+    /// ids can be checked
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// let parser: GFA2Parser<BString, OptionalFields> = GFA2Parser::new();
+    /// let gfa2 = parser.parse_file("./tests/gfa2_files/big.gfa").unwrap();
+    ///
+    /// for error in gfa2.validate_utf8() {
+    ///     println!("{}", error);
+    /// }
+    /// ```
+    pub fn validate_utf8(&self) -> Vec<Utf8Error> {
+        let mut errors = vec![];
+        for (i, h) in self.headers.iter().enumerate() {
+            let version = h.version.as_ref().map(|v| ("version", v.as_bytes()));
+            check_utf8(&mut errors, "Header", i, version, h.tag.fields());
+        }
+        for (i, s) in self.segments.iter().enumerate() {
+            let sequence = s.sequence_bytes();
+            let fields = vec![
+                ("id", s.id.as_bytes()),
+                ("len", s.len.as_bytes()),
+                ("sequence", sequence.as_ref()),
+            ];
+            check_utf8(&mut errors, "Segment", i, fields, s.tag.fields());
+        }
+        for (i, f) in self.fragments.iter().enumerate() {
+            let fields = vec![
+                ("id", f.id.as_bytes()),
+                ("ext_ref", f.ext_ref.as_bytes()),
+                ("alignment", f.alignment.as_bytes()),
+            ];
+            check_utf8(&mut errors, "Fragment", i, fields, f.tag.fields());
+        }
+        for (i, e) in self.edges.iter().enumerate() {
+            let fields = vec![
+                ("id", e.id.as_bytes()),
+                ("sid1", e.sid1.as_bytes()),
+                ("sid2", e.sid2.as_bytes()),
+                ("alignment", e.alignment.as_bytes()),
+            ];
+            check_utf8(&mut errors, "Edge", i, fields, e.tag.fields());
+        }
+        for (i, g) in self.gaps.iter().enumerate() {
+            let fields = vec![
+                ("id", g.id.as_bytes()),
+                ("sid1", g.sid1.as_bytes()),
+                ("sid2", g.sid2.as_bytes()),
+            ];
+            check_utf8(&mut errors, "Gap", i, fields, g.tag.fields());
+        }
+        for (i, o) in self.groups_o.iter().enumerate() {
+            let fields = vec![
+                ("id", o.id.as_bytes()),
+                ("var_field", o.var_field.as_bytes()),
+            ];
+            check_utf8(&mut errors, "O-Group", i, fields, o.tag.fields());
+        }
+        for (i, u) in self.groups_u.iter().enumerate() {
+            let fields = vec![
+                ("id", u.id.as_bytes()),
+                ("var_field", u.var_field.as_bytes()),
+            ];
+            check_utf8(&mut errors, "U-Group", i, fields, u.tag.fields());
+        }
+        errors
+    }
+}
+
+impl<N: SegmentId, T: OptFields> GFA2<N, T> {
+    /// Returns every segment whose length differs from the length of
+    /// its sequence, with its index in the segments of the graph.\
+    /// The segments with a ```*``` sequence or a ```*``` length are
+    /// not checked
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// let parser: GFA2Parser<BString, ()> = GFA2Parser::new();
+    /// let gfa2 = parser.parse_file("./tests/gfa2_files/unknown_length.gfa").unwrap();
+    ///
+    /// for (index, issue) in gfa2.validate_segment_lengths() {
+    ///     println!("segment {}: {}", index, issue);
+    /// }
+    /// ```
+    pub fn validate_segment_lengths(&self) -> Vec<(usize, ValidationIssue)> {
+        self.segments
+            .iter()
+            .enumerate()
+            .filter_map(|(i, s)| s.length_mismatch().map(|issue| (i, issue)))
+            .collect()
+    }
+}
+
+impl<N: SegmentId, T: OptFields> Segment<N, T> {
+    /// checks that the length of the segment is the length of its
+    /// sequence (a ```*``` sequence or a ```*``` length is not checked)
+    fn length_mismatch(&self) -> Option<ValidationIssue> {
+        let sequence = self.known_sequence().filter(|_| self.len != "*")?;
+        match self.length() {
+            Some(len) if len as usize == sequence.len() => None,
+            _ => Some(ValidationIssue::LengthMismatch {
+                segment: self.id.to_name().into(),
+                len: self.len.clone(),
+                sequence_len: sequence.len(),
+            }),
+        }
+    }
+}
+
+impl<N, T> GFA2<N, T>
+where
+    N: SegmentId + Clone + Eq + Hash,
+    T: OptFields,
+{
+    /// Returns every reference to an id that isn't declared in the graph,
+    /// with the kind of line and its index among the lines of that kind.\
+    /// The fragments and the segment references of the edges and gaps
+    /// have to point to a segment, while the members of a group can also
+    /// be the id of an edge, a gap or another group
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// let parser: GFA2Parser<BString, ()> = GFA2Parser::new();
+    /// let gfa2 = parser.parse_file("./tests/gfa2_files/sample2.gfa").unwrap();
+    ///
+    /// for error in gfa2.validate_references() {
+    ///     println!("{}", error);
+    /// }
+    /// ```
+    pub fn validate_references(&self) -> Vec<ValidationIssue> {
+        let segments: HashSet<&N> = self.segments.iter().map(|s| &s.id).collect();
+        let mut names: HashSet<N> = HashSet::new();
+        names.extend(self.edges.iter().map(|e| e.id.clone()));
+        names.extend(self.gaps.iter().map(|g| g.id.clone()));
+        let group_ids = self.groups_o.iter().map(|o| &o.id);
+        let group_ids = group_ids.chain(self.groups_u.iter().map(|u| &u.id));
+        names.extend(group_ids.filter_map(|id| N::parse_opt_id(id)));
+        names.retain(|id| !id.is_anonymous());
+
+        let mut errors = vec![];
+        let mut check = |line_type, index, id: &N, declared: bool| {
+            if !declared {
+                errors.push(ValidationIssue::UndeclaredReference {
+                    line_type,
+                    index,
+                    id: id.to_name().into(),
+                });
+            }
+        };
+        for (i, f) in self.fragments.iter().enumerate() {
+            check("Fragment", i, &f.id, segments.contains(&f.id));
+        }
+        let refs = self.edges.iter().map(|e| ("Edge", [&e.sid1, &e.sid2]));
+        let gap_refs = self.gaps.iter().map(|g| ("Gap", [&g.sid1, &g.sid2]));
+        let mut indices: HashMap<&str, usize> = HashMap::new();
+        for (line_type, sids) in refs.chain(gap_refs) {
+            let index = indices.entry(line_type).or_insert(0);
+            for sid in sids.iter() {
+                if let Some((id, _)) = sid.split_ref() {
+                    check(line_type, *index, &id, segments.contains(&id));
+                }
+            }
+            *index += 1;
+        }
+        let is_declared = |id: &N| segments.contains(id) || names.contains(id);
+        for (i, o) in self.groups_o.iter().enumerate() {
+            for member in o.members() {
+                if let Some(id) = member.split_last().and_then(|(_, id)| N::parse_id(id)) {
+                    check("O-Group", i, &id, is_declared(&id));
+                }
+            }
+        }
+        for (i, u) in self.groups_u.iter().enumerate() {
+            for member in u.members() {
+                if let Some(id) = N::parse_opt_id(member) {
+                    check("U-Group", i, &id, is_declared(&id));
+                }
+            }
+        }
+        errors
+    }
+}
+
+impl<N, T> GFA<N, T>
+where
+    N: SegmentId + Eq + Hash,
+    T: OptFields,
+{
+    /// Returns every reference of the links, containments and paths to
+    /// a segment that isn't declared in the graph, with the kind of line
+    /// and its index among the lines of that kind
+    pub fn validate_references(&self) -> Vec<ValidationIssue> {
+        let segments: HashSet<&N> = self.segments.iter().map(|s| &s.name).collect();
+        let mut errors = vec![];
+        let mut check = |line_type, index, id: &N| {
+            if !segments.contains(id) {
+                errors.push(ValidationIssue::UndeclaredReference {
+                    line_type,
+                    index,
+                    id: id.to_name().into(),
+                });
+            }
+        };
+        for (i, l) in self.links.iter().enumerate() {
+            check("Link", i, &l.from_segment);
+            check("Link", i, &l.to_segment);
+        }
+        for (i, c) in self.containments.iter().enumerate() {
+            check("Containment", i, &c.container_name);
+            check("Containment", i, &c.contained_name);
+        }
+        for (i, p) in self.paths.iter().enumerate() {
+            for member in p.segment_names.split_str(b",") {
+                if let Some(id) = member.split_last().and_then(|(_, id)| N::parse_id(id)) {
+                    check("Path", i, &id);
+                }
+            }
+        }
+        errors
+    }
+}
+
+/// checks that an interval of positions lies on a segment of length `len`
+/// (if known), pushing the problems found into `errors`
+fn check_interval<N: SegmentId>(
+    errors: &mut Vec<ValidationError>,
+    line: usize,
+    segment: &N,
+    len: Option<usize>,
+    beg: &BString,
+    end: &BString,
+) {
+    let segment = BString::from(segment.to_name());
+    let mut check_position = |position: &BString| {
+        let (pos, is_end) = match parse_position(position) {
+            Some(pos) => pos,
+            None => {
+                errors.push(ValidationError::new(
+                    line,
+                    ValidationIssue::InvalidPosition {
+                        segment: segment.clone(),
+                        position: position.clone(),
+                    },
+                ));
+                return None;
+            }
+        };
+        if let Some(len) = len {
+            if pos > len {
+                errors.push(ValidationError::new(
+                    line,
+                    ValidationIssue::InvalidPosition {
+                        segment: segment.clone(),
+                        position: position.clone(),
+                    },
+                ));
+            } else if is_end != (pos == len) {
+                errors.push(ValidationError::new(
+                    line,
+                    ValidationIssue::MisplacedEndMarker {
+                        segment: segment.clone(),
+                        position: position.clone(),
+                    },
+                ));
+            }
+        }
+        Some(pos)
+    };
+
+    if let (Some(b), Some(e)) = (check_position(beg), check_position(end)) {
+        if b > e {
+            errors.push(ValidationError::new(
+                line,
+                ValidationIssue::InvalidInterval {
+                    segment,
+                    beg: beg.clone(),
+                    end: end.clone(),
+                },
+            ));
+        }
+    }
+}
+
+impl<N, T> GFA2Parser<N, T>
+where
+    N: SegmentId + Clone + Eq + Hash,
+    T: OptFields,
+{
+    /// Function that validates a GFA2 file without storing the entire
+    /// graph, returning every problem found together with its line number.\
+    /// The file is read twice: the first pass collects the declared ids
+    /// and the length of each segment, the second one checks that every
+    /// reference points to a declared id and that every position lies on
+    /// its segment.\
+    /// The id of a group is also checked to not be used by a segment or by
+    /// another group, because a U-group member could refer to both of them.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// use gfa2::parser_gfa2::GFA2Parser;
+    ///
+    /// let parser: GFA2Parser<BString, ()> = GFA2Parser::new();
+    /// for error in parser.validate_file("./tests/gfa2_files/sample2.gfa") {
+    ///     println!("{}", error);
+    /// }
+    /// ```
+    pub fn validate_file<P: AsRef<std::path::Path>>(&self, path: P) -> Vec<ValidationError> {
+        use bstr::io::BufReadExt;
+
+        let mut errors = vec![];
+        let mut segments: HashMap<N, Option<usize>> = HashMap::new();
+        // ids that are not segment ids but can still be referenced by a group
+        let mut names: HashSet<N> = HashSet::new();
+        // the line of each group, to report the collisions with a segment
+        // declared after the group
+        let mut groups: HashMap<N, usize> = HashMap::new();
+        let collision = |id: &N, with_segment| ValidationIssue::GroupIdCollision {
+            id: id.to_name().into(),
+            with_segment,
+        };
+
+        let read_lines =
+            || -> Result<_, ParseError> { Ok(open_file(path.as_ref())?.byte_lines().enumerate()) };
+
+        // first pass: collect the declared ids
+        let lines = match read_lines() {
+            Ok(lines) => lines,
+            Err(err) => {
+                let issue = ValidationIssue::Unreadable(err.to_string());
+                return vec![ValidationError::new(0, issue)];
+            }
+        };
+        for (index, line) in lines {
+            let line = match line {
+                Ok(line) => line,
+                Err(err) => {
+                    let issue = ValidationIssue::Unreadable(err.to_string());
+                    errors.push(ValidationError::new(index + 1, issue));
+                    return errors;
+                }
+            };
+            match self.parse_gfa_line(line.as_ref()) {
+                Ok(Line::Segment(s)) => {
+                    // an unknown length (*) skips every check on the length
+                    let len = s.length().map(|len| len as usize);
+                    if let Some(issue) = s.length_mismatch() {
+                        errors.push(ValidationError::new(index + 1, issue));
+                    }
+                    if let Some(line) = groups.get(&s.id) {
+                        errors.push(ValidationError::new(*line, collision(&s.id, true)));
+                    }
+                    if segments.insert(s.id.clone(), len).is_some() {
+                        let issue = ValidationIssue::DuplicateSegment(s.id.to_name().into());
+                        errors.push(ValidationError::new(index + 1, issue));
+                    }
+                }
+                Ok(Line::Edge(e)) => {
+                    names.insert(e.id);
+                }
+                Ok(Line::Gap(g)) => {
+                    names.insert(g.id);
+                }
+                Ok(Line::GroupO(GroupO { id, .. })) | Ok(Line::GroupU(GroupU { id, .. })) => {
+                    let id = match N::parse_opt_id(&id) {
+                        Some(id) if !id.is_anonymous() => id,
+                        _ => continue,
+                    };
+                    if segments.contains_key(&id) {
+                        errors.push(ValidationError::new(index + 1, collision(&id, true)));
+                    } else if groups.contains_key(&id) {
+                        errors.push(ValidationError::new(index + 1, collision(&id, false)));
+                    } else {
+                        groups.insert(id.clone(), index + 1);
+                    }
+                    names.insert(id);
+                }
+                _ => (),
+            }
+        }
+
+        // second pass: check references and positions
+        let lines = match read_lines() {
+            Ok(lines) => lines,
+            Err(err) => {
+                let issue = ValidationIssue::Unreadable(err.to_string());
+                errors.push(ValidationError::new(0, issue));
+                return errors;
+            }
+        };
+        let is_declared = |id: &N| segments.contains_key(id) || names.contains(id);
+        let dangling = |id: &N| ValidationIssue::DanglingReference(id.to_name().into());
+
+        for (index, line) in lines {
+            let line_no = index + 1;
+            let line = match line {
+                Ok(line) => line,
+                Err(err) => {
+                    let issue = ValidationIssue::Unreadable(err.to_string());
+                    errors.push(ValidationError::new(line_no, issue));
+                    break;
+                }
+            };
+            let parsed = match self.parse_gfa_line(line.as_ref()) {
+                Ok(parsed) => parsed,
+                Err(err) if err.can_safely_continue(&self.tolerance) => continue,
+                Err(err) => {
+                    let issue = ValidationIssue::InvalidLine(err.to_string());
+                    errors.push(ValidationError::new(line_no, issue));
+                    continue;
+                }
+            };
+
+            match parsed {
+                Line::Fragment(f) => match segments.get(&f.id) {
+                    Some(len) => {
+                        check_interval(&mut errors, line_no, &f.id, *len, &f.sbeg, &f.send)
+                    }
+                    None => errors.push(ValidationError::new(line_no, dangling(&f.id))),
+                },
+                Line::Edge(e) => {
+                    let ends = [(&e.sid1, &e.beg1, &e.end1), (&e.sid2, &e.beg2, &e.end2)];
+                    for (sid, beg, end) in ends.iter() {
+                        let id = match sid.split_ref() {
+                            Some((id, _)) => id,
+                            None => continue,
+                        };
+                        match segments.get(&id) {
+                            Some(len) => check_interval(&mut errors, line_no, &id, *len, beg, end),
+                            None => errors.push(ValidationError::new(line_no, dangling(&id))),
+                        }
+                    }
+                }
+                Line::Gap(g) => {
+                    for sid in [&g.sid1, &g.sid2].iter() {
+                        if let Some((id, _)) = sid.split_ref() {
+                            if !segments.contains_key(&id) {
+                                errors.push(ValidationError::new(line_no, dangling(&id)));
+                            }
+                        }
+                    }
+                }
+                Line::GroupO(o) => {
+                    for member in o.members() {
+                        let id = member.split_last().and_then(|(_, id)| N::parse_id(id));
+                        match id {
+                            Some(id) if !is_declared(&id) => {
+                                errors.push(ValidationError::new(line_no, dangling(&id)))
+                            }
+                            _ => (),
+                        }
+                    }
+                }
+                Line::GroupU(u) => {
+                    for member in u.members() {
+                        match N::parse_opt_id(member) {
+                            Some(id) if !is_declared(&id) => {
+                                errors.push(ValidationError::new(line_no, dangling(&id)))
+                            }
+                            _ => (),
+                        }
+                    }
+                }
+                _ => (),
+            }
+        }
+
+        errors.sort_by_key(|err| err.line);
+        errors
+    }
+
+    /// Function that reads a GFA2 file once, and reports every fragment,
+    /// edge or gap that references a segment declared later in the file.\
+    /// The parser doesn't depend on the order of the lines, but some tools
+    /// read the files in a single pass and need the segments first.\
+    /// The references to segments that are never declared are not
+    /// reported (see validate_file).
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// use gfa2::parser_gfa2::GFA2Parser;
+    ///
+    /// let parser: GFA2Parser<BString, ()> = GFA2Parser::new();
+    /// for error in parser.check_declaration_order("./tests/gfa2_files/sample2.gfa")? {
+    ///     println!("{}", error);
+    /// }
+    /// ```
+    pub fn check_declaration_order<P: AsRef<std::path::Path>>(
+        &self,
+        path: P,
+    ) -> Result<Vec<OrderError>, ParseError> {
+        use bstr::io::BufReadExt;
+
+        let file = open_file(path)?;
+        let mut declared: HashMap<N, usize> = HashMap::new();
+        // the references to segments not declared yet, with their line
+        let mut pending: Vec<(usize, N)> = vec![];
+
+        for (index, line) in file.byte_lines().enumerate() {
+            let line_no = index + 1;
+            let line = line?;
+            let refs = match self.parse_gfa_line(line.as_ref()) {
+                Ok(Line::Segment(s)) => {
+                    declared.entry(s.id).or_insert(line_no);
+                    continue;
+                }
+                Ok(Line::Fragment(f)) => vec![f.id],
+                Ok(Line::Edge(e)) => vec![e.sid1, e.sid2]
+                    .into_iter()
+                    .filter_map(|sid| sid.split_ref().map(|(id, _)| id))
+                    .collect(),
+                Ok(Line::Gap(g)) => vec![g.sid1, g.sid2]
+                    .into_iter()
+                    .filter_map(|sid| sid.split_ref().map(|(id, _)| id))
+                    .collect(),
+                _ => continue,
+            };
+            for id in refs {
+                if !declared.contains_key(&id) {
+                    pending.push((line_no, id));
+                }
+            }
+        }
+
+        Ok(pending
+            .into_iter()
+            .filter_map(|(line, id)| {
+                declared.get(&id).map(|declared_at| OrderError {
+                    line,
+                    segment: id.to_name().into(),
+                    declared_at: *declared_at,
+                })
+            })
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn valid_file_has_no_errors() {
+        let parser: GFA2Parser<BString, ()> = GFA2Parser::new();
+        let errors = parser.validate_file("./tests/gfa2_files/spec_q7.gfa");
+        assert!(errors.is_empty());
+    }
+
+    #[test]
+    fn can_validate_file() {
+        let parser: GFA2Parser<BString, ()> = GFA2Parser::new();
+        let errors = parser.validate_file("./tests/gfa2_files/invalid.gfa");
+
+        let segment = BString::from("1");
+        assert_eq!(
+            errors,
+            vec![
+                ValidationError::new(4, ValidationIssue::DuplicateSegment("2".into())),
+                ValidationError::new(5, ValidationIssue::DanglingReference("3".into())),
+                ValidationError::new(
+                    6,
+                    ValidationIssue::MisplacedEndMarker {
+                        segment: segment.clone(),
+                        position: "8".into(),
+                    }
+                ),
+                ValidationError::new(
+                    7,
+                    ValidationIssue::InvalidPosition {
+                        segment: segment.clone(),
+                        position: "12$".into(),
+                    }
+                ),
+                ValidationError::new(
+                    8,
+                    ValidationIssue::InvalidInterval {
+                        segment,
+                        beg: "6".into(),
+                        end: "2".into(),
+                    }
+                ),
+                ValidationError::new(9, ValidationIssue::DanglingReference("4".into())),
+            ]
+        );
+    }
+
+    #[test]
+    fn can_validate_file_usize() {
+        let parser: GFA2Parser<usize, ()> = GFA2Parser::new();
+        let errors = parser.validate_file("./tests/gfa2_files/invalid.gfa");
+        assert_eq!(6, errors.len());
+    }
+
+    #[test]
+    fn unknown_length_is_not_checked() {
+        let parser: GFA2Parser<BString, ()> = GFA2Parser::new();
+        let errors = parser.validate_file("./tests/gfa2_files/unknown_length.gfa");
+        assert_eq!(
+            vec![ValidationError::new(
+                3,
+                ValidationIssue::LengthMismatch {
+                    segment: "2".into(),
+                    len: "5".into(),
+                    sequence_len: 4,
+                }
+            )],
+            errors
+        );
+    }
+
+    #[test]
+    fn can_validate_segment_lengths() {
+        let parser: GFA2Parser<BString, ()> = GFA2Parser::new();
+        let gfa2 = parser
+            .parse_file("./tests/gfa2_files/unknown_length.gfa")
+            .unwrap();
+        assert_eq!(
+            vec![(
+                1,
+                ValidationIssue::LengthMismatch {
+                    segment: "2".into(),
+                    len: "5".into(),
+                    sequence_len: 4,
+                }
+            )],
+            gfa2.validate_segment_lengths()
+        );
+
+        let gfa2 = parser.parse_file("./tests/gfa2_files/data.gfa").unwrap();
+        assert!(gfa2.validate_segment_lengths().is_empty());
+    }
+
+    #[test]
+    fn group_ids_collisions() {
+        let parser: GFA2Parser<BString, ()> = GFA2Parser::new();
+        let errors = parser.validate_file("./tests/gfa2_files/group_collision.gfa");
+        let collision = |id: &str, with_segment| ValidationIssue::GroupIdCollision {
+            id: id.into(),
+            with_segment,
+        };
+        assert_eq!(
+            vec![
+                ValidationError::new(4, collision("1", true)),
+                ValidationError::new(6, collision("g1", false)),
+                ValidationError::new(7, collision("3", true)),
+            ],
+            errors
+        );
+
+        // the usize ids are reported with their names
+        let parser: GFA2Parser<usize, ()> = GFA2Parser::new();
+        let errors = parser.validate_file("./tests/gfa2_files/group_collision.gfa");
+        assert_eq!(
+            vec![
+                ValidationError::new(4, collision("1", true)),
+                ValidationError::new(6, collision("g1", false)),
+                ValidationError::new(7, collision("3", true)),
+            ],
+            errors
+        );
+    }
+
+    #[test]
+    fn can_validate_references() {
+        let parser: GFA2Parser<BString, ()> = GFA2Parser::new();
+        let gfa2 = parser.parse_file("./tests/gfa2_files/sample2.gfa").unwrap();
+        assert!(gfa2.validate_references().is_empty());
+
+        let lines = [
+            "S\t1\t4\tACGT",
+            "S\t2\t4\tACGT",
+            "F\t3\tr1+\t0\t2\t0\t2\t*",
+            "E\te1\t1+\t2-\t0\t2\t0\t2\t*",
+            "E\t*\t1+\t4-\t0\t2\t0\t2\t*",
+            "G\t*\t5+\t2-\t10\t*",
+            "O\tP1\t1+ e1+ 6-",
+            "U\t*\tP1 2 7",
+        ];
+        let gfa2: GFA2<BString, ()> = parser.parse_lines(lines.iter()).unwrap();
+        let error = |line_type, index, id: &str| ValidationIssue::UndeclaredReference {
+            line_type,
+            index,
+            id: id.into(),
+        };
+        assert_eq!(
+            vec![
+                error("Fragment", 0, "3"),
+                error("Edge", 1, "4"),
+                error("Gap", 0, "5"),
+                error("O-Group", 0, "6"),
+                error("U-Group", 0, "7"),
+            ],
+            gfa2.validate_references()
+        );
+        assert_eq!(
+            "Edge 1: Reference to an undeclared id `4`",
+            gfa2.validate_references()[1].to_string()
+        );
+    }
+
+    #[test]
+    fn can_validate_gfa_references() {
+        use crate::parser_gfa1::GFAParser;
+
+        let parser: GFAParser<BString, ()> = GFAParser::new();
+        let gfa = parser.parse_file("./tests/gfa1_files/lil.gfa").unwrap();
+        assert!(gfa.validate_references().is_empty());
+
+        let lines = [
+            "S\t11\tACCTT",
+            "L\t11\t+\t12\t-\t4M",
+            "C\t13\t+\t11\t-\t0\t*",
+            "P\t14\t11+,15-\t*",
+        ];
+        let gfa: GFA<BString, ()> = parser.parse_lines(lines.iter()).unwrap();
+        let ids: Vec<_> = gfa
+            .validate_references()
+            .into_iter()
+            .filter_map(|issue| match issue {
+                ValidationIssue::UndeclaredReference { line_type, id, .. } => Some((line_type, id)),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(
+            vec![
+                ("Link", "12".into()),
+                ("Containment", "13".into()),
+                ("Path", "15".into())
+            ],
+            ids
+        );
+    }
+
+    #[test]
+    fn can_check_declaration_order() {
+        let parser: GFA2Parser<BString, ()> = GFA2Parser::new();
+        let errors = parser
+            .check_declaration_order("./tests/gfa2_files/unordered.gfa")
+            .unwrap();
+        let error = |line, segment: &str, declared_at| OrderError {
+            line,
+            segment: segment.into(),
+            declared_at,
+        };
+        // the segment 4 is never declared, so it's not an order error
+        assert_eq!(
+            vec![error(3, "2", 5), error(4, "3", 7), error(6, "3", 7)],
+            errors
+        );
+
+        let errors = parser
+            .check_declaration_order("./tests/gfa2_files/spec_q7.gfa")
+            .unwrap();
+        assert!(errors.is_empty());
+        assert!(parser
+            .check_declaration_order("./tests/gfa2_files/not_a_file.gfa")
+            .is_err());
+        assert!(matches!(
+            parser.check_declaration_order("./tests/gfa2_files/extension_error.txt"),
+            Err(ParseError::ExtensionError(_))
+        ));
+    }
+
+    #[test]
+    fn can_validate_utf8() {
+        let mut gfa2: GFA2<BString, OptionalFields> = GFA2::new();
+        let mut segment = Segment::new(b"1", b"4", b"AC\xffT");
+        segment.tag = vec![
+            OptField::parse(b"RC:i:4").unwrap(),
+            OptField::new(b"nm", OptFieldVal::Z(BString::from(&b"s\xe9q"[..]))),
+        ];
+        gfa2.segments.push(Segment::new(b"2", b"4", b"ACGT"));
+        gfa2.segments.push(segment);
+        gfa2.groups_u.push(GroupU::new(
+            BString::from(&b"u\xff"[..]),
+            "1 2".into(),
+            vec![],
+        ));
+
+        let error = |line_type, index, field: &str, valid_up_to| Utf8Error {
+            line_type,
+            index,
+            field: field.to_string(),
+            valid_up_to,
+        };
+        assert_eq!(
+            vec![
+                error("Segment", 1, "sequence", 2),
+                error("Segment", 1, "nm", 1),
+                error("U-Group", 0, "id", 1),
+            ],
+            gfa2.validate_utf8()
+        );
+
+        let parser: GFA2Parser<BString, OptionalFields> = GFA2Parser::new();
+        let gfa2 = parser.parse_file("./tests/gfa2_files/spec_q7.gfa").unwrap();
+        assert!(gfa2.validate_utf8().is_empty());
+    }
+
+    #[test]
+    fn unreadable_file() {
+        let parser: GFA2Parser<BString, ()> = GFA2Parser::new();
+        let errors = parser.validate_file("./tests/gfa2_files/not_a_file.gfa");
+        assert_eq!(1, errors.len());
+        assert_eq!(0, errors[0].line);
+
+        let errors = parser.validate_file("./tests/gfa2_files/extension_error.txt");
+        assert_eq!(1, errors.len());
+        assert!(matches!(errors[0].issue, ValidationIssue::Unreadable(_)));
+    }
+}
@@ -4,9 +4,10 @@
 /// with the format GFA2 the optional field tag is been replaced by a
 /// simple tag element with 0 or N occurencies.
 /// So, I don't think this file could be useful as the original.
-use bstr::BString;
+use bstr::{BStr, BString, ByteSlice};
 use lazy_static::lazy_static;
 use regex::bytes::Regex;
+use serde::{Deserialize, Serialize};
 
 /// These type aliases are useful for configuring the parsers, as the
 /// type of the optional field container must be given when creating a
@@ -16,7 +17,7 @@ pub type NoOptionalFields = ();
 
 /// An optional field a la SAM. Identified by its tag, which is any
 /// two characters matching [A-Za-z][A-Za-z0-9].
-#[derive(Debug, Clone, PartialEq, PartialOrd)]
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Serialize, Deserialize, Hash)]
 pub struct OptField {
     pub tag: [u8; 2],
     pub value: OptFieldVal,
@@ -26,7 +27,7 @@ pub struct OptField {
 /// `B` type, which denotes either an integer or float array, is split
 /// in two variants, and they ignore the size modifiers in the spec,
 /// instead always holding i64 or f32.
-#[derive(Debug, Clone, PartialEq, PartialOrd)]
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Serialize, Deserialize, Hash)]
 pub enum OptFieldVal {
     Z(BString),
     I(BString),
@@ -57,7 +58,8 @@ impl OptField {
 
     /// Parses the header and optional fields from a bytestring in the format\
     /// ```<Header> <- {VN:Z:2.0}\t{TS:i:[-+]?[0-9]+}\t<tag>*```
-    /// ```<tag> <- <TAG>:<TYPE>:<VALUE> <- [A-Za-z0-9][A-Za-z0-9]:[ABHJZif]:[ -~]*```
+    /// ```<tag> <- <TAG>:<TYPE>:<VALUE> <- [A-Za-z0-9][A-Za-z0-9]:[ABHJZif]:[ -~]*```\
+    /// Returns None if the field is malformed
     pub fn parse(input: &[u8]) -> Option<Self> {
         lazy_static! {
             static ref RE: Regex =
@@ -68,6 +70,12 @@ impl OptField {
 
         let o_tag = input.get(0..=1)?;
         let o_type = input.get(3)?;
+        if !o_tag.iter().all(u8::is_ascii_alphanumeric)
+            || input[2] != b':'
+            || input.get(4) != Some(&b':')
+        {
+            return None;
+        }
 
         let o_val = match o_type {
             b'A' => RE.find(input).map(|s| s.as_bytes().into()).map(A),
@@ -84,6 +92,273 @@ impl OptField {
     }
 }
 
+impl OptFieldVal {
+    /// Returns the bytes stored in the field, whatever its type
+    fn bytes(&self) -> &BString {
+        use OptFieldVal::*;
+
+        match self {
+            A(x) | I(x) | F(x) | Z(x) | J(x) | H(x) | B(x) => x,
+        }
+    }
+
+    /// Returns the type char of the value (e.g. ```i``` for an integer)
+    fn type_char(&self) -> u8 {
+        use OptFieldVal::*;
+
+        match self {
+            A(_) => b'A',
+            I(_) => b'i',
+            F(_) => b'f',
+            Z(_) => b'Z',
+            J(_) => b'J',
+            H(_) => b'H',
+            B(_) => b'B',
+        }
+    }
+
+    /// Returns the bytes stored in the field without the
+    /// ```<TAG>:<TYPE>:``` prefix (that is kept by OptField::parse),
+    /// if the type of the prefix matches the one of the field
+    fn unprefixed(&self) -> &[u8] {
+        let bytes = self.bytes();
+        match bytes.get(0..5) {
+            Some([t0, t1, b':', t, b':'])
+                if t0.is_ascii_alphanumeric()
+                    && t1.is_ascii_alphanumeric()
+                    && *t == self.type_char() =>
+            {
+                &bytes[5..]
+            }
+            _ => bytes,
+        }
+    }
+
+    /// Parses the unprefixed value of the field as a number of type T
+    fn parse_number<T: std::str::FromStr>(&self) -> Option<T> {
+        self.unprefixed().to_str().ok()?.parse().ok()
+    }
+
+    /// Parses the value of an `f` (float) or `i` (integer) field as a
+    /// float of type T
+    fn as_float<T: std::str::FromStr>(&self) -> Option<T> {
+        match self {
+            OptFieldVal::F(_) | OptFieldVal::I(_) => self.parse_number(),
+            _ => None,
+        }
+    }
+
+    /// Parses the value of an `i` (integer) field, returning None if
+    /// the field has another type or it's not a valid integer
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// let field = OptField::parse(b"LN:i:123").unwrap();
+    /// assert_eq!(Some(123), field.value.as_i64());
+    /// ```
+    pub fn as_i64(&self) -> Option<i64> {
+        match self {
+            OptFieldVal::I(_) => self.parse_number(),
+            _ => None,
+        }
+    }
+
+    /// Parses the value of an `f` (float) or `i` (integer) field,
+    /// returning None if the field has another type or it's not a
+    /// valid number
+    pub fn as_f32(&self) -> Option<f32> {
+        self.as_float()
+    }
+
+    /// Parses the value of a `B` (array) field holding integers, in the
+    /// ```[cCsSiI](,[-+]?[0-9]+)*``` format, returning None if the field
+    /// has another type, it's an array of floats or any of its elements
+    /// is not a valid integer
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// let field = OptField::parse(b"ar:B:i,1,-2,3").unwrap();
+    /// assert_eq!(Some(vec![1, -2, 3]), field.value.as_int_array());
+    /// ```
+    pub fn as_int_array(&self) -> Option<Vec<i64>> {
+        match self {
+            OptFieldVal::B(_) => {
+                let mut values = self.unprefixed().split_str(b",");
+                match values.next()? {
+                    b"c" | b"C" | b"s" | b"S" | b"i" | b"I" => values
+                        .map(|value| value.to_str().ok()?.parse().ok())
+                        .collect(),
+                    _ => None,
+                }
+            }
+            _ => None,
+        }
+    }
+}
+
+impl OptField {
+    /// Returns the value of the field without the ```<TAG>:<TYPE>:```
+    /// prefix (that is kept by OptField::parse)
+    pub fn value_bytes(&self) -> &[u8] {
+        self.value.unprefixed()
+    }
+
+    /// Returns the number of bytes used by the field, counting both
+    /// the field itself and the bytes of its value
+    pub fn memory_size(&self) -> usize {
+        std::mem::size_of::<OptField>() + self.value.bytes().capacity()
+    }
+
+    /// Parses the value of a `J` (JSON) field, returning None if the
+    /// field has another type or it's not valid JSON
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// let field = OptField::parse(b"vo:J:{\"labels\":false}").unwrap();
+    /// assert_eq!(
+    ///     Some(serde_json::json!({"labels": false})),
+    ///     field.as_json()
+    /// );
+    /// ```
+    pub fn as_json(&self) -> Option<serde_json::Value> {
+        match self.value {
+            OptFieldVal::J(_) => serde_json::from_slice(self.value_bytes()).ok(),
+            _ => None,
+        }
+    }
+}
+
+/// The predefined tags of the GFA1 and GFA2 specs, with the type that
+/// their values must have
+const RESERVED_TAGS: [([u8; 2], u8); 11] = [
+    (*b"VN", b'Z'),
+    (*b"TS", b'i'),
+    (*b"LN", b'i'),
+    (*b"RC", b'i'),
+    (*b"FC", b'i'),
+    (*b"KC", b'i'),
+    (*b"SH", b'H'),
+    (*b"UR", b'Z'),
+    (*b"MQ", b'i'),
+    (*b"NM", b'i'),
+    (*b"ID", b'Z'),
+];
+
+/// A problem found when validating an optional field, or all the
+/// optional fields of a line
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TagError {
+    /// A predefined tag (e.g. `LN`) has a type different from the
+    /// one of the spec.
+    WrongType {
+        tag: BString,
+        expected: char,
+        found: char,
+    },
+    /// The value doesn't match the format of its type. Includes the tag
+    /// and the value.
+    InvalidValue { tag: BString, value: BString },
+    /// The same tag is used more than once in the same line.
+    DuplicateTag(BString),
+}
+
+impl std::fmt::Display for TagError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TagError::WrongType {
+                tag,
+                expected,
+                found,
+            } => write!(
+                f,
+                "Tag `{}` should have type `{}` but has type `{}`",
+                tag, expected, found
+            ),
+            TagError::InvalidValue { tag, value } => {
+                write!(f, "Tag `{}` has an invalid value `{}`", tag, value)
+            }
+            TagError::DuplicateTag(tag) => write!(f, "Tag `{}` is used more than once", tag),
+        }
+    }
+}
+
+impl std::error::Error for TagError {}
+
+impl OptField {
+    /// Returns the type char of the field (e.g. ```i``` for an integer)
+    pub fn type_char(&self) -> u8 {
+        self.value.type_char()
+    }
+
+    /// Checks that the value of the field matches the format of its
+    /// type, as defined by the SAM and GFA specs:\
+    /// ```A``` a single printable char, ```i``` an integer, ```f``` a
+    /// float, ```Z``` printable chars (spaces included), ```J``` a JSON
+    /// value without tabs or newlines, ```H``` an even number of hex
+    /// digits and ```B``` a ```[cCsSiIf]``` subtype followed by a comma
+    /// separated list of numbers of that subtype.\
+    /// The predefined tags (e.g. ```LN```) must also have the type of
+    /// the spec
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// assert!(OptField::parse(b"LN:i:123").unwrap().validate().is_ok());
+    /// assert!(OptField::parse(b"LN:Z:123").unwrap().validate().is_err());
+    /// assert!(OptField::parse(b"xx:H:1AF").unwrap().validate().is_err());
+    /// ```
+    pub fn validate(&self) -> Result<(), TagError> {
+        lazy_static! {
+            static ref INT: Regex = Regex::new(r"(?-u)^[-+]?[0-9]+$").unwrap();
+            static ref FLOAT: Regex =
+                Regex::new(r"(?-u)^[-+]?[0-9]*\.?[0-9]+([eE][-+]?[0-9]+)?$").unwrap();
+            static ref HEX: Regex = Regex::new(r"(?-u)^([0-9A-F][0-9A-F])*$").unwrap();
+            static ref ARRAY: Regex = Regex::new(
+                r"(?-u)^([cCsSiI](,[-+]?[0-9]+)*|f(,[-+]?[0-9]*\.?[0-9]+([eE][-+]?[0-9]+)?)*)$"
+            )
+            .unwrap();
+        }
+
+        let tag = self.tag.as_bstr();
+        let found = self.type_char();
+        if let Some((_, expected)) = RESERVED_TAGS.iter().find(|(t, _)| *t == self.tag) {
+            if *expected != found {
+                return Err(TagError::WrongType {
+                    tag: tag.into(),
+                    expected: *expected as char,
+                    found: found as char,
+                });
+            }
+        }
+
+        let value = self.value_bytes();
+        let printable = |b: &u8| (b' '..=b'~').contains(b);
+        let valid = match found {
+            b'A' => matches!(value, [c] if printable(c) && *c != b' '),
+            b'i' => INT.is_match(value),
+            b'f' => FLOAT.is_match(value),
+            b'Z' => value.iter().all(printable),
+            b'J' => {
+                value.iter().all(printable)
+                    && serde_json::from_slice::<serde_json::Value>(value).is_ok()
+            }
+            b'H' => HEX.is_match(value),
+            _ => ARRAY.is_match(value),
+        };
+        if valid {
+            Ok(())
+        } else {
+            Err(TagError::InvalidValue {
+                tag: tag.into(),
+                value: value.into(),
+            })
+        }
+    }
+}
+
 /// The Display implementation produces spec-compliant strings in the
 /// ```<TAG>:<TYPE>:<VALUE>``` format, and can be parsed back using
 /// OptField::parse().
@@ -103,6 +378,15 @@ impl std::fmt::Display for OptField {
     }
 }
 
+/// writes every optional field preceded by a tab, as in the
+/// ```<tag>*``` part of a line, without allocating
+pub(crate) fn write_tags(f: &mut std::fmt::Formatter<'_>, fields: &[OptField]) -> std::fmt::Result {
+    for tag in fields {
+        write!(f, "\t{}", tag)?;
+    }
+    Ok(())
+}
+
 /// The OptFields trait describes how to parse, store, and query
 /// optional fields. Each of the GFA line types and the GFA struct
 /// itself are generic over the optional fields, so the choice of
@@ -112,10 +396,26 @@ pub trait OptFields: Sized + Default + Clone {
     /// Return the optional field with the given tag, if it exists.
     fn get_field(&self, tag: &[u8]) -> Option<&OptField>;
 
-    /// Return a slice over all optional fields. NB: This may be
-    /// replaced by an iterator or something else in the future
+    /// Return a slice over all optional fields, borrowed so that they
+    /// can be written without being cloned
     fn fields(&self) -> &[OptField];
 
+    /// Return a copy of the optional fields sorted by their tag, so
+    /// that the same fields written in a different order compare equal.
+    /// Fields with the same tag keep their relative order.\
+    /// The default implementation parses the sorted fields again, in the
+    /// <TAG>:<TYPE>:<VALUE> format, to build the new collection
+    fn sorted(&self) -> Self {
+        let mut fields: Vec<&OptField> = self.fields().iter().collect();
+        fields.sort_by_key(|o| o.tag);
+        Self::parse(fields.into_iter().map(|o| {
+            let mut bytes = o.tag.to_vec();
+            bytes.extend_from_slice(&[b':', o.type_char(), b':']);
+            bytes.extend_from_slice(o.value_bytes());
+            bytes
+        }))
+    }
+
     /// Given an iterator over bytestrings, each expected to hold one
     /// optional field (in the <TAG>:<TYPE>:<VALUE> format), parse
     /// them as optional fields to create a collection. Returns `Self`
@@ -125,6 +425,80 @@ pub trait OptFields: Sized + Default + Clone {
     where
         T: IntoIterator,
         T::Item: AsRef<[u8]>;
+
+    /// Like parse, but also returns the fields that couldn't be parsed
+    /// as optional fields (and so have been skipped)
+    fn parse_with_invalid<T>(input: T) -> (Self, Vec<BString>)
+    where
+        T: IntoIterator,
+        T::Item: AsRef<[u8]>,
+    {
+        let (valid, invalid): (Vec<BString>, Vec<BString>) = input
+            .into_iter()
+            .map(|f| BString::from(f.as_ref()))
+            .partition(|f| OptField::parse(f).is_some());
+        (Self::parse(valid), invalid)
+    }
+
+    /// Validates every optional field with OptField::validate, and
+    /// checks that no tag is used more than once in the same line,
+    /// returning the first problem found
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// for segment in gfa2.segments.iter() {
+    ///     if let Err(err) = segment.tag.validate() {
+    ///         println!("segment {}: {}", segment.id, err);
+    ///     }
+    /// }
+    /// ```
+    fn validate(&self) -> Result<(), TagError> {
+        let fields = self.fields();
+        for (i, field) in fields.iter().enumerate() {
+            field.validate()?;
+            if fields[..i].iter().any(|other| other.tag == field.tag) {
+                return Err(TagError::DuplicateTag(field.tag.as_bstr().into()));
+            }
+        }
+        Ok(())
+    }
+
+    /// Return the value of the optional field with the given tag, if
+    /// it exists
+    fn get_tag(&self, key: &[u8; 2]) -> Option<&OptFieldVal> {
+        self.get_field(key).map(|field| &field.value)
+    }
+
+    /// Return the value of the `i` (integer) optional field with the
+    /// given tag, None if it doesn't exist, has another type or is not
+    /// a valid integer
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// let length = segment.tag.get_tag_int(b"LN");
+    /// ```
+    fn get_tag_int(&self, key: &[u8; 2]) -> Option<i64> {
+        self.get_tag(key)?.as_i64()
+    }
+
+    /// Return the value of the `f` (float) or `i` (integer) optional
+    /// field with the given tag, None if it doesn't exist, has another
+    /// type or is not a valid number
+    fn get_tag_float(&self, key: &[u8; 2]) -> Option<f64> {
+        self.get_tag(key)?.as_float()
+    }
+
+    /// Return the value of the `Z` (string) or `A` (char) optional field
+    /// with the given tag, None if it doesn't exist or has another type
+    fn get_tag_string(&self, key: &[u8; 2]) -> Option<&BStr> {
+        let field = self.get_field(key)?;
+        match field.value {
+            OptFieldVal::Z(_) | OptFieldVal::A(_) => Some(field.value_bytes().as_bstr()),
+            _ => None,
+        }
+    }
 }
 
 /// This implementation is useful for performance if we don't actually
@@ -139,18 +513,31 @@ impl OptFields for () {
         &[]
     }
 
+    fn sorted(&self) -> Self {}
+
     fn parse<T>(_input: T) -> Self
     where
         T: IntoIterator,
         T::Item: AsRef<[u8]>,
     {
     }
+
+    fn parse_with_invalid<T>(_input: T) -> (Self, Vec<BString>)
+    where
+        T: IntoIterator,
+        T::Item: AsRef<[u8]>,
+    {
+        ((), vec![])
+    }
 }
 
 /// Stores all the optional fields in a vector. `get_field` simply
 /// uses std::iter::Iterator::find(), but as there are only a
 /// relatively small number of optional fields in practice, it should
-/// be efficient enough.
+/// be efficient enough.\
+/// The fields are kept in the same order as they appear in the line,
+/// duplicated tags included, so they can be written back unchanged;
+/// when a tag is duplicated `get_field` returns its first occurrence.
 impl OptFields for Vec<OptField> {
     fn get_field(&self, tag: &[u8]) -> Option<&OptField> {
         self.iter().find(|o| o.tag == tag)
@@ -160,6 +547,12 @@ impl OptFields for Vec<OptField> {
         self.as_slice()
     }
 
+    fn sorted(&self) -> Self {
+        let mut sorted = self.clone();
+        sorted.sort_by_key(|o| o.tag);
+        sorted
+    }
+
     fn parse<T>(input: T) -> Self
     where
         T: IntoIterator,
@@ -170,4 +563,238 @@ impl OptFields for Vec<OptField> {
             .filter_map(|f| OptField::parse(f.as_ref()))
             .collect()
     }
+
+    fn parse_with_invalid<T>(input: T) -> (Self, Vec<BString>)
+    where
+        T: IntoIterator,
+        T::Item: AsRef<[u8]>,
+    {
+        let mut fields = vec![];
+        let mut invalid = vec![];
+        for f in input {
+            match OptField::parse(f.as_ref()) {
+                Some(field) => fields.push(field),
+                None => invalid.push(f.as_ref().into()),
+            }
+        }
+        (fields, invalid)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn can_parse_json_field() {
+        let field = OptField::parse(b"vo:J:{\"labels\":false,\"n\":[1,2]}").unwrap();
+        assert_eq!(b"{\"labels\":false,\"n\":[1,2]}", field.value_bytes());
+        assert_eq!(
+            Some(serde_json::json!({"labels": false, "n": [1, 2]})),
+            field.as_json()
+        );
+
+        // the value can also be stored without the prefix
+        let field = OptField::new(b"vo", OptFieldVal::J("[1,2]".into()));
+        assert_eq!(Some(serde_json::json!([1, 2])), field.as_json());
+
+        let not_json = OptField::parse(b"zz:Z:{}").unwrap();
+        assert_eq!(None, not_json.as_json());
+        let invalid = OptField::parse(b"vo:J:{labels}").unwrap();
+        assert_eq!(None, invalid.as_json());
+    }
+
+    #[test]
+    fn optional_fields_keep_order_and_duplicates() {
+        let fields = ["zz:Z:first", "aa:i:1", "zz:Z:second"];
+        let tags: OptionalFields = OptFields::parse(fields.iter());
+
+        let parsed: Vec<String> = tags.fields().iter().map(|t| t.to_string()).collect();
+        assert_eq!(fields.to_vec(), parsed);
+        assert_eq!(
+            Some(&OptFieldVal::Z("zz:Z:first".into())),
+            tags.get_field(b"zz").map(|t| &t.value)
+        );
+    }
+
+    #[test]
+    fn optional_fields_sorted_by_tag() {
+        let tags: OptionalFields = OptFields::parse(["zz:Z:2", "aa:i:1", "zz:Z:1"].iter());
+        let other: OptionalFields = OptFields::parse(["aa:i:1", "zz:Z:2", "zz:Z:1"].iter());
+        assert_ne!(tags, other);
+        assert_eq!(tags.sorted(), other.sorted());
+
+        let parsed: Vec<String> = tags.sorted().iter().map(|t| t.to_string()).collect();
+        assert_eq!(vec!["aa:i:1", "zz:Z:2", "zz:Z:1"], parsed);
+    }
+
+    /// an implementor that relies on the default methods of OptFields
+    #[derive(Default, Clone, Debug, PartialEq)]
+    struct TagList(Vec<OptField>);
+
+    impl OptFields for TagList {
+        fn get_field(&self, tag: &[u8]) -> Option<&OptField> {
+            self.0.get_field(tag)
+        }
+
+        fn fields(&self) -> &[OptField] {
+            &self.0
+        }
+
+        fn parse<T>(input: T) -> Self
+        where
+            T: IntoIterator,
+            T::Item: AsRef<[u8]>,
+        {
+            TagList(OptFields::parse(input))
+        }
+    }
+
+    #[test]
+    fn default_sorted_by_tag() {
+        let tags: TagList = OptFields::parse(["zz:Z:2", "aa:i:1", "zz:Z:1"].iter());
+        let mut fields = tags.0.clone();
+        // a field stored without the prefix is sorted too
+        fields.push(OptField::new(b"mm", OptFieldVal::J("[1,2]".into())));
+        let sorted = TagList(fields).sorted();
+
+        let parsed: Vec<String> = sorted.fields().iter().map(|t| t.to_string()).collect();
+        assert_eq!(vec!["aa:i:1", "mm:J:[1,2]", "zz:Z:2", "zz:Z:1"], parsed);
+    }
+
+    #[test]
+    fn default_parse_with_invalid() {
+        let (tags, invalid): (TagList, _) =
+            OptFields::parse_with_invalid(["aa:i:1", "k!:i:3", "zz:Z:2"].iter());
+        assert_eq!(2, tags.fields().len());
+        assert_eq!(vec![BString::from("k!:i:3")], invalid);
+    }
+
+    #[test]
+    fn can_get_typed_tags() {
+        let fields: OptionalFields =
+            OptFields::parse(vec!["LN:i:1329", "dp:f:2.5", "nm:Z:chr1", "st:A:+"]);
+
+        assert_eq!(
+            Some(&OptFieldVal::I("LN:i:1329".into())),
+            fields.get_tag(b"LN")
+        );
+        assert_eq!(Some(1329), fields.get_tag_int(b"LN"));
+        assert_eq!(None, fields.get_tag_int(b"dp"));
+        assert_eq!(Some(2.5), fields.get_tag_float(b"dp"));
+        assert_eq!(Some(1329.0), fields.get_tag_float(b"LN"));
+        assert_eq!(
+            Some("chr1".as_bytes().as_bstr()),
+            fields.get_tag_string(b"nm")
+        );
+        assert_eq!(Some("+".as_bytes().as_bstr()), fields.get_tag_string(b"st"));
+        assert_eq!(None, fields.get_tag_string(b"LN"));
+        assert_eq!(None, fields.get_tag(b"xx"));
+        assert_eq!(None, ().get_tag_int(b"LN"));
+    }
+
+    #[test]
+    fn can_convert_values() {
+        let field = |f: &str| OptField::parse(f.as_bytes()).unwrap().value;
+        assert_eq!(Some(123), field("LN:i:123").as_i64());
+        assert_eq!(Some(-4), field("xx:i:-4").as_i64());
+        assert_eq!(Some(123), OptFieldVal::I("123".into()).as_i64());
+        assert_eq!(None, field("dp:f:2.5").as_i64());
+        assert_eq!(None, field("xx:i:abc").as_i64());
+
+        assert_eq!(Some(2.5), field("dp:f:2.5").as_f32());
+        assert_eq!(Some(123.0), field("LN:i:123").as_f32());
+        assert_eq!(Some(1.0e-3), OptFieldVal::F("1e-3".into()).as_f32());
+        assert_eq!(None, field("nm:Z:2.5").as_f32());
+
+        assert_eq!(Some(vec![1, -2, 3]), field("ar:B:i,1,-2,3").as_int_array());
+        assert_eq!(Some(vec![7]), OptFieldVal::B("C,7".into()).as_int_array());
+        assert_eq!(Some(vec![]), field("ar:B:s").as_int_array());
+        assert_eq!(None, field("ar:B:f,1.5,2").as_int_array());
+        assert_eq!(None, field("ar:B:i,1,x").as_int_array());
+        assert_eq!(None, field("ar:B:").as_int_array());
+        assert_eq!(None, field("LN:i:123").as_int_array());
+    }
+
+    #[test]
+    fn can_validate_fields() {
+        let validate = |f: &str| OptField::parse(f.as_bytes()).unwrap().validate();
+        let valid = [
+            "LN:i:123",
+            "xx:i:-4",
+            "dp:f:2.5",
+            "dp:f:-.5e3",
+            "st:A:+",
+            "nm:Z:a name",
+            "VN:Z:2.0",
+            "vo:J:{\"a\":[1,2]}",
+            "SH:H:1AF0",
+            "xx:H:",
+            "ar:B:i,1,-2",
+            "ar:B:f,1.5,2",
+            "ar:B:C",
+        ];
+        for field in valid.iter() {
+            assert_eq!(Ok(()), validate(field), "{}", field);
+        }
+
+        let invalid = [
+            "xx:i:1.5",
+            "dp:f:1e",
+            "st:A:ab",
+            "st:A: ",
+            "vo:J:{a}",
+            "xx:H:1AF",
+            "xx:H:1g",
+            "ar:B:i,1.5",
+            "ar:B:x,1",
+            "ar:B:",
+            "ar:B:i,",
+        ];
+        for field in invalid.iter() {
+            assert!(
+                matches!(validate(field), Err(TagError::InvalidValue { .. })),
+                "{}",
+                field
+            );
+        }
+
+        assert_eq!(
+            Err(TagError::WrongType {
+                tag: "LN".into(),
+                expected: 'i',
+                found: 'Z'
+            }),
+            validate("LN:Z:123")
+        );
+        // the value can also be stored without the prefix
+        let field = OptField::new(b"xx", OptFieldVal::H("0a".into()));
+        assert!(field.validate().is_err());
+    }
+
+    #[test]
+    fn can_validate_line_fields() {
+        let tags: OptionalFields = OptFields::parse(["LN:i:1", "xx:Z:a", "yy:f:1"].iter());
+        assert_eq!(Ok(()), tags.validate());
+        assert_eq!(Ok(()), ().validate());
+
+        let tags: OptionalFields = OptFields::parse(["xx:Z:a", "LN:i:1", "xx:Z:b"].iter());
+        assert_eq!(Err(TagError::DuplicateTag("xx".into())), tags.validate());
+        let tags: OptionalFields = OptFields::parse(["LN:i:1", "xx:A:ab"].iter());
+        assert!(tags.validate().is_err());
+    }
+
+    #[test]
+    fn invalid_optional_field_is_skipped() {
+        let fields = ["aa:i:1", "z!:Z:typo", "zz:Q:typo", "bb", "cc:Z:last"];
+        let (tags, invalid): (OptionalFields, _) = OptFields::parse_with_invalid(fields.iter());
+
+        let parsed: Vec<String> = tags.fields().iter().map(|t| t.to_string()).collect();
+        assert_eq!(vec!["aa:i:1", "cc:Z:last"], parsed);
+        assert_eq!(
+            vec![BString::from("z!:Z:typo"), "zz:Q:typo".into(), "bb".into()],
+            invalid
+        );
+        assert_eq!(tags, <OptionalFields as OptFields>::parse(fields.iter()));
+    }
 }
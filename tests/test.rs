@@ -3,7 +3,7 @@ use gfa2::{
     gfa1::GFA,
     gfa2::GFA2,
     parser_gfa1::{GFAParser, GFAParserLineIter},
-    parser_gfa2::GFA2Parser,
+    parser_gfa2::{GFA2Parser, GFA2ParserLineIter, ParseError},
     tag::OptionalFields,
 };
 
@@ -94,6 +94,22 @@ fn extension_error() {
     }
 }
 
+#[test]
+fn extension_error_message() {
+    let parser: GFA2Parser<usize, ()> = GFA2Parser::new();
+    match parser.parse_file("./tests/gfa2_files/extension_error.txt") {
+        Err(ParseError::ExtensionError(ext)) => assert_eq!("txt", ext),
+        _ => panic!("the extension .txt should be rejected"),
+    }
+    let err = parser
+        .parse_file("./tests/gfa2_files/extension_error.txt")
+        .unwrap_err();
+    assert_eq!(
+        "unsupported extension '.txt', expected .gfa or .gfa2",
+        err.to_string()
+    );
+}
+
 #[test]
 fn can_parse_gfa2_graph() {
     let parser: GFA2Parser<BString, OptionalFields> = GFA2Parser::new();
@@ -220,6 +236,53 @@ fn can_parse_gfa_lines() {
     println!("{}", gfa);
 }
 
+#[test]
+fn gfa_display_round_trip() {
+    let parser = GFAParser::new();
+    let gfa: GFA<BString, OptionalFields> =
+        parser.parse_file(&"./tests/gfa1_files/lil.gfa").unwrap();
+
+    // the lines are written grouped by type, so only their order changes
+    let original = std::fs::read_to_string("./tests/gfa1_files/lil.gfa").unwrap();
+    let mut original: Vec<&str> = original.lines().collect();
+    let written = gfa.to_string();
+    let mut written: Vec<&str> = written.lines().collect();
+    original.sort_unstable();
+    written.sort_unstable();
+    assert_eq!(original, written);
+}
+
+#[test]
+fn can_parse_written_gfa2_from_reader() {
+    let parser: GFA2Parser<BString, OptionalFields> = GFA2Parser::new();
+    let gfa2 = parser.parse_file("./tests/gfa2_files/sample2.gfa").unwrap();
+
+    // the graph is written in a buffer and read back, without a file
+    let written = std::io::Cursor::new(gfa2.to_string().into_bytes());
+    assert_eq!(gfa2, parser.parse_reader(written).unwrap());
+}
+
+#[test]
+fn serde_round_trip() {
+    let parser: GFA2Parser<BString, OptionalFields> = GFA2Parser::new();
+    let gfa2 = parser.parse_file(&"./tests/gfa2_files/data.gfa").unwrap();
+    let json = serde_json::to_string(&gfa2).unwrap();
+    let reloaded: GFA2<BString, OptionalFields> = serde_json::from_str(&json).unwrap();
+    assert_eq!(gfa2, reloaded);
+
+    let parser: GFAParser<usize, OptionalFields> = GFAParser::new();
+    let gfa = parser.parse_file(&"./tests/gfa1_files/lil.gfa").unwrap();
+    let json = serde_json::to_string(&gfa).unwrap();
+    let reloaded: GFA<usize, OptionalFields> = serde_json::from_str(&json).unwrap();
+    assert_eq!(gfa, reloaded);
+
+    // a line serialized by reference is read back as an owned line
+    let line = gfa2.lines_iter().next().unwrap();
+    let json = serde_json::to_string(&line).unwrap();
+    let reloaded: gfa2::gfa2::Line<BString, OptionalFields> = serde_json::from_str(&json).unwrap();
+    assert_eq!(line.to_string(), reloaded.as_ref().to_string());
+}
+
 #[test]
 fn gfa_usize_parser() {
     let usize_parser: GFAParser<usize, OptionalFields> = GFAParser::new();
@@ -228,6 +291,26 @@ fn gfa_usize_parser() {
     assert!(!usize_gfa.is_err())
 }
 
+#[test]
+fn usize_display_round_trip() {
+    let parser: GFA2Parser<BString, OptionalFields> = GFA2Parser::new();
+    let gfa2 = parser.parse_file("./tests/gfa2_files/sample2.gfa").unwrap();
+    let usize_parser: GFA2Parser<usize, OptionalFields> = GFA2Parser::new();
+    let usize_gfa2 = usize_parser
+        .parse_file("./tests/gfa2_files/sample2.gfa")
+        .unwrap();
+    // the usize ids are written back with their names
+    assert_eq!(gfa2.to_string(), usize_gfa2.to_string());
+
+    let parser: GFAParser<BString, OptionalFields> = GFAParser::new();
+    let gfa = parser.parse_file("./tests/gfa1_files/diatom.gfa").unwrap();
+    let usize_parser: GFAParser<usize, OptionalFields> = GFAParser::new();
+    let usize_gfa = usize_parser
+        .parse_file("./tests/gfa1_files/diatom.gfa")
+        .unwrap();
+    assert_eq!(gfa.to_string(), usize_gfa.to_string());
+}
+
 #[test]
 fn can_parse_medium_file_gfa1() {
     // parsing file and counting items, about 3 seconds (WITH PROGRESSBAR)
@@ -282,6 +365,7 @@ fn can_parse_medium_file_gfa2() {
 fn gfa_parser_line_iter() {
     use {
         bstr::io::BufReadExt,
+        gfa2::gfa2::SegmentId,
         std::{fs::File, io::BufReader},
     };
 
@@ -299,5 +383,66 @@ fn gfa_parser_line_iter() {
         })
         .collect::<Vec<_>>();
 
-    assert_eq!(segment_names, (1..=15).into_iter().collect::<Vec<_>>());
+    let expected = (1..=15)
+        .map(|n: usize| usize::parse_id(n.to_string().as_bytes()).unwrap())
+        .collect::<Vec<_>>();
+    assert_eq!(segment_names, expected);
+}
+
+#[test]
+fn gfa2_parser_line_iter_counts_edges() {
+    use {
+        bstr::io::BufReadExt,
+        gfa2::gfa2::Line,
+        std::{fs::File, io::BufReader},
+    };
+
+    let parser: GFA2Parser<BString, ()> = GFA2Parser::new();
+    let gfa2 = parser.parse_file("./tests/gfa2_files/sample2.gfa").unwrap();
+
+    let file = File::open("./tests/gfa2_files/sample2.gfa").unwrap();
+    let lines = BufReader::new(file).byte_lines().map(|x| x.unwrap());
+    let (mut dovetails, mut containments) = (0, 0);
+    for line in GFA2ParserLineIter::from_parser(parser, lines) {
+        if let Ok(Line::Edge(edge)) = line {
+            dovetails += edge.is_dovetail() as usize;
+            containments += edge.is_containment() as usize;
+        }
+    }
+    assert_eq!(
+        gfa2.edges.iter().filter(|e| e.is_dovetail()).count(),
+        dovetails
+    );
+    assert_eq!(
+        gfa2.edges.iter().filter(|e| e.is_containment()).count(),
+        containments
+    );
+    assert!(dovetails > 0);
+}
+
+#[test]
+fn gfa2_parser_line_iter() {
+    use {
+        bstr::io::BufReadExt,
+        std::{fs::File, io::BufReader},
+    };
+
+    let parser: GFA2Parser<BString, ()> = GFA2Parser::new();
+    let file = File::open(&"./tests/gfa2_files/sample2.gfa").unwrap();
+    let lines = BufReader::new(file).byte_lines().map(|x| x.unwrap());
+    let mut parser_iter = GFA2ParserLineIter::from_parser(parser, lines);
+
+    // the comments are returned as errors, without stopping the iteration
+    assert!(parser_iter.next().unwrap().is_err());
+    let segment_names = parser_iter
+        .filter_map(|line| {
+            let line = line.ok()?;
+            let seg = line.some_segment()?;
+
+            Some(seg.id)
+        })
+        .collect::<Vec<_>>();
+
+    assert_eq!(9, segment_names.len());
+    assert_eq!("1", segment_names[0]);
 }